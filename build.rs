@@ -69,6 +69,40 @@ fn main() -> std::io::Result<()> {
         println!("cargo:rerun-if-changed=src/commands/wazuh/dashboards");
     }
 
+    // bundle default yara rules
+    {
+        let mut rules_dir = current_dir()?;
+        rules_dir.push("src/commands/yara/rules");
+
+        let mut include_macros = read_dir(&rules_dir)?
+            .filter_map(Result::ok)
+            .map(|d| {
+                let mut rule_file = rules_dir.clone();
+                rule_file.push(d.path());
+                format!(
+                    r#"("{}", include_str!("{}"))"#,
+                    d.file_name().to_string_lossy(),
+                    rule_file.display()
+                )
+            })
+            .collect::<Vec<_>>();
+
+        include_macros.sort();
+
+        std::fs::write(
+            format!(
+                "{}/yara_default_rules.rs",
+                std::env::var("OUT_DIR").expect("could not find OUT_DIR variable")
+            ),
+            format!(
+                "const YARA_DEFAULT_RULES: &[(&str, &str)] = &[{}];",
+                include_macros.join(",")
+            ),
+        )?;
+
+        println!("cargo:rerun-if-changed=src/commands/yara/rules");
+    }
+
     // bundle OWASP ModSecurity Core Ruleset
     {
         let mut rules_dir = PathBuf::from(
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use serde_json::json;
+
+use crate::utils::{
+    checks::{CheckResult, CheckStep, Troubleshooter, check_fn},
+    plugin::Plugin,
+};
+
+/// Troubleshooter that loads a native plugin and runs whatever check it implements, so
+/// site-specific checks can be added to the daemon via config without rebuilding jj
+///
+/// See [`crate::utils::plugin`] for the ABI a plugin library must implement
+#[derive(clap::Parser, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PluginTroubleshooter {
+    /// Path to the plugin's shared library (.so/.dylib/.dll)
+    #[arg(long, short)]
+    pub library: PathBuf,
+
+    /// JSON configuration passed to the plugin's check function
+    #[arg(long, short, default_value = "null")]
+    pub config: String,
+}
+
+impl Default for PluginTroubleshooter {
+    fn default() -> Self {
+        PluginTroubleshooter {
+            library: PathBuf::new(),
+            config: "null".to_string(),
+        }
+    }
+}
+
+impl Troubleshooter for PluginTroubleshooter {
+    fn display_name(&self) -> &'static str {
+        "Plugin"
+    }
+
+    fn checks<'a>(&'a self) -> eyre::Result<Vec<Box<dyn CheckStep<'a> + 'a>>> {
+        Ok(vec![check_fn("Run plugin check", |_tr| {
+            Ok(self.run_plugin())
+        })])
+    }
+}
+
+impl PluginTroubleshooter {
+    fn run_plugin(&self) -> CheckResult {
+        self.try_run_plugin().unwrap_or_else(|e| {
+            CheckResult::fail(
+                format!("Could not run plugin {}: {e}", self.library.display()),
+                json!({ "library": self.library.display().to_string() }),
+            )
+        })
+    }
+
+    fn try_run_plugin(&self) -> eyre::Result<CheckResult> {
+        let plugin = Plugin::load(&self.library)?;
+        plugin.run_check(&self.config)
+    }
+}
@@ -0,0 +1,140 @@
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+use crate::utils::checks::{CheckResult, Troubleshooter, check_fn};
+
+/// Troubleshooter that runs a small [Rhai](https://rhai.rs) script as a check step, and
+/// optionally a second script as a remediation hook when the check fails (e.g. to restart a
+/// service). Scripts are sandboxed with operation/depth limits and a wall-clock timeout, so a
+/// misbehaving check can't wedge the daemon
+///
+/// A check script should evaluate to either a `bool`, or a map with a `success` bool and a
+/// `message` string, e.g.:
+///
+/// ```rhai
+/// #{ success: false, message: "disk usage above threshold" }
+/// ```
+///
+/// The remediation script's result is recorded alongside the check, but never changes whether
+/// the check itself passed or failed
+#[derive(clap::Parser, serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ScriptTroubleshooter {
+    /// Rhai source run as the check itself
+    #[arg(long, short, default_value = "true")]
+    pub script: String,
+
+    /// Rhai source run only if the check script fails, e.g. to restart a service
+    #[arg(long, short)]
+    pub remediation: Option<String>,
+
+    /// Maximum time to let either script run before it is aborted
+    #[arg(long, short, default_value = "5s")]
+    pub timeout: humantime::Duration,
+}
+
+impl Default for ScriptTroubleshooter {
+    fn default() -> Self {
+        ScriptTroubleshooter {
+            script: "true".to_string(),
+            remediation: None,
+            timeout: Duration::from_secs(5).into(),
+        }
+    }
+}
+
+impl Troubleshooter for ScriptTroubleshooter {
+    fn display_name(&self) -> &'static str {
+        "Script"
+    }
+
+    fn checks<'a>(
+        &'a self,
+    ) -> eyre::Result<Vec<Box<dyn crate::utils::checks::CheckStep<'a> + 'a>>> {
+        Ok(vec![check_fn("Run check script", |_tr| {
+            Ok(self.run_script())
+        })])
+    }
+}
+
+impl ScriptTroubleshooter {
+    fn run_script(&self) -> CheckResult {
+        let result = run_sandboxed(&self.script, *self.timeout)
+            .unwrap_or_else(|e| CheckResult::fail(format!("Check script error: {e}"), json!(null)));
+
+        if result.result_type == crate::utils::checks::CheckResultType::Success {
+            return result;
+        }
+
+        let Some(remediation) = self.remediation.as_deref() else {
+            return result;
+        };
+
+        match run_sandboxed(remediation, *self.timeout) {
+            Ok(remediation_result) => result.merge_overwrite_details(json!({
+                "remediation": remediation_result.log_item,
+            })),
+            Err(e) => result.merge_overwrite_details(json!({
+                "remediation_error": e.to_string(),
+            })),
+        }
+    }
+}
+
+/// Runs `source` in a fresh, sandboxed engine, enforcing `timeout` via a progress callback and
+/// limiting operations/call depth/collection sizes so a script can't hang or exhaust memory
+fn run_sandboxed(source: &str, timeout: Duration) -> eyre::Result<CheckResult> {
+    let mut engine = rhai::Engine::new();
+    engine.set_max_operations(10_000_000);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(1_000_000);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+
+    let deadline = Instant::now() + timeout;
+    engine.on_progress(move |_| {
+        (Instant::now() >= deadline)
+            .then(|| rhai::Dynamic::from("script exceeded its timeout".to_string()))
+    });
+
+    let value = engine
+        .eval::<rhai::Dynamic>(source)
+        .map_err(|e| eyre::eyre!("{e}"))?;
+
+    Ok(interpret_result(value))
+}
+
+/// Turns a script's returned [`rhai::Dynamic`] into a [`CheckResult`]: a bare `bool`, or a map
+/// with `success`/`message` fields
+fn interpret_result(value: rhai::Dynamic) -> CheckResult {
+    if let Some(success) = value.clone().try_cast::<bool>() {
+        return if success {
+            CheckResult::succeed("Script returned true", json!(null))
+        } else {
+            CheckResult::fail("Script returned false", json!(null))
+        };
+    }
+
+    if let Some(map) = value.clone().try_cast::<rhai::Map>() {
+        let success = map
+            .get("success")
+            .is_some_and(|v| v.clone().try_cast::<bool>().unwrap_or(false));
+        let message = map
+            .get("message")
+            .and_then(|v| v.clone().into_string().ok())
+            .unwrap_or_else(|| "Script completed".to_string());
+
+        return if success {
+            CheckResult::succeed(message, json!(null))
+        } else {
+            CheckResult::fail(message, json!(null))
+        };
+    }
+
+    CheckResult::fail(
+        format!("Script returned an unsupported value: {value:?}"),
+        json!(null),
+    )
+}
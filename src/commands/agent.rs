@@ -0,0 +1,138 @@
+use std::process::Command;
+
+use chrono::Utc;
+use clap::Parser;
+use colored::Colorize;
+use eyre::Context;
+use serde_json::json;
+
+use crate::utils::agent::AgentReport;
+
+/// Periodically pushes this host's ports/enum/stat summary to a central `jj serve --agent`
+/// instance, so a fleet of hosts can be triaged from one place instead of logging into each
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Agent {
+    /// Base URL of the central `jj serve --agent` instance (e.g. https://10.0.0.5:8080)
+    #[arg(long, short)]
+    server: String,
+
+    /// Bearer token to authenticate with, matching the server's --agent-token
+    #[arg(long, short)]
+    token: Option<String>,
+
+    /// How often to push a report
+    #[arg(long, short, default_value = "60s")]
+    interval: humantime::Duration,
+
+    /// Push a single report and exit, instead of looping forever
+    #[arg(long)]
+    once: bool,
+
+    /// Skip TLS certificate verification, for talking to a server using a self-signed certificate
+    #[arg(long)]
+    insecure: bool,
+}
+
+impl super::Command for Agent {
+    fn execute(self) -> eyre::Result<()> {
+        let client = reqwest::blocking::Client::builder()
+            .danger_accept_invalid_certs(self.insecure)
+            .build()
+            .context("Could not build HTTP client")?;
+
+        let url = format!("{}/jj-agent/report", self.server.trim_end_matches('/'));
+
+        loop {
+            match build_report()
+                .and_then(|report| push_report(&client, &url, self.token.as_deref(), &report))
+            {
+                Ok(()) => println!("{}", format!("--- Pushed report to {url}").green()),
+                Err(e) => eprintln!("{} Could not push report: {e}", "warning:".yellow()),
+            }
+
+            if self.once {
+                return Ok(());
+            }
+
+            std::thread::sleep(*self.interval);
+        }
+    }
+}
+
+/// Gathers this host's ports/enum/stat summary by re-invoking this same binary, so the reported
+/// data always matches what an operator would see running those commands directly
+fn build_report() -> eyre::Result<AgentReport> {
+    let hostname = crate::utils::qx("hostname")
+        .map(|(_, s)| s.trim().to_string())
+        .unwrap_or_else(|_| "(unknown)".to_string());
+
+    let ports_json =
+        run_jj(&["ports", "--format", "json"]).context("Could not gather ports summary")?;
+    let ports = serde_json::from_str(&ports_json)
+        .context("Could not parse jj ports --format json output")?;
+
+    let enum_summary =
+        run_jj(&["enum", "--no-pager"]).unwrap_or_else(|e| format!("(could not run jj enum: {e})"));
+
+    let stat = crate::utils::system::snapshot()
+        .map(|s| {
+            json!({
+                "cpu_percent": s.cpu_percent,
+                "mem_used_percent": s.mem.used_percent,
+                "disk_free_percent": s.disk.free_percent,
+            })
+        })
+        .unwrap_or_else(|e| json!({ "error": e.to_string() }));
+
+    Ok(AgentReport {
+        hostname,
+        timestamp: Utc::now(),
+        ports,
+        enum_summary,
+        stat,
+    })
+}
+
+/// Re-invoke this binary so we reuse the already-polished output of another `jj` subcommand
+/// instead of duplicating its logic here
+fn run_jj(args: &[&str]) -> eyre::Result<String> {
+    let exe = std::env::current_exe().context("Could not determine path to this binary")?;
+
+    let output = Command::new(&exe)
+        .args(args)
+        .output()
+        .with_context(|| format!("Could not spawn {} {}", exe.display(), args.join(" ")))?;
+
+    if !output.status.success() {
+        eyre::bail!(
+            "jj {} exited with {}: {}",
+            args.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn push_report(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    token: Option<&str>,
+    report: &AgentReport,
+) -> eyre::Result<()> {
+    let mut req = client.post(url).json(report);
+    if let Some(token) = token {
+        req = req.bearer_auth(token);
+    }
+
+    let res = req
+        .send()
+        .context("Could not reach the central jj server")?;
+    if !res.status().is_success() {
+        eyre::bail!("Server responded with {}", res.status());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use std::path::Path;
+
+use colored::Colorize;
+use eyre::{Context, bail};
+use nix::unistd::geteuid;
+
+use crate::utils::{
+    os_version::get_distro,
+    packages::{DownloadSettings, install_apt_packages, install_dnf_packages},
+    qx, system,
+};
+
+/// A curated auditd ruleset: execve logging, watches on security-relevant files under /etc,
+/// and kernel module load/unload tracking. Keyed entries (`-k ...`) let `ausearch -k <key>`
+/// pull just one category back out of the log
+const AUDITD_RULES: &str = r#"-D
+
+-a always,exit -F arch=b64 -S execve -k exec-log
+-a always,exit -F arch=b32 -S execve -k exec-log
+
+-w /etc/passwd -p wa -k identity
+-w /etc/shadow -p wa -k identity
+-w /etc/group -p wa -k identity
+-w /etc/gshadow -p wa -k identity
+-w /etc/sudoers -p wa -k identity
+-w /etc/sudoers.d/ -p wa -k identity
+-w /etc/ssh/sshd_config -p wa -k sshd-config
+-w /etc/crontab -p wa -k cron
+-w /etc/cron.d/ -p wa -k cron
+
+-a always,exit -F arch=b64 -S init_module,finit_module,delete_module -k module-load
+-a always,exit -F arch=b32 -S init_module,finit_module,delete_module -k module-load
+"#;
+
+const RULES_PATH: &str = "/etc/audit/rules.d/jj-hardening.rules";
+
+/// Installs a curated auditd ruleset (execve logging, /etc watches, module load tracking),
+/// loads it with augenrules, and verifies events actually flow to the audit log
+#[derive(clap::Parser, Debug)]
+#[command(version, about)]
+pub struct Auditd {
+    /// Use the download container when installing the audit package to circumvent the host
+    /// based firewall
+    #[arg(long, short = 'd')]
+    use_download_shell: bool,
+
+    /// Use a specific IP address for source NAT when downloading through the container
+    #[arg(long, short = 'I')]
+    sneaky_ip: Option<std::net::Ipv4Addr>,
+
+    /// Report what would change without writing or loading anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl super::Command for Auditd {
+    fn execute(self) -> eyre::Result<()> {
+        if !geteuid().is_root() {
+            bail!("You must be root to install the auditd ruleset");
+        }
+
+        if self.dry_run {
+            println!(
+                "{} Would install auditd, write {RULES_PATH}, and load it with augenrules",
+                "---".blue()
+            );
+            print!("{AUDITD_RULES}");
+            return Ok(());
+        }
+
+        let distro = get_distro()?;
+
+        if !distro.is_rhel_or_deb_based() {
+            bail!("auditd ruleset deployment is only supported on RHEL or Debian based systems");
+        }
+
+        let download_settings = self
+            .use_download_shell
+            .then_some(DownloadSettings::Container {
+                name: None,
+                sneaky_ip: self.sneaky_ip,
+            })
+            .unwrap_or(DownloadSettings::NoContainer);
+
+        println!("{}", "--- Installing auditd...".green());
+
+        if distro.is_deb_based() {
+            install_apt_packages(download_settings, &["auditd", "audispd-plugins"])?;
+        } else {
+            install_dnf_packages(download_settings, &["audit"])?;
+        }
+
+        println!("{}", "--- Writing curated ruleset...".green());
+
+        std::fs::create_dir_all(
+            Path::new(RULES_PATH)
+                .parent()
+                .context("Rules path has no parent directory")?,
+        )?;
+        std::fs::write(RULES_PATH, AUDITD_RULES)
+            .with_context(|| format!("Could not write {RULES_PATH}"))?;
+
+        system("systemctl enable auditd")?;
+        system("systemctl start auditd")?;
+
+        println!("{}", "--- Loading ruleset with augenrules...".green());
+
+        let status = system("augenrules --load")?;
+        if !status.success() {
+            bail!("augenrules exited with {status} while loading the ruleset");
+        }
+
+        self.verify_events_flow()?;
+
+        println!("{}", "--- Auditd ruleset installed and verified!".green());
+
+        Ok(())
+    }
+}
+
+impl Auditd {
+    /// Triggers an execve our ruleset is keyed to watch for, then checks `ausearch` actually
+    /// picked it up, so a silently-broken audit pipeline (disabled, wrong arch, etc.) is caught
+    /// immediately rather than discovered during an incident
+    fn verify_events_flow(&self) -> eyre::Result<()> {
+        println!("{}", "--- Verifying audit events flow...".green());
+
+        qx("/bin/true")?;
+
+        for attempt in 0..5 {
+            if attempt > 0 {
+                std::thread::sleep(std::time::Duration::from_secs(1));
+            }
+
+            let (status, out) = qx("ausearch -k exec-log -ts recent 2>/dev/null")?;
+            if status.success() && out.contains("exec-log") {
+                return Ok(());
+            }
+        }
+
+        bail!("Loaded the ruleset, but no exec-log events showed up in ausearch");
+    }
+}
@@ -56,6 +56,7 @@ use crate::{
 pub use crate::checks::CheckTypes;
 
 mod check_thread;
+mod elastic;
 mod logs;
 mod tui;
 
@@ -282,6 +283,39 @@ pub struct CheckDaemon {
     #[arg(short, long)]
     show_extra_details: bool,
 
+    /// Elasticsearch/OpenSearch URL to bulk-index check results into (e.g.
+    /// https://localhost:10200), such as the one the elk command sets up
+    #[arg(long)]
+    elasticsearch_url: Option<String>,
+
+    /// Index name prefix results are indexed under; a `-YYYY.MM.DD` suffix is appended daily,
+    /// and an index template matching `<prefix>-*` is installed on startup
+    #[arg(long, default_value = "jj-checks")]
+    elasticsearch_index: String,
+
+    /// Username to authenticate to Elasticsearch with
+    #[arg(long, default_value = "elastic")]
+    elasticsearch_username: String,
+
+    /// Password to authenticate to Elasticsearch with
+    #[arg(long)]
+    elasticsearch_password: Option<String>,
+
+    /// Skip TLS certificate verification when contacting Elasticsearch, rather than having to
+    /// distribute the elk command's self-signed CA to every check-daemon host
+    #[arg(long)]
+    elasticsearch_insecure: bool,
+
+    /// In interactive mode, write diagnostic/debug logs here instead of discarding them.
+    /// stdout/stderr aren't safe to write to while the TUI owns the terminal, so without this
+    /// set, interactive mode logs nothing
+    #[arg(long)]
+    debug_log: Option<PathBuf>,
+
+    /// Include debug-level detail in --debug-log
+    #[arg(short, long)]
+    verbose: bool,
+
     #[command(subcommand)]
     daemon_config: DaemonConfigArg,
 }
@@ -310,7 +344,19 @@ pub enum DaemonConfigArg {
 
 impl super::Command for CheckDaemon {
     fn execute(self) -> eyre::Result<()> {
-        let log_config = logs::LogConfig::new(self.logs_ip, self.log_file.clone());
+        let log_config = logs::LogConfig::new(
+            self.logs_ip,
+            self.log_file.clone(),
+            self.elasticsearch_url
+                .clone()
+                .map(|url| elastic::ElasticsearchConfig {
+                    url,
+                    index: self.elasticsearch_index.clone(),
+                    username: self.elasticsearch_username.clone(),
+                    password: self.elasticsearch_password.clone().unwrap_or_default(),
+                    insecure: self.elasticsearch_insecure,
+                }),
+        );
 
         let daemon: RwLock<RuntimeDaemonConfig> = RwLock::new(RuntimeDaemonConfig {
             check_interval: std::time::Duration::from_secs(self.check_interval.into()),
@@ -435,7 +481,36 @@ impl super::Command for CheckDaemon {
     }
 
     fn setup_tracing(&self) -> eyre::Result<()> {
-        // do nothing; let TUI do rendering and handle events
+        // In non-interactive mode, basic_log_runner prints directly; no tracing sink needed.
+        // In interactive mode, the TUI owns the terminal, so tracing must never write to
+        // stdout/stderr - only install a sink if --debug-log gives it somewhere safe to go
+        let (true, Some(debug_log)) = (self.interactive_mode, &self.debug_log) else {
+            return Ok(());
+        };
+
+        use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+        let file = std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(debug_log)
+            .with_context(|| format!("Could not open --debug-log {}", debug_log.display()))?;
+
+        let level = if self.verbose {
+            tracing::Level::DEBUG
+        } else {
+            tracing::Level::INFO
+        };
+
+        tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(file)
+                    .with_ansi(false),
+            )
+            .with(tracing_subscriber::filter::Targets::new().with_target("jj_rs", level))
+            .init();
+
         Ok(())
     }
 }
@@ -0,0 +1,146 @@
+//! Bulk-indexes `TroubleshooterResult` log events into Elasticsearch/OpenSearch, so check
+//! history is queryable from the same Kibana the `elk` command sets up
+
+use eyre::Context;
+
+use super::TroubleshooterResult;
+
+/// Index template installed (if missing) the first time a sink is created, matching
+/// `<index>-*`. Step results are keyed dynamically (`step_1_foo`, `step_2_bar`, ...) by
+/// `TroubleshooterResult`'s serializer, so they're matched with a dynamic template rather than
+/// listed individually
+const INDEX_TEMPLATE: &str = r#"{
+  "index_patterns": ["INDEX_NAME-*"],
+  "template": {
+    "mappings": {
+      "properties": {
+        "timestamp": { "type": "date" },
+        "check_id": { "type": "keyword" },
+        "overall_result": { "type": "keyword" },
+        "overall_result_int": { "type": "integer" }
+      },
+      "dynamic_templates": [
+        {
+          "step_results": {
+            "match": "step_*",
+            "match_mapping_type": "object",
+            "mapping": {
+              "properties": {
+                "name": { "type": "keyword" },
+                "result": {
+                  "properties": {
+                    "timestamp": { "type": "date" },
+                    "result_type": { "type": "keyword" },
+                    "log_item": { "type": "text" },
+                    "extra_details": { "type": "flattened" }
+                  }
+                }
+              }
+            }
+          }
+        }
+      ]
+    }
+  }
+}"#;
+
+/// Configuration for reaching an Elasticsearch/OpenSearch instance, parsed from CLI flags
+pub struct ElasticsearchConfig {
+    pub url: String,
+    pub index: String,
+    pub username: String,
+    pub password: String,
+    pub insecure: bool,
+}
+
+/// Sink that bulk-indexes `TroubleshooterResult`s into Elasticsearch under daily indices named
+/// `<index>-YYYY.MM.DD`
+pub struct ElasticsearchSink {
+    client: reqwest::Client,
+    url: String,
+    index: String,
+    username: String,
+    password: String,
+}
+
+impl ElasticsearchSink {
+    /// Builds a sink and installs its index template, so the index exists with sane mappings
+    /// before the first document is written
+    pub async fn new(config: ElasticsearchConfig) -> eyre::Result<Self> {
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(config.insecure)
+            .build()
+            .context("Could not build Elasticsearch HTTP client")?;
+
+        let sink = Self {
+            client,
+            url: config.url.trim_end_matches('/').to_string(),
+            index: config.index,
+            username: config.username,
+            password: config.password,
+        };
+
+        sink.ensure_index_template().await?;
+
+        Ok(sink)
+    }
+
+    async fn ensure_index_template(&self) -> eyre::Result<()> {
+        let response = self
+            .client
+            .put(format!("{}/_index_template/{}", self.url, self.index))
+            .basic_auth(&self.username, Some(&self.password))
+            .header("content-type", "application/json")
+            .body(INDEX_TEMPLATE.replace("INDEX_NAME", &self.index))
+            .send()
+            .await
+            .context("Could not contact Elasticsearch to install the check index template")?
+            .json::<serde_json::Value>()
+            .await
+            .context("Could not parse Elasticsearch response to the index template upload")?;
+
+        if response.get("acknowledged") != Some(&serde_json::Value::Bool(true)) {
+            eprintln!("Elasticsearch did not acknowledge the check index template: {response}");
+        }
+
+        Ok(())
+    }
+
+    /// Bulk-indexes a batch of results into today's index via the `_bulk` API
+    pub async fn bulk_index(&self, results: &[TroubleshooterResult]) -> eyre::Result<()> {
+        if results.is_empty() {
+            return Ok(());
+        }
+
+        let index_name = format!("{}-{}", self.index, chrono::Utc::now().format("%Y.%m.%d"));
+
+        let mut body = String::new();
+        for result in results {
+            body += &serde_json::to_string(&serde_json::json!({
+                "create": { "_index": index_name }
+            }))?;
+            body.push('\n');
+            body += &serde_json::to_string(result)?;
+            body.push('\n');
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/_bulk", self.url))
+            .basic_auth(&self.username, Some(&self.password))
+            .header("content-type", "application/x-ndjson")
+            .body(body)
+            .send()
+            .await
+            .context("Could not contact Elasticsearch to bulk-index check results")?
+            .json::<serde_json::Value>()
+            .await
+            .context("Could not parse Elasticsearch bulk-index response")?;
+
+        if response.get("errors") == Some(&serde_json::Value::Bool(true)) {
+            eprintln!("Elasticsearch reported errors bulk-indexing check results: {response}");
+        }
+
+        Ok(())
+    }
+}
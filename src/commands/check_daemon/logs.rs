@@ -9,16 +9,25 @@ use tokio::{
     net::TcpStream,
 };
 
-use super::TroubleshooterResult;
+use super::{TroubleshooterResult, elastic};
 
 pub struct LogConfig {
     ip: Option<SocketAddr>,
     file: Option<PathBuf>,
+    elasticsearch: Option<elastic::ElasticsearchConfig>,
 }
 
 impl LogConfig {
-    pub fn new(ip: Option<SocketAddr>, file: Option<PathBuf>) -> Self {
-        Self { ip, file }
+    pub fn new(
+        ip: Option<SocketAddr>,
+        file: Option<PathBuf>,
+        elasticsearch: Option<elastic::ElasticsearchConfig>,
+    ) -> Self {
+        Self {
+            ip,
+            file,
+            elasticsearch,
+        }
     }
 }
 
@@ -59,6 +68,20 @@ async fn get_log_socket(ip: SocketAddr) -> Option<TcpStream> {
     }
 }
 
+async fn get_elastic_sink(
+    config: Option<elastic::ElasticsearchConfig>,
+) -> Option<elastic::ElasticsearchSink> {
+    let config = config?;
+
+    match elastic::ElasticsearchSink::new(config).await {
+        Ok(sink) => Some(sink),
+        Err(e) => {
+            eprintln!("Could not set up Elasticsearch sink: {e}");
+            None
+        }
+    }
+}
+
 pub async fn log_handler_thread(
     config: LogConfig,
     #[cfg(unix)] mut log_pipe: tokio::net::unix::pipe::Receiver,
@@ -88,6 +111,7 @@ pub async fn log_handler_thread(
         Some(f) => get_log_socket(f).await,
         None => None,
     };
+    let elastic_sink = get_elastic_sink(config.elasticsearch).await;
 
     #[cfg(unix)]
     let mut log_buffer = vec![0u8; 65536];
@@ -131,6 +155,8 @@ pub async fn log_handler_thread(
             }
         };
 
+        let mut elastic_batch = vec![];
+
         for msg in msgs {
             // The idea is that other log events can be sent, such as progress updates
             #[allow(irrefutable_let_patterns)]
@@ -157,9 +183,21 @@ pub async fn log_handler_thread(
                 }
             }
 
+            if let LogEvent::Result(r) = &msg
+                && elastic_sink.is_some()
+            {
+                elastic_batch.push(r.clone());
+            }
+
             if let Err(e) = log_event_sender.send(msg).await {
                 eprintln!("Could not dispatch log event: {e}");
             }
         }
+
+        if let Some(ref sink) = elastic_sink
+            && let Err(e) = sink.bulk_index(&elastic_batch).await
+        {
+            eprintln!("Could not bulk-index check results into Elasticsearch: {e}");
+        }
     }
 }
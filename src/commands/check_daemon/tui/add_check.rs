@@ -2178,9 +2178,8 @@ fn handle_wizard<'scope, 'env: 'scope>(
                             .list(Some(dir))?
                             .into_iter()
                             .filter_map(|row| {
-                                eprintln!("Row found: {row}");
+                                tracing::debug!("FTP listing row: {row}");
                                 let listing = parse_file_listing(dir, regex, &row)?;
-                                eprintln!("Here");
                                 Some(
                                     if listing.is_dir {
                                         recursive_list_files(regex, stream, dir)
@@ -2217,7 +2216,7 @@ fn handle_wizard<'scope, 'env: 'scope>(
 
                                         let regex = provide_ftp_listing_regex();
 
-                                        eprintln!("Path list: {path_list:?}");
+                                        tracing::debug!("FTP path list: {path_list:?}");
 
                                         Ok(path_list
                                             .into_iter()
@@ -3314,7 +3313,7 @@ fn handle_wizard<'scope, 'env: 'scope>(
                         .map(|(key, value)| value.parse().map(|v| (key.clone(), v)))
                         .collect::<Result<Map<_, _>, _>>()
                     else {
-                        eprintln!("Could not finalize check configuration (serialization 1)");
+                        tracing::warn!("Could not finalize check configuration (serialization 1)");
                         tui.buffer.clear();
                         return true;
                     };
@@ -3324,7 +3323,7 @@ fn handle_wizard<'scope, 'env: 'scope>(
                     });
 
                     let Ok(parsed) = serde_json::from_value(json) else {
-                        eprintln!("Could not finalize check configuration (serialization 2)");
+                        tracing::warn!("Could not finalize check configuration (serialization 2)");
                         tui.buffer.clear();
                         return true;
                     };
@@ -3445,7 +3444,7 @@ fn handle_wizard<'scope, 'env: 'scope>(
                 {
                     #[cfg(unix)]
                     let Ok(log_writer) = log_writer.try_clone() else {
-                        eprintln!("Could not clone log writer!");
+                        tracing::error!("Could not clone log writer!");
                         return true;
                     };
 
@@ -3464,7 +3463,7 @@ fn handle_wizard<'scope, 'env: 'scope>(
                         send_shutdown.subscribe(),
                         false,
                     ) {
-                        eprintln!("Could not register new check: {e}");
+                        tracing::error!("Could not register new check: {e}");
                     }
 
                     let Some(path) = tui.config_file_path.as_ref() else {
@@ -3484,7 +3483,7 @@ fn handle_wizard<'scope, 'env: 'scope>(
                         .map_err(|e| format!("{e}"))
                         .and_then(|c| std::fs::write(path, c).map_err(|e| format!("{e}")))
                     {
-                        eprintln!("Could not save configuration: {e}");
+                        tracing::error!("Could not save configuration: {e}");
                     }
 
                     tui.add_check_tab.wizard_state = None;
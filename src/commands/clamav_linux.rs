@@ -81,7 +81,7 @@ where
 {
     match settings {
         DownloadSettings::Container { name, sneaky_ip } => {
-            let container = DownloadContainer::new(name.clone(), *sneaky_ip)?;
+            let container = DownloadContainer::new(name.clone(), *sneaky_ip, None, None)?;
             container.run(|| -> eyre::Result<()> { f() })??;
             Ok(())
         }
@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use colored::Colorize;
+use eyre::Context;
+
+use crate::{Cli, checks::CheckTypes, utils::busybox::Busybox};
+
+/// Generates shell completions and man pages for the whole `jj` command tree, so the large
+/// command surface is discoverable without a network connection
+///
+/// `list-check-names` and `list-applets` print the check types and busybox applets this exact
+/// binary supports, one per line, meant to be wired into a shell completion function for
+/// `jj check`/`jj check-daemon` and `jj busybox`/`jj bb` arguments instead of a list baked in at
+/// generation time
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Completions {
+    #[command(subcommand)]
+    command: CompletionsCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum CompletionsCommands {
+    /// Print a completion script for the given shell to stdout
+    Shell(ShellArgs),
+
+    /// Generate a man page per command into a directory
+    Manpages(ManpagesArgs),
+
+    /// Print every check type name, one per line
+    #[command(hide = true)]
+    ListCheckNames,
+
+    /// Print every embedded busybox applet name, one per line
+    #[command(hide = true)]
+    ListApplets,
+}
+
+#[derive(Parser, Debug)]
+struct ShellArgs {
+    shell: Shell,
+}
+
+#[derive(Parser, Debug)]
+struct ManpagesArgs {
+    /// Directory to write generated `.1` man pages into
+    output_dir: PathBuf,
+}
+
+impl super::Command for Completions {
+    fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            CompletionsCommands::Shell(args) => print_shell_completions(args.shell),
+            CompletionsCommands::Manpages(args) => generate_manpages(&args.output_dir),
+            CompletionsCommands::ListCheckNames => {
+                for name in CheckTypes::check_names() {
+                    println!("{name}");
+                }
+                Ok(())
+            }
+            CompletionsCommands::ListApplets => {
+                for applet in list_applets()? {
+                    println!("{applet}");
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn print_shell_completions(shell: Shell) -> eyre::Result<()> {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, &mut std::io::stdout());
+
+    Ok(())
+}
+
+fn generate_manpages(output_dir: &std::path::Path) -> eyre::Result<()> {
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Could not create {}", output_dir.display()))?;
+
+    let cmd = Cli::command();
+    let count = generate_manpage(&cmd, cmd.get_name(), output_dir)?;
+
+    println!(
+        "{}",
+        format!("--- Wrote {count} man page(s) to {}", output_dir.display()).green()
+    );
+
+    Ok(())
+}
+
+fn generate_manpage(
+    cmd: &clap::Command,
+    prefix: &str,
+    output_dir: &std::path::Path,
+) -> eyre::Result<usize> {
+    let path = output_dir.join(format!("{prefix}.1"));
+    let mut out = std::fs::File::create(&path)
+        .with_context(|| format!("Could not create {}", path.display()))?;
+    clap_mangen::Man::new(cmd.clone())
+        .render(&mut out)
+        .with_context(|| format!("Could not render {}", path.display()))?;
+
+    let mut count = 1;
+    for sub in cmd.get_subcommands() {
+        let sub_prefix = format!("{prefix}-{}", sub.get_name());
+        count += generate_manpage(sub, &sub_prefix, output_dir)?;
+    }
+
+    Ok(count)
+}
+
+fn list_applets() -> eyre::Result<Vec<String>> {
+    Ok(Busybox::new()?.applets()?.to_vec())
+}
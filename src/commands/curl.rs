@@ -0,0 +1,130 @@
+use std::{fs::File, io::Write, path::PathBuf, str::FromStr};
+
+use clap::Parser;
+use eyre::Context;
+
+#[derive(Debug, Clone)]
+struct CurlHeader {
+    name: String,
+    value: String,
+}
+
+impl FromStr for CurlHeader {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((name, value)) = s.split_once(':').or_else(|| s.split_once('=')) else {
+            eyre::bail!("Could not split header `{s}` on `:` or `=`");
+        };
+
+        Ok(CurlHeader {
+            name: name.trim().to_string(),
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+/// A small curl-compatible subset built on top of reqwest, for systems where curl and wget have
+/// been removed or trojaned
+///
+/// ```sh
+/// jj curl -X POST -H "Content-Type: application/json" -d '{"ping":true}' https://example.com
+/// ```
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Curl {
+    /// URL to request
+    url: String,
+
+    /// HTTP method to use. Defaults to POST if --data is given, GET otherwise
+    #[arg(short = 'X', long = "request")]
+    method: Option<String>,
+
+    /// Extra headers, as `name: value` or `name=value`
+    #[arg(short = 'H', long = "header")]
+    headers: Vec<CurlHeader>,
+
+    /// Request body, sent as-is
+    #[arg(short = 'd', long = "data")]
+    data: Option<String>,
+
+    /// Write the response body to a file instead of stdout
+    #[arg(short = 'o', long = "output")]
+    output: Option<PathBuf>,
+
+    /// Follow redirects
+    #[arg(short = 'L', long = "location")]
+    location: bool,
+
+    /// Skip TLS certificate verification
+    #[arg(short = 'k', long = "insecure")]
+    insecure: bool,
+
+    /// Show response status and headers along with the body
+    #[arg(short = 'i', long = "include")]
+    include: bool,
+
+    /// Set a custom User-Agent header
+    #[arg(short = 'A', long = "user-agent")]
+    user_agent: Option<String>,
+}
+
+impl super::Command for Curl {
+    fn execute(self) -> eyre::Result<()> {
+        let method = match &self.method {
+            Some(method) => reqwest::Method::from_bytes(method.as_bytes())
+                .context("Could not parse HTTP method")?,
+            None if self.data.is_some() => reqwest::Method::POST,
+            None => reqwest::Method::GET,
+        };
+
+        let client = reqwest::blocking::Client::builder()
+            .danger_accept_invalid_certs(self.insecure)
+            .redirect(if self.location {
+                reqwest::redirect::Policy::limited(10)
+            } else {
+                reqwest::redirect::Policy::none()
+            })
+            .build()
+            .context("Could not build HTTP client")?;
+
+        let mut request = client.request(method, &self.url);
+
+        for header in &self.headers {
+            request = request.header(header.name.as_str(), header.value.as_str());
+        }
+
+        if let Some(user_agent) = &self.user_agent {
+            request = request.header("User-Agent", user_agent.as_str());
+        }
+
+        if let Some(data) = self.data {
+            request = request.body(data);
+        }
+
+        let response = request.send().context("Could not send request")?;
+
+        if self.include {
+            println!("{:?} {}", response.version(), response.status());
+            for (name, value) in response.headers() {
+                println!("{name}: {}", value.to_str().unwrap_or(""));
+            }
+            println!();
+        }
+
+        let body = response.bytes().context("Could not read response body")?;
+
+        if let Some(output) = self.output {
+            File::create(&output)
+                .context("Could not create output file")?
+                .write_all(&body)
+                .context("Could not write response body to output file")?;
+        } else {
+            std::io::stdout()
+                .write_all(&body)
+                .context("Could not write response body to stdout")?;
+        }
+
+        Ok(())
+    }
+}
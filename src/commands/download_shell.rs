@@ -9,6 +9,7 @@ use std::{
 
 use clap::{Parser, ValueEnum};
 use eyre::Context;
+#[cfg(feature = "bundled-tools")]
 use flate2::write::GzDecoder;
 use nix::{
     sys::{
@@ -20,7 +21,8 @@ use nix::{
 
 use crate::utils::{busybox::Busybox, download_container::DownloadContainer, passwd::load_users};
 
-use super::zsh::ZSH_BYTES;
+#[cfg(feature = "bundled-tools")]
+use super::zsh::{ZSH_BYTES_AARCH64, ZSH_BYTES_X86_64};
 
 #[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ShellType {
@@ -45,6 +47,18 @@ pub struct DownloadShell {
     #[arg(value_enum, long, short = 'S', default_value_t = ShellType::Zsh)]
     shell: ShellType,
 
+    /// HTTP/SOCKS proxy URL (e.g. http://10.0.0.5:8080 or socks5://10.0.0.5:1080) to export as
+    /// http_proxy/https_proxy/all_proxy inside the shell, for environments where direct egress
+    /// is blocked and only a proxy is permitted outbound
+    #[arg(long)]
+    proxy: Option<String>,
+
+    /// DNS-over-HTTPS endpoint to resolve through inside the container (e.g.
+    /// https://cloudflare-dns.com/dns-query), instead of plaintext UDP to 1.1.1.1. Useful when
+    /// the host's DNS is firewalled or poisoned
+    #[arg(long)]
+    doh: Option<String>,
+
     /// A command to run in the container instead of providing a shell
     command: Vec<String>,
 }
@@ -53,13 +67,27 @@ fn zsh_command() -> eyre::Result<(OwnedFd, Command)> {
     let temp_fd = memfd_create("", MFdFlags::empty())?;
     let raw_fd = temp_fd.into_raw_fd();
 
-    let temp_file = unsafe { File::from_raw_fd(raw_fd) };
-    let mut decoder = GzDecoder::new(temp_file);
+    let mut temp_file = unsafe { File::from_raw_fd(raw_fd) };
+
+    #[cfg(feature = "bundled-tools")]
+    {
+        let zsh_bytes = crate::utils::embedded_tool_bytes_for_current_arch(
+            ZSH_BYTES_X86_64,
+            ZSH_BYTES_AARCH64,
+        )?;
 
-    decoder.write_all(ZSH_BYTES)?;
+        let mut decoder = GzDecoder::new(temp_file);
+        decoder.write_all(zsh_bytes)?;
+        temp_file = decoder.finish()?;
+    }
+
+    #[cfg(not(feature = "bundled-tools"))]
+    {
+        let zsh_bytes = crate::utils::fetch_tool_bytes("zsh")?;
+        temp_file.write_all(&zsh_bytes)?;
+    }
 
-    let zsh_file = decoder.finish()?;
-    let raw_fd = zsh_file.into_raw_fd();
+    let raw_fd = temp_file.into_raw_fd();
 
     Ok((
         unsafe { OwnedFd::from_raw_fd(raw_fd) },
@@ -69,7 +97,12 @@ fn zsh_command() -> eyre::Result<(OwnedFd, Command)> {
 
 impl super::Command for DownloadShell {
     fn execute(mut self) -> eyre::Result<()> {
-        let container = DownloadContainer::new(self.name.take(), self.sneaky_ip)?;
+        let container = DownloadContainer::new(
+            self.name.take(),
+            self.sneaky_ip,
+            self.proxy.take(),
+            self.doh.take(),
+        )?;
 
         let bash_cmd = format!(
             r#"exec bash --rcfile <(cat ~/.bashrc 2>/dev/null || cat /etc/bashrc 2>/dev/null || echo 'export PS1="\u@\h:\w\$ "'; echo 'PS1="\033[0;32m({})\033[0m $PS1"')"#,
@@ -80,6 +113,12 @@ impl super::Command for DownloadShell {
 
         let sh_ps1 = format!(r"\033[0;32m({})\033[0m \u@\h:\w\$ ", container.name());
 
+        let set_proxy_env = |cmd: &mut Command| {
+            for (key, value) in container.proxy_env() {
+                cmd.env(key, value);
+            }
+        };
+
         container.run(|| -> eyre::Result<()> {
             match (
                 std::env::var("SUDO_UID")
@@ -116,6 +155,7 @@ impl super::Command for DownloadShell {
                         }
 
                         cmd.env("PS1", zsh_ps1);
+                        set_proxy_env(&mut cmd);
 
                         let mut child = match cmd.spawn() {
                             Ok(c) => c,
@@ -165,6 +205,7 @@ impl super::Command for DownloadShell {
                         }
 
                         cmd.env("PS1", sh_ps1);
+                        set_proxy_env(&mut cmd);
 
                         let mut child = match cmd.spawn() {
                             Ok(c) => c,
@@ -211,6 +252,8 @@ impl super::Command for DownloadShell {
                             cmd.env("USER", user);
                         }
 
+                        set_proxy_env(&mut cmd);
+
                         let mut child = match cmd.spawn() {
                             Ok(c) => c,
                             Err(e) => {
@@ -256,6 +299,8 @@ impl super::Command for DownloadShell {
                             cmd.env("USER", user);
                         }
 
+                        set_proxy_env(&mut cmd);
+
                         let mut child = match cmd.spawn() {
                             Ok(c) => c,
                             Err(e) => {
@@ -274,25 +319,29 @@ impl super::Command for DownloadShell {
                     }
                 },
                 (_, _, false) => {
-                    Command::new(&self.command[0])
-                        .args(&self.command[1..])
-                        .spawn()?
-                        .wait()?;
+                    let mut cmd = Command::new(&self.command[0]);
+                    cmd.args(&self.command[1..]);
+                    set_proxy_env(&mut cmd);
+                    cmd.spawn()?.wait()?;
                 }
                 (_, ShellType::Sh, _) => {
                     let bb = Busybox::new()?;
-
-                    bb.command("sh").env("PS1", sh_ps1).spawn()?.wait()?;
+                    let mut cmd = bb.command("sh");
+                    cmd.env("PS1", sh_ps1);
+                    set_proxy_env(&mut cmd);
+                    cmd.spawn()?.wait()?;
                 }
                 (_, ShellType::Bash, _) => {
-                    Command::new("bash")
-                        .args(["-c", &bash_cmd])
-                        .spawn()?
-                        .wait()?;
+                    let mut cmd = Command::new("bash");
+                    cmd.args(["-c", &bash_cmd]);
+                    set_proxy_env(&mut cmd);
+                    cmd.spawn()?.wait()?;
                 }
                 (_, ShellType::Zsh, _) => {
                     let (fd, mut cmd) = zsh_command()?;
-                    cmd.env("PS1", zsh_ps1).spawn()?.wait()?;
+                    cmd.env("PS1", zsh_ps1);
+                    set_proxy_env(&mut cmd);
+                    cmd.spawn()?.wait()?;
                     drop(fd);
                 }
             }
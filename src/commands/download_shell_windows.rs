@@ -0,0 +1,124 @@
+use std::process::Command;
+
+use clap::Parser;
+use colored::Colorize;
+use eyre::{Context, bail, eyre};
+
+/// Windows counterpart to the Linux download shell. There's no equivalent to a throwaway network
+/// namespace here, so instead this adds a temporary Windows Firewall (WFP) rule that allows
+/// outbound traffic for one specific program, spawns that program, and removes the rule again
+/// once it exits - circumventing an outbound-block policy for exactly the one process that needs
+/// to reach out
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct DownloadShell {
+    /// A command to run with the firewall exception in place. Defaults to cmd.exe
+    command: Vec<String>,
+}
+
+/// Resolves `name` to a full path the way Windows Firewall program rules expect, either because
+/// it's already a path or by asking `where` to find it on PATH
+fn resolve_program_path(name: &str) -> eyre::Result<String> {
+    if std::path::Path::new(name).is_file() {
+        return Ok(name.to_string());
+    }
+
+    let output = Command::new("where")
+        .arg(name)
+        .output()
+        .context("Could not run `where` to resolve program path")?;
+
+    if !output.status.success() {
+        bail!("Could not find {name} on PATH");
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(str::trim)
+        .map(String::from)
+        .ok_or_else(|| eyre!("`where` produced no output for {name}"))
+}
+
+/// A Windows Firewall outbound-allow rule scoped to one program, removed again on drop
+struct FirewallException {
+    rule_name: String,
+}
+
+impl FirewallException {
+    fn new(program_path: &str) -> eyre::Result<Self> {
+        let rule_name = format!("jj-download-shell-{}", std::process::id());
+
+        let status = Command::new("netsh")
+            .args([
+                "advfirewall",
+                "firewall",
+                "add",
+                "rule",
+                &format!("name={rule_name}"),
+                "dir=out",
+                "action=allow",
+                &format!("program={program_path}"),
+                "enable=yes",
+            ])
+            .spawn()
+            .context("Could not run netsh to add firewall exception")?
+            .wait()
+            .context("Could not wait for netsh to add firewall exception")?;
+
+        if !status.success() {
+            bail!("netsh exited with {status} while adding firewall exception");
+        }
+
+        Ok(Self { rule_name })
+    }
+}
+
+impl Drop for FirewallException {
+    fn drop(&mut self) {
+        let removed = Command::new("netsh")
+            .args([
+                "advfirewall",
+                "firewall",
+                "delete",
+                "rule",
+                &format!("name={}", self.rule_name),
+            ])
+            .spawn()
+            .and_then(|mut child| child.wait());
+
+        if let Err(e) = removed {
+            eprintln!(
+                "Could not remove firewall exception {}: {e}",
+                self.rule_name
+            );
+        }
+    }
+}
+
+impl super::Command for DownloadShell {
+    fn execute(self) -> eyre::Result<()> {
+        let (program, args) = if self.command.is_empty() {
+            ("cmd.exe".to_string(), Vec::new())
+        } else {
+            (self.command[0].clone(), self.command[1..].to_vec())
+        };
+
+        let program_path = resolve_program_path(&program)?;
+
+        println!(
+            "{} Allowing outbound traffic for {program_path}",
+            "---".blue()
+        );
+        let _exception = FirewallException::new(&program_path)?;
+
+        Command::new(&program)
+            .args(&args)
+            .spawn()
+            .context("Could not spawn command")?
+            .wait()
+            .context("Could not wait for command to finish")?;
+
+        Ok(())
+    }
+}
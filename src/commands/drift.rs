@@ -0,0 +1,240 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, Subcommand};
+use colored::Colorize;
+use eyre::Context;
+use imara_diff::{Algorithm, Diff, InternedInput};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::nft::Nft;
+
+/// Label used for the firewall ruleset entry in the snapshot, alongside the watched file paths
+const NFT_RULESET_LABEL: &str = "nft ruleset";
+
+/// Files watched by default if none are given on the command line
+const DEFAULT_WATCHED_FILES: &[&str] = &[
+    "/etc/ssh/sshd_config",
+    "/etc/passwd",
+    "/etc/nginx/nginx.conf",
+    "/etc/apache2/apache2.conf",
+    "/etc/httpd/conf/httpd.conf",
+];
+
+/// Snapshots a configurable set of config files (and the active firewall ruleset) and, on
+/// subsequent runs, shows unified diffs of whatever changed since the last snapshot — cheap
+/// drift detection for the handful of files attackers and teammates both tend to touch
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Drift {
+    #[command(subcommand)]
+    command: DriftCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum DriftCommands {
+    /// Record the current contents of the watched files as the new baseline
+    #[command(visible_alias = "snap")]
+    Snapshot(SnapshotArgs),
+
+    /// Diff the watched files against the last snapshot
+    #[command(visible_alias = "c")]
+    Check(CheckArgs),
+}
+
+#[derive(Parser, Debug)]
+struct SnapshotArgs {
+    /// Files to watch. Defaults to a handful of common config files if omitted
+    paths: Vec<PathBuf>,
+
+    /// Where to store the snapshot
+    #[arg(long, default_value = "/var/lib/jj/drift-snapshot.json")]
+    snapshot_file: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+struct CheckArgs {
+    /// Files to watch. Defaults to a handful of common config files if omitted
+    paths: Vec<PathBuf>,
+
+    /// Where the snapshot is stored
+    #[arg(long, default_value = "/var/lib/jj/drift-snapshot.json")]
+    snapshot_file: PathBuf,
+
+    /// Re-run this check on an interval instead of once, e.g. so it can be left running under a
+    /// recurring job such as the check daemon
+    #[arg(long, short = 'i')]
+    interval: Option<humantime::Duration>,
+
+    /// Rebaseline the snapshot to the current state after each check, instead of always diffing
+    /// against the original baseline
+    #[arg(long)]
+    rebaseline: bool,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Snapshot {
+    entries: BTreeMap<String, String>,
+}
+
+impl super::Command for Drift {
+    fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            DriftCommands::Snapshot(args) => snapshot(&args.paths, &args.snapshot_file),
+            DriftCommands::Check(args) => check(args),
+        }
+    }
+}
+
+fn watched_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    if !paths.is_empty() {
+        return paths.to_vec();
+    }
+
+    DEFAULT_WATCHED_FILES.iter().map(PathBuf::from).collect()
+}
+
+fn capture_current(paths: &[PathBuf]) -> Snapshot {
+    let mut entries = BTreeMap::new();
+
+    for path in watched_paths(paths) {
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            entries.insert(path.display().to_string(), contents);
+        }
+    }
+
+    if let Ok(ruleset) = read_nft_ruleset() {
+        entries.insert(NFT_RULESET_LABEL.to_string(), ruleset);
+    }
+
+    Snapshot { entries }
+}
+
+fn read_nft_ruleset() -> eyre::Result<String> {
+    let nft = Nft::new()?;
+    let output = nft
+        .command()
+        .arg("list ruleset")
+        .output()
+        .context("Could not run nft list ruleset")?;
+
+    if !output.status.success() {
+        eyre::bail!("nft list ruleset exited with {}", output.status);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn snapshot(paths: &[PathBuf], snapshot_file: &Path) -> eyre::Result<()> {
+    let snapshot = capture_current(paths);
+    write_snapshot(snapshot_file, &snapshot)?;
+
+    println!(
+        "{}",
+        format!(
+            "--- Snapshotted {} watched item(s) to {}",
+            snapshot.entries.len(),
+            snapshot_file.display()
+        )
+        .green()
+    );
+
+    Ok(())
+}
+
+fn check(args: CheckArgs) -> eyre::Result<()> {
+    let Some(interval) = args.interval else {
+        return run_check_once(&args.paths, &args.snapshot_file, args.rebaseline);
+    };
+
+    println!(
+        "{} every {}",
+        "--- Starting drift watcher, checking".blue(),
+        interval
+    );
+    loop {
+        if let Err(e) = run_check_once(&args.paths, &args.snapshot_file, args.rebaseline) {
+            eprintln!("{} {e}", "Drift check failed:".red());
+        }
+        std::thread::sleep(interval.into());
+    }
+}
+
+fn run_check_once(paths: &[PathBuf], snapshot_file: &Path, rebaseline: bool) -> eyre::Result<()> {
+    let baseline = read_snapshot(snapshot_file)?;
+    let current = capture_current(paths);
+
+    let mut changed = 0;
+    for (label, current_contents) in &current.entries {
+        match baseline.entries.get(label) {
+            None => {
+                println!("{} {label}", "[NEW]".yellow());
+                changed += 1;
+            }
+            Some(before) if before != current_contents => {
+                print_diff(label, before, current_contents);
+                changed += 1;
+            }
+            Some(_) => {}
+        }
+    }
+    for label in baseline.entries.keys() {
+        if !current.entries.contains_key(label) {
+            println!("{} {label}", "[MISSING]".yellow());
+            changed += 1;
+        }
+    }
+
+    if changed == 0 {
+        println!("{}", "--- No drift detected".green());
+    } else {
+        println!("{}", format!("--- {changed} item(s) drifted").red());
+    }
+
+    if rebaseline {
+        write_snapshot(snapshot_file, &current)?;
+    }
+
+    Ok(())
+}
+
+fn print_diff(label: &str, before: &str, after: &str) {
+    println!("{} {label}", "--- diff for".blue());
+
+    let input = InternedInput::new(before, after);
+    let mut diff = Diff::compute(Algorithm::Histogram, &input);
+    diff.postprocess_lines(&input);
+
+    let before_lines = before.split('\n').collect::<Vec<_>>();
+    let after_lines = after.split('\n').collect::<Vec<_>>();
+
+    for hunk in diff.hunks() {
+        for line in &before_lines[hunk.before.start as usize..hunk.before.end as usize] {
+            println!("{}", format!("-{line}").red());
+        }
+        for line in &after_lines[hunk.after.start as usize..hunk.after.end as usize] {
+            println!("{}", format!("+{line}").green());
+        }
+    }
+}
+
+fn write_snapshot(path: &Path, snapshot: &Snapshot) -> eyre::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Could not create {}", parent.display()))?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(snapshot)?)
+        .with_context(|| format!("Could not write snapshot to {}", path.display()))
+}
+
+fn read_snapshot(path: &Path) -> eyre::Result<Snapshot> {
+    let contents = std::fs::read_to_string(path).with_context(|| {
+        format!(
+            "Could not read snapshot {} (run `jj drift snapshot` first)",
+            path.display()
+        )
+    })?;
+    serde_json::from_str(&contents).with_context(|| format!("Could not parse {}", path.display()))
+}
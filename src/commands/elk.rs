@@ -25,11 +25,11 @@ use tar::Archive;
 use walkdir::WalkDir;
 
 use crate::{
-    pcre,
+    pcre, strvec,
     utils::{
         busybox::Busybox,
         download_container::DownloadContainer,
-        download_file, get_public_ip,
+        download_file, download_file_mirrors, download_files_concurrent, get_public_ip,
         os_version::get_distro,
         packages::{DownloadSettings, install_apt_packages, install_dnf_packages},
         passwd, qx, system,
@@ -72,13 +72,16 @@ pub struct ElkSubcommandArgs {
     #[arg(long, short = 'V', default_value = "9.3.0")]
     pub elastic_version: String,
 
-    /// URL to download Elasticsearch, Logstash, and Kibana from
-    #[arg(long, default_value = "https://artifacts.elastic.co/downloads")]
-    pub download_url: String,
+    /// URL to download Elasticsearch, Logstash, and Kibana from. Can be repeated to give an
+    /// ordered list of mirrors, each one tried in turn if the previous one fails
+    #[arg(long, default_values_t = strvec!["https://artifacts.elastic.co/downloads"])]
+    pub download_url: Vec<String>,
 
-    /// URL to download Auditbeat, Filebeat, Packetbeat, Metricbeat, and Winlogbeat from
-    #[arg(long, default_value = "https://artifacts.elastic.co/downloads/beats")]
-    pub beats_download_url: String,
+    /// URL to download Auditbeat, Filebeat, Packetbeat, Metricbeat, and Winlogbeat from. Can be
+    /// repeated to give an ordered list of mirrors, each one tried in turn if the previous one
+    /// fails
+    #[arg(long, default_values_t = strvec!["https://artifacts.elastic.co/downloads/beats"])]
+    pub beats_download_url: Vec<String>,
 
     /// Where to put files to be shared on the network
     #[arg(long, short = 'S', default_value = "/opt/es-share")]
@@ -145,6 +148,22 @@ pub struct ElkBeatsArgs {
     pub dont_install_suricata: bool,
 }
 
+#[derive(Parser, Clone, Debug)]
+#[command(version, about)]
+pub struct SetupIlmArgs {
+    /// Where to install and configure everything ELK related, including beats
+    #[arg(long, short = 'e', default_value = "/opt/jj-es")]
+    pub elastic_install_directory: PathBuf,
+
+    /// Delete indices once they reach this age, in days
+    #[arg(long, short = 'r', default_value_t = 14)]
+    pub retention_days: u32,
+
+    /// Number of primary shards to request for each managed index pattern
+    #[arg(long, short = 'n', default_value_t = 1)]
+    pub number_of_shards: u32,
+}
+
 #[derive(Parser, Clone, Debug)]
 #[command(version, about)]
 pub struct SuricataInstallArgs {
@@ -210,6 +229,11 @@ pub enum ElkCommands {
     #[command(visible_alias = "wb")]
     SetupWinlogbeat(ElkSubcommandArgs),
 
+    /// Create an ILM policy and index templates so beats/check-result indices roll off disk
+    /// instead of filling it mid-competition
+    #[command(visible_alias = "ilm")]
+    SetupIlm(SetupIlmArgs),
+
     /// Export dashboards to allow for a manual import
     #[command(visible_alias = "exp-db")]
     ExportDashboards,
@@ -359,6 +383,19 @@ impl Elk {
             install_suricata(&busybox, args)?;
         }
 
+        if let EC::Install(args) = &self.command {
+            setup_ilm(
+                elastic_password,
+                &SetupIlmArgs {
+                    elastic_install_directory: args.elastic_install_directory.clone(),
+                    retention_days: 14,
+                    number_of_shards: 1,
+                },
+            )?;
+        } else if let EC::SetupIlm(args) = &self.command {
+            setup_ilm(elastic_password, args)?;
+        }
+
         Ok(())
     }
 }
@@ -384,6 +421,14 @@ fn get_elastic_password(password: &mut Option<String>) -> eyre::Result<String> {
     Ok(new_pass)
 }
 
+/// Picks a JVM heap size from total system RAM: half of it, clamped to a sane floor and the
+/// usual ~31 GiB ceiling (compressed oops stop working above that, and a bigger heap just means
+/// longer GC pauses)
+fn jvm_heap_mb(total_bytes: u64, min_mb: u64) -> u64 {
+    let half_mb = total_bytes / 1024 / 1024 / 2;
+    half_mb.clamp(min_mb, 31 * 1024)
+}
+
 fn setup_zram(args: &ElkSubcommandArgs) -> eyre::Result<()> {
     let mods = qx("lsmod")?.1;
 
@@ -430,13 +475,18 @@ fn download_packages(args: &ElkSubcommandArgs) -> eyre::Result<()> {
             let download_package = move || {
                 let mut dest_path = args.elasticsearch_share_directory.clone();
                 dest_path.push(format!("{pkg}.tar.gz"));
-                let res = download_file(
-                    &format!(
-                        "{}/{}/{}-{}-linux-x86_64.tar.gz",
-                        args.download_url, pkg, pkg, args.elastic_version
-                    ),
-                    dest_path,
-                );
+                let urls = args
+                    .download_url
+                    .iter()
+                    .map(|base| {
+                        format!(
+                            "{base}/{pkg}/{pkg}-{}-linux-x86_64.tar.gz",
+                            args.elastic_version
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let mirrors = urls.iter().map(String::as_str).collect::<Vec<_>>();
+                let res = download_file_mirrors(&mirrors, dest_path);
                 println!("Done downloading {pkg}!");
                 res
             };
@@ -455,13 +505,18 @@ fn download_packages(args: &ElkSubcommandArgs) -> eyre::Result<()> {
                 move || {
                     let mut dest_path = args.elasticsearch_share_directory.clone();
                     dest_path.push(format!("{beat}.tar.gz"));
-                    let res = download_file(
-                        &format!(
-                            "{}/{}/{}-{}-linux-x86_64.tar.gz",
-                            args.beats_download_url, beat, beat, args.elastic_version
-                        ),
-                        dest_path,
-                    );
+                    let urls = args
+                        .beats_download_url
+                        .iter()
+                        .map(|base| {
+                            format!(
+                                "{base}/{beat}/{beat}-{}-linux-x86_64.tar.gz",
+                                args.elastic_version
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    let mirrors = urls.iter().map(String::as_str).collect::<Vec<_>>();
+                    let res = download_file_mirrors(&mirrors, dest_path);
                     println!("Done downloading {beat} for Linux!");
                     res
                 }
@@ -481,13 +536,18 @@ fn download_packages(args: &ElkSubcommandArgs) -> eyre::Result<()> {
                 move || {
                     let mut dest_path = args.elasticsearch_share_directory.clone();
                     dest_path.push(format!("{beat}.zip"));
-                    let res = download_file(
-                        &format!(
-                            "{}/{}/{}-{}-windows-x86_64.zip",
-                            args.beats_download_url, beat, beat, args.elastic_version
-                        ),
-                        dest_path,
-                    );
+                    let urls = args
+                        .beats_download_url
+                        .iter()
+                        .map(|base| {
+                            format!(
+                                "{base}/{beat}/{beat}-{}-windows-x86_64.zip",
+                                args.elastic_version
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    let mirrors = urls.iter().map(String::as_str).collect::<Vec<_>>();
+                    let res = download_file_mirrors(&mirrors, dest_path);
                     println!("Done downloading {beat} for Windows!");
                     res
                 }
@@ -515,7 +575,7 @@ fn download_packages(args: &ElkSubcommandArgs) -> eyre::Result<()> {
     };
 
     if args.use_download_shell {
-        let container = DownloadContainer::new(None, args.sneaky_ip)?;
+        let container = DownloadContainer::new(None, args.sneaky_ip, None, None)?;
 
         container.run(|| download_packages_internal(true))??;
     } else {
@@ -842,6 +902,46 @@ fn setup_elasticsearch(
         elasticsearch_config
     };
 
+    let mem = system::mem_stats()?;
+    let cpus = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+
+    // A 4 GB competition VM OOMs if we hand Elasticsearch the defaults tuned for a real
+    // server, so size the heap and index buffer off of what's actually there
+    let es_heap_mb = jvm_heap_mb(mem.total_bytes, 512);
+    let index_buffer_percent: u8 = if mem.total_bytes < 8 * 1024 * 1024 * 1024 {
+        5
+    } else {
+        10
+    };
+
+    println!(
+        "Sizing Elasticsearch for {} MiB RAM / {cpus} CPUs -> {es_heap_mb} MiB heap, {index_buffer_percent}% index buffer",
+        mem.total_bytes / 1024 / 1024
+    );
+
+    std::fs::create_dir_all(cpaths!(es_path_conf, "jvm.options.d"))
+        .context("Could not create jvm.options.d")?;
+    std::fs::write(
+        cpaths!(es_path_conf, "jvm.options.d", "jj-heap.options"),
+        format!("-Xms{es_heap_mb}m\n-Xmx{es_heap_mb}m\n"),
+    )
+    .context("Could not write elasticsearch jvm heap options")?;
+
+    let index_buffer_regex = regex::Regex::new("(?ms)(#?indices.memory.index_buffer_size: [^\n]+)")
+        .expect("Static regex failed after testing");
+    let elasticsearch_config = index_buffer_regex.replace(
+        &elasticsearch_config,
+        format!("indices.memory.index_buffer_size: {index_buffer_percent}%"),
+    );
+    let elasticsearch_config = if !index_buffer_regex.is_match(&elasticsearch_config) {
+        elasticsearch_config
+            + &format!("\nindices.memory.index_buffer_size: {index_buffer_percent}%")
+    } else {
+        elasticsearch_config
+    };
+
     std::fs::write(
         cpaths!(es_path_conf, "elasticsearch.yml"),
         &*elasticsearch_config,
@@ -1337,6 +1437,112 @@ fn load_kibana_dashboards(
     Ok(())
 }
 
+/// Index patterns whose disk usage needs to be bounded by ILM, covering every beat jj ships plus
+/// check-daemon's own results index
+const MANAGED_INDEX_PATTERNS: &[&str] = &[
+    "filebeat-*",
+    "winlogbeat-*",
+    "auditbeat-*",
+    "packetbeat-*",
+    "metricbeat-*",
+    "jj-checks-*",
+];
+
+fn setup_ilm(password: &mut Option<String>, args: &SetupIlmArgs) -> eyre::Result<()> {
+    let elastic_password = get_elastic_password(password)?;
+
+    let es_path_conf = cpaths!(args.elastic_install_directory, "elasticsearch", "config");
+
+    println!("{}", "--- Setting up ILM retention policy...".green());
+
+    let root_cert = reqwest::Certificate::from_pem(
+        std::fs::read_to_string(cpaths!(&es_path_conf, "certs", "http_ca.crt"))?.as_bytes(),
+    )?;
+
+    let client = reqwest::blocking::Client::builder()
+        .add_root_certificate(root_cert)
+        .build()?;
+
+    let policy = serde_json::json!({
+        "policy": {
+            "phases": {
+                "hot": {
+                    "min_age": "0ms",
+                    "actions": {
+                        "set_priority": { "priority": 100 }
+                    }
+                },
+                "delete": {
+                    "min_age": format!("{}d", args.retention_days),
+                    "actions": {
+                        "delete": {}
+                    }
+                }
+            }
+        }
+    });
+
+    let response = client
+        .put("https://localhost:10200/_ilm/policy/jj-retention-policy")
+        .basic_auth("elastic", Some(elastic_password.clone()))
+        .json(&policy)
+        .send()?
+        .json::<serde_json::Value>()?;
+
+    if response.get("error").is_some() {
+        println!("Error creating ILM policy!");
+        println!("{response}");
+    } else {
+        println!(
+            "Created ILM policy 'jj-retention-policy' (delete after {} days)",
+            args.retention_days
+        );
+    }
+
+    for pattern in MANAGED_INDEX_PATTERNS {
+        let template_name = format!(
+            "jj-ilm-{}",
+            pattern.trim_end_matches('*').trim_end_matches('-')
+        );
+
+        let template = serde_json::json!({
+            "index_patterns": [pattern],
+            "priority": 200,
+            "template": {
+                "settings": {
+                    "index.lifecycle.name": "jj-retention-policy",
+                    "index.number_of_shards": args.number_of_shards,
+                    "index.number_of_replicas": 0
+                }
+            }
+        });
+
+        let response = client
+            .put(format!(
+                "https://localhost:10200/_index_template/{template_name}"
+            ))
+            .basic_auth("elastic", Some(elastic_password.clone()))
+            .json(&template)
+            .send()?
+            .json::<serde_json::Value>()?;
+
+        if response
+            .get("acknowledged")
+            .and_then(serde_json::Value::as_bool)
+            == Some(true)
+        {
+            println!("Applied retention template for '{pattern}'");
+        } else {
+            println!("Error applying retention template for '{pattern}'!");
+            println!("{response}");
+        }
+    }
+
+    println!("{}", "--- ILM retention policy applied!".green());
+
+    Ok(())
+}
+
 fn setup_logstash(
     bb: &Busybox,
     password: &mut Option<String>,
@@ -1541,6 +1747,17 @@ Environment="ES_API_KEY={}:{}"
         LOGSTASH_FILTER_CONF,
     )?;
 
+    let mem = system::mem_stats()?;
+    let ls_heap_mb = (mem.total_bytes / 1024 / 1024 / 4).clamp(256, 4 * 1024);
+
+    std::fs::create_dir_all(cpaths!(ls_path_conf, "jvm.options.d"))
+        .context("Could not create jvm.options.d")?;
+    std::fs::write(
+        cpaths!(ls_path_conf, "jvm.options.d", "jj-heap.options"),
+        format!("-Xms{ls_heap_mb}m\n-Xmx{ls_heap_mb}m\n"),
+    )
+    .context("Could not write logstash jvm heap options")?;
+
     system("systemctl daemon-reload")?;
     system("systemctl enable jj-logstash")?;
     system("systemctl restart jj-logstash")?;
@@ -2665,53 +2882,47 @@ pub fn untar_beat(
 fn download_beats(download_shell: bool, args: &ElkBeatsArgs) -> eyre::Result<()> {
     println!("{}", "--- Downloading beats...".green());
 
-    let mut download_threads = vec![];
+    let http_ca_crt = format!("{}/http_ca.crt", args.elastic_install_directory.display());
 
-    for beat in ["auditbeat", "filebeat", "packetbeat", "metricbeat"] {
-        let args = args.clone();
-        let download_package = move || {
-            let res = download_file(
+    if download_shell {
+        for beat in ["auditbeat", "filebeat", "packetbeat", "metricbeat"] {
+            download_file(
                 &format!(
                     "http://{}:{}/{}.tar.gz",
                     args.elk_ip, args.elk_share_port, beat
                 ),
                 format!("/tmp/{beat}.tar.gz"),
-            );
+            )?;
             println!("Done downloading {beat}!");
-            res
-        };
-        if download_shell {
-            download_package()?;
-        } else {
-            download_threads.push(thread::spawn(download_package));
         }
-    }
 
-    let args = args.clone();
-    if download_shell {
         download_file(
             &format!("http://{}:{}/http_ca.crt", args.elk_ip, args.elk_share_port),
-            format!("{}/http_ca.crt", args.elastic_install_directory.display()),
+            &http_ca_crt,
         )?;
     } else {
-        let args = args.clone();
-        download_threads.push(thread::spawn(move || {
-            download_file(
-                &format!("http://{}:{}/http_ca.crt", args.elk_ip, args.elk_share_port),
-                format!("{}/http_ca.crt", args.elastic_install_directory.display()),
-            )
-        }));
-    }
-
-    for thread in download_threads {
-        match thread.join() {
-            Ok(r) => r?,
-            Err(_) => {
-                eprintln!(
-                    "{}",
-                    "!!! Could not join download thread due to panic!".red()
-                );
-            }
+        let mut downloads: Vec<(String, PathBuf, Option<String>)> =
+            ["auditbeat", "filebeat", "packetbeat", "metricbeat"]
+                .into_iter()
+                .map(|beat| {
+                    (
+                        format!(
+                            "http://{}:{}/{}.tar.gz",
+                            args.elk_ip, args.elk_share_port, beat
+                        ),
+                        PathBuf::from(format!("/tmp/{beat}.tar.gz")),
+                        None,
+                    )
+                })
+                .collect();
+        downloads.push((
+            format!("http://{}:{}/http_ca.crt", args.elk_ip, args.elk_share_port),
+            PathBuf::from(&http_ca_crt),
+            None,
+        ));
+
+        for result in download_files_concurrent(downloads) {
+            result?;
         }
     }
 
@@ -2722,7 +2933,7 @@ pub fn install_beats(bb: &Busybox, args: &ElkBeatsArgs) -> eyre::Result<()> {
     std::fs::create_dir_all(&args.elastic_install_directory)?;
 
     if args.use_download_shell {
-        let container = DownloadContainer::new(None, args.sneaky_ip)?;
+        let container = DownloadContainer::new(None, args.sneaky_ip, None, None)?;
 
         container.run(|| download_beats(true, args))??;
     } else {
@@ -2899,6 +3110,10 @@ output.logstash:
 
     println!("{}", "--- Done configuring beats!".green());
 
+    if let Err(e) = enable_filebeat_modules(args) {
+        eprintln!("Could not enable Filebeat modules for detected services: {e}");
+    }
+
     if let Err(e) = disable_auditd() {
         eprintln!("Could not disable auditd: {e}");
     }
@@ -2930,6 +3145,57 @@ output.logstash:
     Ok(())
 }
 
+/// systemd service names to detect, mapped to the Filebeat module to enable when one of them
+/// is active. sshd's logins are covered by the `system` module's `auth` fileset rather than a
+/// dedicated `sshd` module
+const SERVICE_FILEBEAT_MODULES: &[(&[&str], &str)] = &[
+    (&["nginx"], "nginx"),
+    (&["mysql", "mariadb", "mysqld"], "mysql"),
+    (&["sshd", "ssh"], "system"),
+];
+
+fn service_running(names: &[&str]) -> bool {
+    names.iter().any(|name| {
+        qx(&format!("systemctl is-active --quiet {name}")).is_ok_and(|(status, _)| status.success())
+    })
+}
+
+/// Detects which of a small set of common services are running locally, and enables the
+/// matching Filebeat module for each one found, rather than shipping every module's worth of
+/// parsing rules by default
+fn enable_filebeat_modules(args: &ElkBeatsArgs) -> eyre::Result<()> {
+    let filebeat_home = cpaths!(args.elastic_install_directory, "filebeat");
+
+    println!(
+        "{}",
+        "--- Detecting installed services to select Filebeat modules...".green()
+    );
+
+    for (service_names, module) in SERVICE_FILEBEAT_MODULES {
+        if !service_running(service_names) {
+            continue;
+        }
+
+        println!(
+            "Detected '{}' running, enabling Filebeat module '{module}'...",
+            service_names[0]
+        );
+
+        match Command::new(cpaths!(&filebeat_home, "filebeat"))
+            .current_dir(&filebeat_home)
+            .args(["modules", "enable", module])
+            .spawn()
+            .and_then(|mut child| child.wait())
+        {
+            Ok(status) if status.success() => println!("Enabled Filebeat module '{module}'!"),
+            Ok(status) => eprintln!("`filebeat modules enable {module}` exited with {status}"),
+            Err(e) => eprintln!("Could not run filebeat to enable module '{module}': {e}"),
+        }
+    }
+
+    Ok(())
+}
+
 pub fn install_suricata(bb: &Busybox, args: &SuricataInstallArgs) -> eyre::Result<()> {
     println!("{}", "--- Installing Suricata...".green());
 
@@ -2956,7 +3222,7 @@ pub fn install_suricata(bb: &Busybox, args: &SuricataInstallArgs) -> eyre::Resul
         )?;
 
         if args.use_download_shell {
-            DownloadContainer::new(None, args.sneaky_ip)?
+            DownloadContainer::new(None, args.sneaky_ip, None, None)?
                 .run(|| system("dnf copr enable -y @oisf/suricata-8.0"))??;
         } else {
             system("dnf copr enable -y @oisf/suricata-8.0")?;
@@ -2969,7 +3235,8 @@ pub fn install_suricata(bb: &Busybox, args: &SuricataInstallArgs) -> eyre::Resul
     }
 
     if args.use_download_shell {
-        DownloadContainer::new(None, args.sneaky_ip)?.run(|| system("suricata-update"))??;
+        DownloadContainer::new(None, args.sneaky_ip, None, None)?
+            .run(|| system("suricata-update"))??;
     } else {
         system("suricata-update")?;
     }
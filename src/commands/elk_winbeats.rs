@@ -3,12 +3,13 @@ use std::{net::Ipv4Addr, path::PathBuf, process::Command};
 use clap::{Parser, Subcommand};
 use colored::Colorize;
 
-use crate::utils::download_file;
+use crate::utils::{download_file, qx};
 
 const FILEBEAT_YML: &str = include_str!("elk/filebeat.windows.yml");
 const WINLOGBEAT_YML: &str = include_str!("elk/winlogbeat.windows.yml");
 const PACKETBEAT_YML: &str = include_str!("elk/packetbeat.windows.yml");
 const METRICBEAT_YML: &str = include_str!("elk/metricbeat.yml");
+const SYSMON_CONFIG: &str = include_str!("elk/sysmon-config.xml");
 
 #[derive(Parser, Clone, Debug)]
 #[command(version, about)]
@@ -38,11 +39,27 @@ pub struct ElkBeatsArgs {
     pub dont_install_sysmon: bool,
 }
 
+#[derive(Parser, Clone, Debug)]
+#[command(version, about)]
+pub struct InstallSysmonArgs {
+    /// Path to search for Sysmon. If it's a URL, it will download Sysmon. If it's a zip file, it will search for Sysmon64.exe and extract it. Otherwise, it should be a path to Sysmon64.exe
+    #[arg(
+        long,
+        short = 'P',
+        default_value = "https://live.sysinternals.com/Sysmon64.exe"
+    )]
+    pub sysmon_path: String,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum ElkCommands {
     /// Install beats and configure the system to send logs to the ELK stack
     #[command(visible_alias = "beats")]
     InstallBeats(ElkBeatsArgs),
+
+    /// Install Sysmon with jj's bundled hardened config and verify events land in the event log
+    #[command(visible_alias = "sysmon")]
+    InstallSysmon(InstallSysmonArgs),
 }
 
 /// Install, configure, and manage beats locally
@@ -55,8 +72,10 @@ pub struct WinBeats {
 
 impl super::Command for WinBeats {
     fn execute(self) -> eyre::Result<()> {
-        let ElkCommands::InstallBeats(args) = self.command;
-        install_winbeats(args, true)
+        match self.command {
+            ElkCommands::InstallBeats(args) => install_winbeats(args, true),
+            ElkCommands::InstallSysmon(args) => install_configure_sysmon(args.sysmon_path),
+        }
     }
 }
 
@@ -364,10 +383,54 @@ pub fn install_configure_sysmon(sysmon_path: String) -> eyre::Result<()> {
         );
     };
 
+    let config_path = std::env::temp_dir().join("jj-sysmon-config.xml");
+    std::fs::write(&config_path, SYSMON_CONFIG)?;
+
+    println!("--- Installing Sysmon with jj's bundled config...");
+
     Command::new(path)
-        .args(["-i", "-n", "-l", "-p", "-accepteula"])
+        .args(["-accepteula", "-i", &config_path.to_string_lossy()])
         .spawn()?
         .wait()?;
 
+    verify_sysmon_events()?;
+
+    println!("{}", "--- Sysmon installed and logging!".green());
+
     Ok(())
 }
+
+fn sysmon_event_count() -> eyre::Result<u64> {
+    let (_, out) = qx("wevtutil gli Microsoft-Windows-Sysmon/Operational")?;
+
+    out.lines()
+        .find_map(|l| l.trim().strip_prefix("numberOfLogRecords:"))
+        .and_then(|n| n.trim().parse().ok())
+        .ok_or_else(|| eyre::eyre!("Could not parse numberOfLogRecords from wevtutil"))
+}
+
+/// Generates a process creation event Sysmon should pick up, then checks the
+/// Microsoft-Windows-Sysmon/Operational event log actually grew, so a silently-broken
+/// install (driver failed to load, channel disabled, etc.) is caught immediately
+fn verify_sysmon_events() -> eyre::Result<()> {
+    println!("--- Verifying Sysmon events land in the event log...");
+
+    let before = sysmon_event_count()?;
+
+    Command::new("cmd.exe")
+        .args(["/c", "exit"])
+        .spawn()?
+        .wait()?;
+
+    for attempt in 0..10 {
+        if attempt > 0 {
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+
+        if sysmon_event_count()? > before {
+            return Ok(());
+        }
+    }
+
+    eyre::bail!("Sysmon was installed, but no new events showed up in its event log");
+}
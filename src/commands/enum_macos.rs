@@ -0,0 +1,102 @@
+use clap::{Parser, Subcommand};
+
+use crate::utils::{
+    pager::{self, PagerOutput},
+    qx,
+};
+
+/// Perform system enumeration or target specific subsystems
+#[derive(Parser, Debug)]
+#[command(about = "System enumeration tools")]
+pub struct Enum {
+    #[command(subcommand)]
+    pub subcommand: Option<EnumSubcommands>,
+
+    /// Disable the output pager
+    #[arg(long)]
+    pub no_pager: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EnumSubcommands {
+    /// Loaded launchd jobs, system-wide and for this user
+    #[command(visible_alias("a"))]
+    Autoruns,
+
+    /// Local user accounts known to Open Directory
+    #[command(visible_alias("u"))]
+    Users,
+
+    /// Current network ports and listening services
+    #[command(visible_alias("p"))]
+    Ports(super::ports::Ports),
+}
+
+impl super::Command for Enum {
+    fn execute(self) -> eyre::Result<()> {
+        let mut ob = pager::get_pager_output(self.no_pager);
+
+        enum_hostname(&mut ob)?;
+        match self.subcommand {
+            Some(EnumSubcommands::Autoruns) => enum_autoruns(&mut ob),
+            Some(EnumSubcommands::Users) => enum_users(&mut ob),
+            Some(EnumSubcommands::Ports(ports)) => enum_ports(&mut ob, ports),
+            None => {
+                enum_autoruns(&mut ob)?;
+                enum_users(&mut ob)?;
+                enum_ports(
+                    &mut ob,
+                    super::ports::Ports {
+                        no_pager: self.no_pager,
+                        display_tcp: true,
+                        display_udp: true,
+                        ..super::ports::Ports::default()
+                    },
+                )?;
+
+                Ok(())
+            }
+        }
+    }
+}
+
+fn enum_autoruns(out: &mut impl PagerOutput) -> eyre::Result<()> {
+    writeln!(out, "\n==== AUTORUNS (launchd)")?;
+
+    let jobs = qx("launchctl list").map_or_else(
+        |_| "(unable to query launchd jobs)".to_string(),
+        |(_, jobs)| jobs,
+    );
+    writeln!(out, "{jobs}")?;
+
+    Ok(())
+}
+
+fn enum_users(out: &mut impl PagerOutput) -> eyre::Result<()> {
+    writeln!(out, "\n==== USERS")?;
+
+    match qx("dscl . -list /Users") {
+        Ok((_, users)) => {
+            for user in users.lines() {
+                writeln!(out, "{user}")?;
+            }
+        }
+        Err(e) => writeln!(out, "(unable to query users: {e})")?,
+    }
+
+    Ok(())
+}
+
+fn enum_ports(out: &mut impl PagerOutput, p: super::ports::Ports) -> eyre::Result<()> {
+    writeln!(out, "\n==== PORTS INFO")?;
+    p.run(out)
+}
+
+fn enum_hostname(out: &mut impl PagerOutput) -> eyre::Result<()> {
+    writeln!(out, "\n==== HOSTNAME INFO")?;
+    let name = qx("hostname")
+        .map(|(_, s)| s.trim().to_string())
+        .unwrap_or_else(|_| "(unable to read hostname)".to_string());
+    writeln!(out, "Hostname: {name}")?;
+    Ok(())
+}
@@ -0,0 +1,374 @@
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+    net::SocketAddr,
+    os::unix::fs::{MetadataExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, Subcommand};
+use colored::Colorize;
+use eyre::Context;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+/// File integrity monitoring: baseline a set of paths, then periodically rescan and report
+/// anything that changed
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Fim {
+    #[command(subcommand)]
+    command: FimCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum FimCommands {
+    /// Hash and record the current state of the watched paths
+    #[command(visible_alias = "i")]
+    Init(FimInitArgs),
+
+    /// Periodically rescan the watched paths against the baseline, reporting changes
+    #[command(visible_alias = "w")]
+    Watch(FimWatchArgs),
+}
+
+#[derive(Parser, Debug)]
+struct FimInitArgs {
+    /// Files or directories to baseline. Directories are walked recursively
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+
+    /// Where to store the baseline
+    #[arg(long, default_value = "/var/lib/jj/fim-baseline.json")]
+    baseline_file: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+struct FimWatchArgs {
+    /// Where the baseline created by `jj fim init` is stored
+    #[arg(long, default_value = "/var/lib/jj/fim-baseline.json")]
+    baseline_file: PathBuf,
+
+    /// How long to wait between rescans, in seconds
+    #[arg(short, long, default_value = "30")]
+    interval: u64,
+
+    /// Specify where to send newline delimited JSON alerts for the watcher
+    #[arg(short = 'I', long)]
+    logs_ip: Option<SocketAddr>,
+
+    /// Specify a log file to save alerts to
+    #[arg(short = 'f', long)]
+    log_file: Option<PathBuf>,
+
+    /// Elasticsearch/OpenSearch URL to index alerts into (e.g. https://localhost:10200), such
+    /// as the one the elk command sets up
+    #[arg(long)]
+    elasticsearch_url: Option<String>,
+
+    /// Index name prefix alerts are indexed under; a `-YYYY.MM.DD` suffix is appended daily
+    #[arg(long, default_value = "jj-fim")]
+    elasticsearch_index: String,
+
+    /// Username to authenticate to Elasticsearch with
+    #[arg(long, default_value = "elastic")]
+    elasticsearch_username: String,
+
+    /// Password to authenticate to Elasticsearch with
+    #[arg(long)]
+    elasticsearch_password: Option<String>,
+
+    /// Skip TLS certificate verification when contacting Elasticsearch, rather than having to
+    /// distribute the elk command's self-signed CA to every watched host
+    #[arg(long)]
+    elasticsearch_insecure: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct FimEntry {
+    sha256: String,
+    mode: u32,
+    uid: u32,
+    gid: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct FimBaseline {
+    roots: Vec<PathBuf>,
+    entries: BTreeMap<PathBuf, FimEntry>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum FimChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Serialize, Debug)]
+struct FimAlert {
+    timestamp: chrono::DateTime<chrono::Utc>,
+    path: PathBuf,
+    kind: FimChangeKind,
+    previous: Option<FimEntry>,
+    current: Option<FimEntry>,
+}
+
+impl super::Command for Fim {
+    fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            FimCommands::Init(args) => init(args),
+            FimCommands::Watch(args) => watch(args),
+        }
+    }
+}
+
+fn init(args: FimInitArgs) -> eyre::Result<()> {
+    println!("{}", "--- Baselining watched paths...".green());
+
+    let entries = scan(&args.paths)?;
+    println!("Hashed {} files", entries.len());
+
+    let baseline = FimBaseline {
+        roots: args.paths,
+        entries,
+    };
+
+    if let Some(parent) = args.baseline_file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(
+        &args.baseline_file,
+        serde_json::to_string_pretty(&baseline)?,
+    )
+    .with_context(|| format!("Could not write {}", args.baseline_file.display()))?;
+
+    println!(
+        "{}",
+        format!("--- Baseline written to {}", args.baseline_file.display()).green()
+    );
+
+    Ok(())
+}
+
+fn watch(args: FimWatchArgs) -> eyre::Result<()> {
+    let mut baseline: FimBaseline = serde_json::from_str(
+        &std::fs::read_to_string(&args.baseline_file)
+            .with_context(|| format!("Could not read {}", args.baseline_file.display()))?,
+    )
+    .with_context(|| format!("Could not parse {}", args.baseline_file.display()))?;
+
+    let mut log_file = match args.log_file.as_deref() {
+        Some(p) => Some(open_log_file(p)?),
+        None => None,
+    };
+
+    println!(
+        "{}",
+        format!(
+            "--- Watching {} path(s) every {}s",
+            baseline.roots.len(),
+            args.interval
+        )
+        .green()
+    );
+
+    loop {
+        let current = scan(&baseline.roots)?;
+        let alerts = diff(&baseline.entries, &current);
+
+        for alert in &alerts {
+            report(alert, &args, log_file.as_mut())?;
+        }
+
+        if !alerts.is_empty() {
+            baseline.entries = current;
+            std::fs::write(
+                &args.baseline_file,
+                serde_json::to_string_pretty(&baseline)?,
+            )
+            .with_context(|| format!("Could not write {}", args.baseline_file.display()))?;
+        }
+
+        std::thread::sleep(std::time::Duration::from_secs(args.interval));
+    }
+}
+
+/// Walks each root (recursively, if it's a directory) and hashes every regular file found
+fn scan(roots: &[PathBuf]) -> eyre::Result<BTreeMap<PathBuf, FimEntry>> {
+    let mut entries = BTreeMap::new();
+
+    for root in roots {
+        for entry in WalkDir::new(root) {
+            let Ok(entry) = entry else {
+                continue;
+            };
+
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let Ok(entry_stats) = hash_entry(entry.path()) else {
+                continue;
+            };
+
+            entries.insert(entry.path().to_path_buf(), entry_stats);
+        }
+    }
+
+    Ok(entries)
+}
+
+fn hash_entry(path: &Path) -> eyre::Result<FimEntry> {
+    let metadata = std::fs::metadata(path)?;
+    let permissions = metadata.permissions();
+
+    Ok(FimEntry {
+        sha256: sha256_file(path)?,
+        mode: permissions.mode(),
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+    })
+}
+
+fn sha256_file(path: &Path) -> eyre::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn diff(
+    baseline: &BTreeMap<PathBuf, FimEntry>,
+    current: &BTreeMap<PathBuf, FimEntry>,
+) -> Vec<FimAlert> {
+    let mut alerts = vec![];
+    let now = chrono::Utc::now();
+
+    for (path, entry) in baseline {
+        match current.get(path) {
+            None => alerts.push(FimAlert {
+                timestamp: now,
+                path: path.clone(),
+                kind: FimChangeKind::Removed,
+                previous: Some(entry.clone()),
+                current: None,
+            }),
+            Some(current_entry) if current_entry != entry => alerts.push(FimAlert {
+                timestamp: now,
+                path: path.clone(),
+                kind: FimChangeKind::Modified,
+                previous: Some(entry.clone()),
+                current: Some(current_entry.clone()),
+            }),
+            _ => {}
+        }
+    }
+
+    for (path, entry) in current {
+        if !baseline.contains_key(path) {
+            alerts.push(FimAlert {
+                timestamp: now,
+                path: path.clone(),
+                kind: FimChangeKind::Added,
+                previous: None,
+                current: Some(entry.clone()),
+            });
+        }
+    }
+
+    alerts
+}
+
+fn open_log_file(path: &Path) -> eyre::Result<std::fs::File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .with_context(|| format!("Could not open {}", path.display()))
+}
+
+/// Emits an alert through the same three channels check-daemon supports: stdout, a log file,
+/// a raw TCP socket, and Elasticsearch
+fn report(
+    alert: &FimAlert,
+    args: &FimWatchArgs,
+    mut log_file: Option<&mut std::fs::File>,
+) -> eyre::Result<()> {
+    let line = serde_json::to_string(alert)?;
+
+    println!(
+        "{}",
+        format!("!!! {:?} {}", alert.kind, alert.path.display()).red()
+    );
+
+    if let Some(file) = log_file.as_deref_mut() {
+        writeln!(file, "{line}").context("Could not write FIM alert to log file")?;
+    }
+
+    if let Some(ip) = args.logs_ip {
+        match std::net::TcpStream::connect(ip) {
+            Ok(mut stream) => {
+                if let Err(e) = writeln!(stream, "{line}") {
+                    eprintln!(
+                        "{}",
+                        format!("??? Could not send FIM alert to {ip}: {e}").yellow()
+                    );
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", format!("??? Could not connect to {ip}: {e}").yellow());
+            }
+        }
+    }
+
+    if let Some(url) = &args.elasticsearch_url {
+        if let Err(e) = index_to_elasticsearch(url, args, alert) {
+            eprintln!(
+                "{}",
+                format!("??? Could not index FIM alert to Elasticsearch: {e}").yellow()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn index_to_elasticsearch(url: &str, args: &FimWatchArgs, alert: &FimAlert) -> eyre::Result<()> {
+    let index = format!(
+        "{}-{}",
+        args.elasticsearch_index,
+        alert.timestamp.format("%Y.%m.%d")
+    );
+
+    let client = reqwest::blocking::Client::builder()
+        .danger_accept_invalid_certs(args.elasticsearch_insecure)
+        .build()?;
+
+    client
+        .post(format!("{url}/{index}/_doc"))
+        .basic_auth(
+            &args.elasticsearch_username,
+            args.elasticsearch_password.as_ref(),
+        )
+        .json(alert)
+        .send()?
+        .error_for_status()?;
+
+    Ok(())
+}
@@ -8,12 +8,16 @@ use std::{
 };
 
 use clap::{Parser, Subcommand};
+use colored::Colorize;
+use eyre::Context;
 
 use crate::utils::{
     busybox::Busybox,
+    dry_run,
     logs::ellipsize,
     nft::Nft,
     ports::linux::{SocketState, parse_net_tcp, parse_net_udp},
+    privilege,
 };
 
 #[derive(Subcommand, Debug)]
@@ -26,6 +30,16 @@ enum FirewallCmd {
     /// IP to another
     #[command(visible_alias = "nr")]
     NatRedirect(NatRedirect),
+
+    /// Instantly lock the host down to only scorebot/checker and management traffic, saving
+    /// the previous ruleset so it can be put back once the incident is contained
+    Panic(Panic),
+
+    /// Restore the ruleset that was in effect before `jj fw panic` was run
+    Restore(RestorePanic),
+
+    /// Add an address to jj's persistent firewall block set, creating it on first use
+    Block(Block),
 }
 
 /// Firewall management
@@ -41,6 +55,9 @@ impl super::Command for Firewall {
         match self.cmd {
             FirewallCmd::QuickSetup(qs) => qs.execute(),
             FirewallCmd::NatRedirect(nr) => nr.execute(),
+            FirewallCmd::Panic(p) => p.execute(),
+            FirewallCmd::Restore(r) => r.execute(),
+            FirewallCmd::Block(b) => b.execute(),
         }
     }
 }
@@ -235,6 +252,8 @@ struct NatRedirect {
 
 impl NatRedirect {
     fn execute(self) -> eyre::Result<()> {
+        privilege::require_root("set up NAT redirection")?;
+
         let nft = Nft::new()?;
 
         let NatRedirect {
@@ -277,3 +296,200 @@ impl NatRedirect {
         Ok(())
     }
 }
+
+#[derive(Parser, Debug)]
+struct Panic {
+    /// Scorebot/checker IPs or CIDRs, and the team's management subnet, allowed through while
+    /// in panic mode. May be given multiple times
+    #[arg(short, long)]
+    allow: Vec<String>,
+
+    /// Read additional allowed IPs/CIDRs from a file, one per line (blank lines and
+    /// `#`-prefixed comments are ignored)
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Where to save the ruleset that was in effect before panic mode was applied
+    #[arg(long, default_value = "/var/lib/jj/fw-panic-backup.nft")]
+    backup_file: PathBuf,
+
+    /// Print the nft commands that would run and where the ruleset backup would be saved,
+    /// without touching the live firewall
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl Panic {
+    fn execute(self) -> eyre::Result<()> {
+        privilege::require_root("apply firewall panic mode")?;
+
+        let mut allowed = self.allow;
+        if let Some(config) = &self.config {
+            let contents = std::fs::read_to_string(config)
+                .with_context(|| format!("Could not read {}", config.display()))?;
+            allowed.extend(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                    .map(str::to_string),
+            );
+        }
+
+        if allowed.is_empty() {
+            eyre::bail!("No allowed IPs/CIDRs given; refusing to panic into a fully closed box");
+        }
+
+        let nft = Nft::new()?;
+
+        dry_run::step(
+            self.dry_run,
+            format!("save current ruleset to {}", self.backup_file.display()),
+            || {
+                let current_ruleset = nft
+                    .command()
+                    .arg("list ruleset")
+                    .output()
+                    .context("Could not list current ruleset")?;
+                if let Some(parent) = self.backup_file.parent() {
+                    std::fs::create_dir_all(parent).ok();
+                }
+                std::fs::write(&self.backup_file, &current_ruleset.stdout)
+                    .with_context(|| format!("Could not write {}", self.backup_file.display()))
+            },
+        )?;
+
+        println!("{}", "--- Locking down to allowed IPs only...".red());
+
+        let run = |cmd: String| {
+            dry_run::step(self.dry_run, format!("run: nft {cmd}"), || {
+                nft.exec(cmd.clone(), Stdio::null())
+            })
+        };
+
+        run("flush ruleset".to_string())?;
+        run("add table inet fw_panic".to_string())?;
+        run(
+            "add chain inet fw_panic input { type filter hook input priority 0; policy drop; }"
+                .to_string(),
+        )?;
+        run(
+            "add chain inet fw_panic output { type filter hook output priority 0; policy drop; }"
+                .to_string(),
+        )?;
+        run("add rule inet fw_panic input iifname lo accept".to_string())?;
+        run("add rule inet fw_panic output oifname lo accept".to_string())?;
+        run("add rule inet fw_panic input ct state established,related accept".to_string())?;
+        run("add rule inet fw_panic output ct state established,related accept".to_string())?;
+
+        for ip in &allowed {
+            run(format!("add rule inet fw_panic input ip saddr {ip} accept"))?;
+            run(format!(
+                "add rule inet fw_panic output ip daddr {ip} accept"
+            ))?;
+            println!("  allowing {ip}");
+        }
+
+        dry_run::summary(
+            self.dry_run,
+            "Panic mode would be applied",
+            "Panic mode applied! Run `jj fw restore` once the incident is contained",
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+struct RestorePanic {
+    /// Ruleset file saved by `jj fw panic` to restore
+    #[arg(long, default_value = "/var/lib/jj/fw-panic-backup.nft")]
+    backup_file: PathBuf,
+}
+
+impl RestorePanic {
+    fn execute(self) -> eyre::Result<()> {
+        privilege::require_root("restore the saved firewall ruleset")?;
+
+        let nft = Nft::new()?;
+
+        println!(
+            "{}",
+            format!(
+                "--- Restoring ruleset from {}...",
+                self.backup_file.display()
+            )
+            .green()
+        );
+
+        nft.exec("flush ruleset", Stdio::null())?;
+
+        let status = nft
+            .command()
+            .args(["-f", &self.backup_file.to_string_lossy()])
+            .status()
+            .with_context(|| format!("Could not load {}", self.backup_file.display()))?;
+
+        if !status.success() {
+            eyre::bail!("nft exited with {status} while restoring the saved ruleset");
+        }
+
+        println!("{}", "--- Previous ruleset restored!".green());
+
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+struct Block {
+    /// Address to drop all inbound traffic from
+    ip: IpAddr,
+}
+
+impl Block {
+    fn execute(self) -> eyre::Result<()> {
+        privilege::require_root("add a firewall block rule")?;
+
+        let nft = Nft::new()?;
+        ensure_blocklist(&nft)?;
+
+        nft.exec(
+            format!("add element inet jj_blocklist blocked {{ {} }}", self.ip),
+            Stdio::null(),
+        )?;
+
+        println!("{}", format!("--- Blocked {}", self.ip).red());
+
+        Ok(())
+    }
+}
+
+/// Create the `jj_blocklist` table/set/chain if they don't already exist. `add table`/`add
+/// set`/`add chain` are all idempotent, but `add rule` is not, so the drop rule is only added
+/// the first time this runs
+fn ensure_blocklist(nft: &Nft) -> eyre::Result<()> {
+    nft.exec("add table inet jj_blocklist", Stdio::null())?;
+    nft.exec(
+        "add set inet jj_blocklist blocked { type ipv4_addr; flags interval; }",
+        Stdio::null(),
+    )?;
+    nft.exec(
+        "add chain inet jj_blocklist input { type filter hook input priority -10; policy accept; }",
+        Stdio::null(),
+    )?;
+
+    let existing = nft
+        .command()
+        .arg("list chain inet jj_blocklist input")
+        .output()
+        .context("Could not list jj_blocklist chain")?;
+
+    if !String::from_utf8_lossy(&existing.stdout).contains("@blocked") {
+        nft.exec(
+            "add rule inet jj_blocklist input ip saddr @blocked drop",
+            Stdio::null(),
+        )?;
+    }
+
+    Ok(())
+}
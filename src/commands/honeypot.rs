@@ -0,0 +1,170 @@
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::Utc;
+use clap::Parser;
+use colored::Colorize;
+use eyre::Context;
+use serde::Serialize;
+
+use crate::utils::logs::ellipsize;
+
+/// How much of a connection's data to read (and log a preview of) before closing it
+const MAX_READ_BYTES: usize = 4096;
+
+/// Binds a fake listener on each given port and logs every connection attempt, so a touch
+/// anywhere near a service that doesn't actually exist on this box is an instant, high-confidence
+/// signal of an attacker poking around
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Honeypot {
+    /// Ports to listen on, comma separated, e.g. --ports 23,3389,445
+    #[arg(short, long, value_delimiter = ',')]
+    ports: Vec<u16>,
+
+    /// Where to append connection attempts as JSONL
+    #[arg(short, long, default_value = "/var/lib/jj/honeypot.jsonl")]
+    log_file: PathBuf,
+
+    /// Automatically add any source that connects to `jj fw block`
+    #[arg(long)]
+    auto_block: bool,
+}
+
+#[derive(Serialize)]
+struct ConnectionLog {
+    timestamp: String,
+    port: u16,
+    source: String,
+    bytes_received: usize,
+    data_preview: String,
+}
+
+impl super::Command for Honeypot {
+    fn execute(self) -> eyre::Result<()> {
+        if self.ports.is_empty() {
+            eyre::bail!("No ports given; specify --ports 23,3389,445");
+        }
+
+        if let Some(parent) = self.log_file.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Could not create {}", parent.display()))?;
+        }
+        let log_file = Arc::new(Mutex::new(
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.log_file)
+                .with_context(|| format!("Could not open {}", self.log_file.display()))?,
+        ));
+
+        let mut handles = vec![];
+        for port in &self.ports {
+            let port = *port;
+            let log_file = Arc::clone(&log_file);
+            let auto_block = self.auto_block;
+            handles.push(std::thread::spawn(move || {
+                if let Err(e) = listen(port, &log_file, auto_block) {
+                    eprintln!(
+                        "{} {e}",
+                        format!("honeypot listener on port {port} failed:").red()
+                    );
+                }
+            }));
+        }
+
+        println!(
+            "{}",
+            format!("--- Honeypot listening on port(s) {:?}", self.ports).green()
+        );
+
+        for handle in handles {
+            handle.join().ok();
+        }
+
+        Ok(())
+    }
+}
+
+fn listen(port: u16, log_file: &Arc<Mutex<std::fs::File>>, auto_block: bool) -> eyre::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .with_context(|| format!("Could not bind honeypot listener on port {port}"))?;
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let log_file = Arc::clone(log_file);
+
+        std::thread::spawn(move || {
+            handle_connection(port, stream, &log_file, auto_block);
+        });
+    }
+
+    Ok(())
+}
+
+fn handle_connection(
+    port: u16,
+    mut stream: TcpStream,
+    log_file: &Arc<Mutex<std::fs::File>>,
+    auto_block: bool,
+) {
+    let source = stream
+        .peer_addr()
+        .map(|a| a.to_string())
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    stream.set_read_timeout(Some(Duration::from_secs(5))).ok();
+
+    let mut buf = [0u8; MAX_READ_BYTES];
+    let bytes_received = stream.read(&mut buf).unwrap_or(0);
+
+    let entry = ConnectionLog {
+        timestamp: Utc::now().to_rfc3339(),
+        port,
+        source: source.clone(),
+        bytes_received,
+        data_preview: ellipsize(200, &String::from_utf8_lossy(&buf[..bytes_received])),
+    };
+
+    if let Ok(mut file) = log_file.lock()
+        && let Ok(line) = serde_json::to_string(&entry)
+    {
+        writeln!(file, "{line}").ok();
+    }
+
+    println!(
+        "{} {source} on port {port} ({bytes_received} byte(s))",
+        "[HIT]".yellow()
+    );
+
+    if auto_block {
+        block_source(&source);
+    }
+}
+
+fn block_source(source: &str) {
+    let Some(ip) = source.rsplit_once(':').map(|(ip, _)| ip) else {
+        return;
+    };
+
+    let Ok(exe) = std::env::current_exe() else {
+        return;
+    };
+
+    match std::process::Command::new(&exe)
+        .args(["fw", "block", ip])
+        .status()
+    {
+        Ok(status) if status.success() => println!("  {} blocked {ip}", "-->".yellow()),
+        Ok(status) => eprintln!(
+            "  {} could not block {ip}: exited with {status}",
+            "-->".yellow()
+        ),
+        Err(e) => eprintln!("  {} could not block {ip}: {e}", "-->".yellow()),
+    }
+}
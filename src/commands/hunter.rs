@@ -0,0 +1,236 @@
+use std::{
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use colored::Colorize;
+use nix::{
+    sys::signal::{Signal, kill},
+    unistd::Pid,
+};
+use regex::Regex;
+
+/// Names of processes whose job is to handle untrusted network input; a shell or interpreter
+/// spawned directly underneath one of these is the textbook shape of a web-shell or exploited
+/// service spawning a reverse shell
+const NETWORK_FACING_PARENTS: &[&str] = &[
+    "nginx", "apache2", "httpd", "php-fpm", "java", "tomcat", "mysqld", "sshd", "vsftpd",
+    "proftpd", "named", "exim4", "postfix",
+];
+
+/// Interpreters and shells that are suspicious to see spawned directly by a network-facing
+/// service, as opposed to being launched interactively by a user
+const SHELL_LIKE_CHILDREN: &[&str] = &[
+    "sh", "bash", "dash", "zsh", "nc", "ncat", "python", "python3", "perl", "socat",
+];
+
+/// Walks `/proc` flagging processes that look like they've been tampered with: running from a
+/// deleted binary, executing out of a world-writable scratch directory, lying about their own
+/// name, spawned in an unusual parent/child relationship, or carrying a long base64-looking blob
+/// on the command line
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Hunter {
+    /// Send SIGKILL to every process this turns up
+    #[arg(long)]
+    kill: bool,
+
+    /// Quarantine (chmod 000 and move aside) the executable backing each flagged process
+    #[arg(long)]
+    quarantine: bool,
+
+    /// Directory to move quarantined executables into
+    #[arg(long, default_value = "/var/lib/jj/quarantine")]
+    quarantine_dir: PathBuf,
+}
+
+#[derive(Debug)]
+struct Suspect {
+    pid: u32,
+    reason: String,
+    exe: Option<PathBuf>,
+}
+
+impl super::Command for Hunter {
+    fn execute(self) -> eyre::Result<()> {
+        let suspects = hunt()?;
+
+        if suspects.is_empty() {
+            println!("{}", "--- No suspicious processes found".green());
+            return Ok(());
+        }
+
+        for suspect in &suspects {
+            println!(
+                "{} PID {}: {}",
+                "[SUSPECT]".red(),
+                suspect.pid,
+                suspect.reason
+            );
+
+            if self.kill {
+                match kill(Pid::from_raw(suspect.pid as i32), Signal::SIGKILL) {
+                    Ok(()) => println!("  {} killed PID {}", "-->".yellow(), suspect.pid),
+                    Err(e) => println!(
+                        "  {} could not kill PID {}: {e}",
+                        "-->".yellow(),
+                        suspect.pid
+                    ),
+                }
+            }
+
+            if self.quarantine
+                && let Some(exe) = &suspect.exe
+            {
+                match quarantine_file(exe, &self.quarantine_dir) {
+                    Ok(()) => println!("  {} quarantined {}", "-->".yellow(), exe.display()),
+                    Err(e) => println!(
+                        "  {} could not quarantine {}: {e}",
+                        "-->".yellow(),
+                        exe.display()
+                    ),
+                }
+            }
+        }
+
+        println!(
+            "{}",
+            format!("--- {} suspicious process(es) found", suspects.len()).red()
+        );
+
+        Ok(())
+    }
+}
+
+fn hunt() -> eyre::Result<Vec<Suspect>> {
+    let base64ish =
+        Regex::new(r"^[A-Za-z0-9+/=]{40,}$").expect("Static regex failed after testing");
+    let mut suspects = vec![];
+
+    for entry in std::fs::read_dir("/proc")?.filter_map(Result::ok) {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+
+        let exe = std::fs::read_link(format!("/proc/{pid}/exe")).ok();
+        let cmdline = std::fs::read_to_string(format!("/proc/{pid}/cmdline")).unwrap_or_default();
+        let args: Vec<&str> = cmdline.split('\0').filter(|a| !a.is_empty()).collect();
+
+        if let Some(reason) = deleted_exe_reason(exe.as_deref()) {
+            suspects.push(suspect(pid, reason, &exe));
+        }
+
+        if let Some(reason) = scratch_dir_reason(exe.as_deref()) {
+            suspects.push(suspect(pid, reason, &exe));
+        }
+
+        if let Some(reason) = argv0_mismatch_reason(exe.as_deref(), args.first()) {
+            suspects.push(suspect(pid, reason, &exe));
+        }
+
+        if let Some(reason) = parentage_reason(pid) {
+            suspects.push(suspect(pid, reason, &exe));
+        }
+
+        if let Some(reason) = base64_cmdline_reason(&base64ish, &args) {
+            suspects.push(suspect(pid, reason, &exe));
+        }
+    }
+
+    Ok(suspects)
+}
+
+fn suspect(pid: u32, reason: String, exe: &Option<PathBuf>) -> Suspect {
+    Suspect {
+        pid,
+        reason,
+        exe: exe.clone(),
+    }
+}
+
+fn deleted_exe_reason(exe: Option<&Path>) -> Option<String> {
+    let exe = exe?;
+    exe.to_string_lossy()
+        .ends_with("(deleted)")
+        .then(|| format!("running from a deleted binary: {}", exe.display()))
+}
+
+fn scratch_dir_reason(exe: Option<&Path>) -> Option<String> {
+    let exe = exe?;
+    (exe.starts_with("/tmp") || exe.starts_with("/dev/shm")).then(|| {
+        format!(
+            "executing out of a world-writable scratch directory: {}",
+            exe.display()
+        )
+    })
+}
+
+/// Flags a process whose `argv[0]` doesn't match the name of the binary the kernel says it's
+/// actually running, a common trick to make a malicious process blend in as something benign
+/// in a `ps` listing
+fn argv0_mismatch_reason(exe: Option<&Path>, argv0: Option<&&str>) -> Option<String> {
+    let exe = exe?;
+    let argv0 = argv0?;
+
+    let exe_name = exe.file_name()?.to_str()?;
+    let argv0_name = Path::new(argv0.trim_start_matches('-'))
+        .file_name()?
+        .to_str()?;
+
+    (exe_name != argv0_name)
+        .then(|| format!("argv[0] '{argv0_name}' does not match its executable '{exe_name}'"))
+}
+
+/// Flags a shell/interpreter spawned directly underneath a network-facing service, the
+/// textbook shape of a web-shell dropping into a reverse shell
+fn parentage_reason(pid: u32) -> Option<String> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let ppid: u32 = stat
+        .rsplit(')')
+        .next()?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()?;
+
+    let comm = std::fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+    let comm = comm.trim();
+    let parent_comm = std::fs::read_to_string(format!("/proc/{ppid}/comm")).ok()?;
+    let parent_comm = parent_comm.trim();
+
+    let parent_is_network_facing = NETWORK_FACING_PARENTS
+        .iter()
+        .any(|p| parent_comm.starts_with(p));
+    let child_is_shell_like = SHELL_LIKE_CHILDREN.iter().any(|c| comm.starts_with(c));
+
+    (parent_is_network_facing && child_is_shell_like).then(|| {
+        format!("'{comm}' (PID {pid}) was spawned by network-facing process '{parent_comm}' (PID {ppid})")
+    })
+}
+
+fn base64_cmdline_reason(pattern: &Regex, args: &[&str]) -> Option<String> {
+    args.iter()
+        .find(|a| pattern.is_match(a))
+        .map(|a| format!("command line contains a long base64-looking argument: {a}"))
+}
+
+fn quarantine_file(exe: &Path, quarantine_dir: &Path) -> eyre::Result<()> {
+    std::fs::create_dir_all(quarantine_dir)?;
+
+    let name = exe
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "unknown".to_string());
+    let dest = quarantine_dir.join(format!("{name}.{}", std::process::id()));
+
+    std::fs::copy(exe, &dest)?;
+    std::fs::set_permissions(&dest, std::fs::Permissions::from_mode(0o000))?;
+    std::fs::remove_file(exe)?;
+
+    Ok(())
+}
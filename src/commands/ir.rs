@@ -0,0 +1,349 @@
+use std::{
+    fs::{File, create_dir_all},
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+use colored::Colorize;
+use eyre::Context;
+use flate2::{Compression, write::GzEncoder};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tar::Builder;
+use walkdir::WalkDir;
+
+use crate::utils::{ports, qx};
+
+/// Gathers an incident-response triage bundle in one shot, so first responders have a
+/// point-in-time snapshot to work from instead of re-running commands against a box that's
+/// changing under them
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Ir {
+    #[command(subcommand)]
+    command: IrCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum IrCommands {
+    /// Collect a triage bundle into a timestamped tarball
+    #[command(visible_alias = "c")]
+    Collect(CollectArgs),
+}
+
+#[derive(Parser, Debug)]
+struct CollectArgs {
+    /// Directory to write the finished triage bundle into
+    #[arg(short, long, default_value = "/var/lib/jj/ir")]
+    output_dir: PathBuf,
+
+    /// Extra files or directories to bundle verbatim, on top of the default config set
+    #[arg(short, long)]
+    paths: Vec<PathBuf>,
+}
+
+/// Config files worth grabbing as-is on most Linux boxes, covering the common points an
+/// intruder would have tampered with to gain or keep access
+const DEFAULT_CONFIGS: &[&str] = &[
+    "/etc/passwd",
+    "/etc/group",
+    "/etc/hosts",
+    "/etc/resolv.conf",
+    "/etc/ssh/sshd_config",
+    "/etc/sudoers",
+];
+
+#[derive(Serialize)]
+struct ArtifactEntry {
+    path: String,
+    sha256: String,
+    size: u64,
+}
+
+#[derive(Serialize)]
+struct Manifest {
+    created: String,
+    hostname: String,
+    artifacts: Vec<ArtifactEntry>,
+}
+
+#[derive(Serialize)]
+struct ProcessEntry {
+    pid: u32,
+    exe: Option<String>,
+    exe_sha256: Option<String>,
+    cmdline: String,
+}
+
+#[derive(Serialize)]
+struct SocketEntry {
+    socket_type: String,
+    local_addr: String,
+    local_port: u16,
+    remote_addr: Option<String>,
+    remote_port: Option<u16>,
+    state: String,
+    pid: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct CrontabEntry {
+    path: String,
+    contents: String,
+}
+
+impl super::Command for Ir {
+    fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            IrCommands::Collect(args) => args.execute(),
+        }
+    }
+}
+
+impl CollectArgs {
+    fn execute(self) -> eyre::Result<()> {
+        let timestamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let staging_dir = self.output_dir.join(format!("ir-{timestamp}"));
+
+        println!(
+            "{}",
+            format!(
+                "--- Collecting triage bundle into {}...",
+                staging_dir.display()
+            )
+            .green()
+        );
+
+        create_dir_all(&staging_dir)?;
+
+        write_json(&staging_dir.join("processes.json"), &collect_processes())?;
+        write_json(&staging_dir.join("sockets.json"), &collect_sockets())?;
+        write_json(&staging_dir.join("crontabs.json"), &collect_crontabs())?;
+        write_text_from_command(&staging_dir.join("logged_in_users.txt"), "w")?;
+        copy_if_exists(Path::new("/proc/modules"), &staging_dir.join("modules.txt"));
+        copy_first_existing(
+            &["/var/log/auth.log", "/var/log/secure"],
+            &staging_dir.join("auth.log"),
+        );
+        collect_bash_histories(&staging_dir.join("bash_history"));
+        collect_configs(&staging_dir.join("configs"), &self.paths);
+
+        let manifest = build_manifest(&staging_dir)?;
+        std::fs::write(
+            staging_dir.join("manifest.json"),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+
+        let archive_path = self.output_dir.join(format!("ir-{timestamp}.tar.gz"));
+        write_archive(&staging_dir, &archive_path)?;
+        std::fs::remove_dir_all(&staging_dir).ok();
+
+        println!(
+            "{}",
+            format!(
+                "--- Triage bundle written to {} ({} artifact(s))",
+                archive_path.display(),
+                manifest.artifacts.len()
+            )
+            .green()
+        );
+
+        Ok(())
+    }
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> eyre::Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(value)?)
+        .with_context(|| format!("Could not write {}", path.display()))
+}
+
+fn write_text_from_command(path: &Path, command: &str) -> eyre::Result<()> {
+    let (_, out) = qx(command)?;
+    std::fs::write(path, out).with_context(|| format!("Could not write {}", path.display()))
+}
+
+fn copy_if_exists(source: &Path, dest: &Path) {
+    let _ = std::fs::copy(source, dest);
+}
+
+fn copy_first_existing(candidates: &[&str], dest: &Path) {
+    for candidate in candidates {
+        if std::fs::copy(candidate, dest).is_ok() {
+            return;
+        }
+    }
+}
+
+/// Builds a process list out of `/proc`, including a SHA-256 of each process's executable so
+/// the bundle can later be cross-checked against known-good hashes
+fn collect_processes() -> Vec<ProcessEntry> {
+    let Ok(read_dir) = std::fs::read_dir("/proc") else {
+        return vec![];
+    };
+
+    read_dir
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+
+            let exe = std::fs::read_link(format!("/proc/{pid}/exe"))
+                .ok()
+                .map(|p| p.to_string_lossy().into_owned());
+            let exe_sha256 = exe
+                .as_ref()
+                .filter(|p| !p.ends_with("(deleted)"))
+                .and_then(|p| sha256_file(Path::new(p)).ok());
+            let cmdline = std::fs::read_to_string(format!("/proc/{pid}/cmdline"))
+                .unwrap_or_default()
+                .replace('\0', " ")
+                .trim()
+                .to_string();
+
+            Some(ProcessEntry {
+                pid,
+                exe,
+                exe_sha256,
+                cmdline,
+            })
+        })
+        .collect()
+}
+
+fn collect_sockets() -> Vec<SocketEntry> {
+    ports::list_ports()
+        .unwrap_or_default()
+        .iter()
+        .map(|s| SocketEntry {
+            socket_type: format!("{:?}", s.socket_type()),
+            local_addr: s.local_addr().to_string(),
+            local_port: s.local_port(),
+            remote_addr: s.remote_addr().map(|a| a.to_string()),
+            remote_port: s.remote_port(),
+            state: format!("{:?}", s.state()),
+            pid: s.pid(),
+        })
+        .collect()
+}
+
+fn collect_crontabs() -> Vec<CrontabEntry> {
+    let mut paths = vec![PathBuf::from("/etc/crontab")];
+
+    for dir in ["/etc/cron.d", "/var/spool/cron", "/var/spool/cron/crontabs"] {
+        for entry in WalkDir::new(dir).into_iter().filter_map(Result::ok) {
+            if entry.file_type().is_file() {
+                paths.push(entry.path().to_path_buf());
+            }
+        }
+    }
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let contents = std::fs::read_to_string(&path).ok()?;
+            Some(CrontabEntry {
+                path: path.to_string_lossy().into_owned(),
+                contents,
+            })
+        })
+        .collect()
+}
+
+fn collect_bash_histories(dest_dir: &Path) {
+    create_dir_all(dest_dir).ok();
+
+    let mut home_dirs = vec![PathBuf::from("/root")];
+    if let Ok(read_dir) = std::fs::read_dir("/home") {
+        home_dirs.extend(read_dir.filter_map(Result::ok).map(|e| e.path()));
+    }
+
+    for home in home_dirs {
+        let Some(user) = home.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+            continue;
+        };
+
+        copy_if_exists(
+            &home.join(".bash_history"),
+            &dest_dir.join(format!("{user}.txt")),
+        );
+    }
+}
+
+fn collect_configs(dest_dir: &Path, extra_paths: &[PathBuf]) {
+    create_dir_all(dest_dir).ok();
+
+    for path in DEFAULT_CONFIGS
+        .iter()
+        .map(PathBuf::from)
+        .chain(extra_paths.iter().cloned())
+    {
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        copy_if_exists(&path, &dest_dir.join(name));
+    }
+}
+
+fn sha256_file(path: &Path) -> eyre::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Walks the staged files and records their hash and size before they're packed into the
+/// archive, so the manifest can be used to verify the bundle wasn't tampered with afterward
+fn build_manifest(staging_dir: &Path) -> eyre::Result<Manifest> {
+    let mut artifacts = vec![];
+
+    for entry in WalkDir::new(staging_dir).into_iter().filter_map(Result::ok) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let size = entry.metadata()?.len();
+        let sha256 = sha256_file(entry.path())?;
+        let rel_path = entry
+            .path()
+            .strip_prefix(staging_dir)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .into_owned();
+
+        artifacts.push(ArtifactEntry {
+            path: rel_path,
+            sha256,
+            size,
+        });
+    }
+
+    Ok(Manifest {
+        created: Utc::now().to_rfc3339(),
+        hostname: qx("hostname")
+            .map(|(_, out)| out.trim().to_string())
+            .unwrap_or_default(),
+        artifacts,
+    })
+}
+
+fn write_archive(staging_dir: &Path, archive_path: &Path) -> eyre::Result<()> {
+    let file = File::create(archive_path)
+        .with_context(|| format!("Could not create {}", archive_path.display()))?;
+    let encoder = GzEncoder::new(file, Compression::default());
+    let mut archive = Builder::new(encoder);
+
+    archive.append_dir_all(".", staging_dir)?;
+    archive.finish()?;
+
+    Ok(())
+}
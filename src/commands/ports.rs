@@ -4,8 +4,13 @@ use clap::Parser;
 use colored::Colorize;
 
 use crate::utils::{
+    output::{self, OutputFormat},
     pager::{self, PagerOutput},
-    ports::{self, SocketRecord, SocketState, SocketType},
+    ports::{
+        self, SocketRecord, SocketState, SocketType,
+        baseline::PortBaseline,
+        enrich::{GeoIpDatabase, ReverseDnsResolver},
+    },
 };
 
 /// Query the system for network status and display results
@@ -13,7 +18,7 @@ use crate::utils::{
 #[command(version, about)]
 pub struct Ports {
     /// Do not use less to page the output
-    #[cfg(target_os = "linux")]
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
     #[arg(long, short = 'n')]
     pub no_pager: bool,
 
@@ -37,7 +42,8 @@ pub struct Ports {
     #[arg(long, short = 'p')]
     pub hide_path: bool,
 
-    /// Display the cgroup the process is a part of
+    /// Display the cgroup the process is a part of, resolved to a container name/image when
+    /// the cgroup matches a running Docker, Podman, or containerd container
     #[cfg(target_os = "linux")]
     #[arg(long, short = 'g')]
     pub display_cgroup: bool,
@@ -65,11 +71,49 @@ pub struct Ports {
     /// Display unspecified addresses, even if they are IPv6 and only IPv4 is selected (and vice versa)
     #[arg(long, short = 'U')]
     pub display_unspecified: bool,
+
+    /// Compare current listeners against a baseline file, printing any listener not present in
+    /// the baseline. Exits with an error if new listeners are found, so this can be wired into
+    /// a monitoring job for alerting
+    #[arg(long, value_name = "PATH", conflicts_with = "save_baseline")]
+    pub baseline: Option<PathBuf>,
+
+    /// Write the current listeners out to a baseline file, creating or overwriting it
+    #[arg(long, value_name = "PATH")]
+    pub save_baseline: Option<PathBuf>,
+
+    /// Resolve remote addresses to hostnames via reverse DNS
+    #[arg(long, short = 'r')]
+    pub resolve: bool,
+
+    /// Annotate remote addresses with a country code, looked up in the given GeoLite2/GeoIP2
+    /// mmdb database
+    #[arg(long, value_name = "PATH")]
+    pub geoip_db: Option<PathBuf>,
+
+    /// Launch an interactive TUI instead of printing a table, with sortable columns,
+    /// incremental filtering, and a detail pane for the selected socket's process
+    #[arg(long, short = 'T')]
+    pub tui: bool,
+
+    /// Display the send/receive queue sizes and retransmit count for each socket, useful for
+    /// diagnosing a service that is up but slow
+    #[cfg(target_os = "linux")]
+    #[arg(long, short = 'q')]
+    pub display_queue: bool,
+
+    /// Output format
+    #[arg(value_enum, long, short = 'F', default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
 }
 
 impl super::Command for Ports {
     fn execute(self) -> eyre::Result<()> {
-        #[cfg(target_os = "linux")]
+        if self.tui {
+            return tui::main();
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
         let mut ob = pager::get_pager_output(self.no_pager);
         #[cfg(windows)]
         let mut ob = pager::get_pager_output(true);
@@ -77,6 +121,8 @@ impl super::Command for Ports {
     }
 }
 
+mod tui;
+
 struct PortGroups {
     pids: HashSet<u64>,
     local_addr: HashSet<IpAddr>,
@@ -87,19 +133,32 @@ struct PortGroups {
     cmd: String,
     state: SocketState,
     socket_type: HashSet<SocketType>,
+    #[cfg(target_os = "linux")]
+    tx_queue: u32,
+    #[cfg(target_os = "linux")]
+    rx_queue: u32,
+    #[cfg(target_os = "linux")]
+    retransmits: u32,
 }
 
+#[derive(serde::Serialize)]
 struct RenderPortGroups {
     pids: String,
     local_addr: String,
+    #[serde(skip)]
     colored_local_addr: String,
     local_port: String,
+    #[serde(skip)]
     colored_local_port: String,
     remote_addr: String,
     remote_port: String,
     cmd: String,
     state: String,
     socket_type: String,
+    #[cfg(target_os = "linux")]
+    queue: String,
+    #[cfg(target_os = "linux")]
+    retransmits: String,
 }
 
 const STANDARD_SERVICE_PORTS: &[u16] = &[
@@ -112,6 +171,43 @@ impl Ports {
 
         ports.sort_by_key(|r| (r.local_port(), r.local_addr()));
 
+        if let Some(path) = &self.save_baseline {
+            PortBaseline::from_sockets(&ports).save(path)?;
+            return Ok(());
+        }
+
+        if let Some(path) = &self.baseline {
+            let current = PortBaseline::from_sockets(&ports);
+
+            if !path.exists() {
+                current.save(path)?;
+                writeln!(
+                    out,
+                    "No baseline found; recorded current listeners to {}",
+                    path.display()
+                )?;
+                return Ok(());
+            }
+
+            let baseline = PortBaseline::load(path)?;
+            let new_listeners = baseline.new_listeners(&current);
+
+            if new_listeners.is_empty() {
+                writeln!(out, "No new listeners since baseline was recorded")?;
+                return Ok(());
+            }
+
+            writeln!(out, "{}", "New listeners since baseline:".red().bold())?;
+            for listener in &new_listeners {
+                writeln!(out, "  {}", listener.to_string().red())?;
+            }
+
+            eyre::bail!(
+                "{} new listener(s) found that were not present in the baseline",
+                new_listeners.len()
+            );
+        }
+
         let Ports {
             display_listening,
             display_established,
@@ -121,6 +217,7 @@ impl Ports {
             display_ipv4,
             display_ipv6,
             display_unspecified,
+            format,
             ..
         } = self;
         let display_listening = display_listening || !display_established;
@@ -128,6 +225,13 @@ impl Ports {
         let (display_ipv4, display_ipv6) =
             (display_ipv4 || !display_ipv6, display_ipv6 || !display_ipv4);
 
+        #[cfg(target_os = "linux")]
+        let containers = if self.display_cgroup {
+            crate::utils::containers::get_containers()
+        } else {
+            Vec::new()
+        };
+
         #[cfg(target_os = "linux")]
         let reducer = reduce_port_list(
             out.is_terminal(),
@@ -135,8 +239,9 @@ impl Ports {
             self.display_cmdline,
             self.hide_path,
             self.display_cgroup,
+            containers,
         );
-        #[cfg(windows)]
+        #[cfg(any(windows, target_os = "macos"))]
         let reducer = reduce_port_list(
             out.is_terminal(),
             !self.no_grouping,
@@ -216,6 +321,13 @@ impl Ports {
                 false
             });
 
+        let mut resolver = self.resolve.then(ReverseDnsResolver::default);
+        let geoip = self
+            .geoip_db
+            .as_deref()
+            .map(GeoIpDatabase::open)
+            .transpose()?;
+
         let rendered_ports = rendered_ports
             .into_iter()
             .map(|port| {
@@ -267,7 +379,26 @@ impl Ports {
                             (pstr.clone(), pstr)
                         }
                     };
-                let remote_addr = port.remote_addr.map(|a| format!("{a}")).unwrap_or_default();
+                let remote_addr = port
+                    .remote_addr
+                    .map(|a| {
+                        let mut addr = format!("{a}");
+
+                        if let Some(country) = geoip.as_ref().and_then(|g| g.country(a)) {
+                            addr = format!("{addr} [{country}]");
+                        }
+
+                        if let Some(host) = resolver
+                            .as_mut()
+                            .and_then(|r| r.resolve(a))
+                            .filter(|h| h != &a.to_string())
+                        {
+                            addr = format!("{addr} ({host})");
+                        }
+
+                        addr
+                    })
+                    .unwrap_or_default();
                 let remote_port = port.remote_port.map(|p| format!("{p}")).unwrap_or_default();
                 let socket_type = port
                     .socket_type
@@ -287,10 +418,18 @@ impl Ports {
                     cmd: port.cmd,
                     state: format!("{}", port.state),
                     socket_type,
+                    #[cfg(target_os = "linux")]
+                    queue: format!("{}/{}", port.rx_queue, port.tx_queue),
+                    #[cfg(target_os = "linux")]
+                    retransmits: port.retransmits.to_string(),
                 }
             })
             .collect::<Vec<_>>();
 
+        if format.is_json() {
+            return output::print_json(&rendered_ports);
+        }
+
         let max_socket_type_len = rendered_ports
             .iter()
             .map(|p| p.socket_type.len())
@@ -341,7 +480,7 @@ impl Ports {
             (false, true) => "Executable (cgroup)",
             (false, false) => "Executable",
         };
-        #[cfg(windows)]
+        #[cfg(any(windows, target_os = "macos"))]
         let cmd_display = "Executable";
 
         if display_all || (display_tcp && display_udp) {
@@ -375,6 +514,11 @@ impl Ports {
             )?;
         }
 
+        #[cfg(target_os = "linux")]
+        if self.display_queue {
+            write!(out, "  {:>11}  {:>8}", "Rx/Tx Queue", "Retrans")?;
+        }
+
         writeln!(out, "  {:>max_pid_len$}: {}", "PIDs", cmd_display)?;
 
         for port in rendered_ports {
@@ -428,6 +572,11 @@ impl Ports {
                 )?;
             }
 
+            #[cfg(target_os = "linux")]
+            if self.display_queue {
+                write!(out, "  {:>11}  {:>8}", port.queue, port.retransmits)?;
+            }
+
             writeln!(out, "  {:>max_pid_len$}: {}", port.pids, port.cmd)?;
         }
 
@@ -449,9 +598,22 @@ fn reduce_port_list(
     display_cmdline: bool,
     hide_path: bool,
     #[cfg(target_os = "linux")] display_cgroup: bool,
+    #[cfg(target_os = "linux")] containers: Vec<crate::utils::containers::Container>,
 ) -> impl FnMut(Vec<PortGroups>, SocketRecord) -> Vec<PortGroups> {
     #[cfg(target_os = "linux")]
-    use crate::utils::ports::linux::OsSocketRecordExt;
+    use crate::utils::{containers, ports::linux::OsSocketRecordExt};
+
+    #[cfg(target_os = "linux")]
+    let describe_cgroup = move |cgroup: Option<&str>| -> String {
+        let Some(cgroup) = cgroup else {
+            return String::new();
+        };
+
+        match containers::resolve_container(cgroup, &containers) {
+            Some(c) => format!("({}: {} [{}])", c.runtime, c.name, c.image),
+            None => format!("({cgroup})"),
+        }
+    };
 
     move |mut groups: Vec<PortGroups>, record: SocketRecord| {
         #[cfg(target_os = "linux")]
@@ -476,10 +638,7 @@ fn reduce_port_list(
                     if path.is_empty() { "" } else { "/" },
                     exe,
                     args,
-                    record
-                        .cgroup()
-                        .map(|cg| format!("({cg})"))
-                        .unwrap_or("".to_string())
+                    describe_cgroup(record.cgroup())
                 )
             }
             (true, false, false) => {
@@ -514,10 +673,7 @@ fn reduce_port_list(
                     },
                     if path.is_empty() { "" } else { "/" },
                     exe,
-                    record
-                        .cgroup()
-                        .map(|cg| format!("({cg})"))
-                        .unwrap_or("".to_string())
+                    describe_cgroup(record.cgroup())
                 )
             }
             (false, false, false) => {
@@ -546,10 +702,7 @@ fn reduce_port_list(
                         .and_then(|p| p.file_name().map(|p| p.to_string_lossy().to_string()))
                         .or(record.exe().map(str::to_string))
                         .unwrap_or("".to_string()),
-                    record
-                        .cgroup()
-                        .map(|cg| format!("({cg})"))
-                        .unwrap_or("".to_string())
+                    describe_cgroup(record.cgroup())
                 )
             }
             (_, false, true) => record
@@ -562,6 +715,8 @@ fn reduce_port_list(
 
         #[cfg(windows)]
         let cmd = {
+            use crate::utils::ports::windows::OsSocketRecordExt;
+
             let cmd = record
                 .exe()
                 .unwrap_or("")
@@ -573,13 +728,36 @@ fn reduce_port_list(
                 None => ("", &*cmd),
             };
             format!(
-                "{}{}{}",
+                "{}{}{}{}",
                 if terminal {
                     path.bright_black()
                 } else {
                     path.into()
                 },
                 if path.is_empty() { "" } else { r"\" },
+                exe,
+                record
+                    .service_names()
+                    .map(|s| format!(" ({s})"))
+                    .unwrap_or_default()
+            )
+        };
+
+        #[cfg(target_os = "macos")]
+        let cmd = {
+            let exe = record.exe().unwrap_or("").to_owned();
+            let (path, exe) = match exe.rsplit_once("/") {
+                Some((path, exe)) => (path, exe),
+                None => ("", &*exe),
+            };
+            format!(
+                "{}{}{}",
+                if terminal {
+                    path.bright_black()
+                } else {
+                    path.into()
+                },
+                if path.is_empty() { "" } else { "/" },
                 exe
             )
         };
@@ -587,7 +765,7 @@ fn reduce_port_list(
         #[cfg(target_os = "linux")]
         let udp_state_matches = matches!(record.state(), SocketState::Closed | SocketState::Listen);
 
-        #[cfg(windows)]
+        #[cfg(any(windows, target_os = "macos"))]
         let udp_state_matches = true;
 
         if (record.state() == SocketState::Listen
@@ -659,6 +837,12 @@ fn reduce_port_list(
                 st => st,
             },
             socket_type: vec![record.socket_type()].into_iter().collect(),
+            #[cfg(target_os = "linux")]
+            tx_queue: record.tx_queue(),
+            #[cfg(target_os = "linux")]
+            rx_queue: record.rx_queue(),
+            #[cfg(target_os = "linux")]
+            retransmits: record.retransmits(),
         });
 
         groups
@@ -0,0 +1,333 @@
+//! Interactive TUI for `jj ports`, with sortable columns, incremental
+//! filtering, and a detail pane for the selected socket's process
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Text},
+    widgets::{Block, Clear, Paragraph, Row, Table, TableState},
+};
+
+use crate::utils::ports::{self, SocketRecord};
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    #[default]
+    Port,
+    Process,
+    State,
+    Remote,
+}
+
+impl SortColumn {
+    fn next(self) -> Self {
+        match self {
+            Self::Port => Self::Process,
+            Self::Process => Self::State,
+            Self::State => Self::Remote,
+            Self::Remote => Self::Port,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Port => "Port",
+            Self::Process => "Process",
+            Self::State => "State",
+            Self::Remote => "Remote",
+        }
+    }
+}
+
+struct Tui {
+    sockets: Vec<SocketRecord>,
+    sort: SortColumn,
+    filter: String,
+    editing_filter: bool,
+    table_state: TableState,
+    show_detail: bool,
+}
+
+impl Tui {
+    fn filtered_sorted(&self) -> Vec<&SocketRecord> {
+        let needle = self.filter.to_lowercase();
+
+        let mut rows: Vec<&SocketRecord> = self
+            .sockets
+            .iter()
+            .filter(|s| needle.is_empty() || row_text(s).to_lowercase().contains(&needle))
+            .collect();
+
+        rows.sort_by(|a, b| match self.sort {
+            SortColumn::Port => a.local_port().cmp(&b.local_port()),
+            SortColumn::Process => a.exe().unwrap_or("").cmp(b.exe().unwrap_or("")),
+            SortColumn::State => format!("{}", a.state()).cmp(&format!("{}", b.state())),
+            SortColumn::Remote => a
+                .remote_addr()
+                .map(|a| a.to_string())
+                .unwrap_or_default()
+                .cmp(&b.remote_addr().map(|a| a.to_string()).unwrap_or_default()),
+        });
+
+        rows
+    }
+
+    fn selected(&self) -> Option<&SocketRecord> {
+        self.filtered_sorted()
+            .get(self.table_state.selected().unwrap_or(0))
+            .copied()
+    }
+}
+
+fn row_text(s: &SocketRecord) -> String {
+    format!(
+        "{} {} {} {} {}",
+        s.socket_type(),
+        s.local_port(),
+        s.state(),
+        s.remote_addr().map(|a| a.to_string()).unwrap_or_default(),
+        s.exe().unwrap_or("")
+    )
+}
+
+fn remote_string(s: &SocketRecord) -> String {
+    match (s.remote_addr(), s.remote_port()) {
+        (Some(addr), Some(port)) => format!("{addr}:{port}"),
+        _ => "-".to_string(),
+    }
+}
+
+pub fn main() -> eyre::Result<()> {
+    let sockets = ports::list_ports()?;
+
+    let mut tui_state = Tui {
+        sockets,
+        sort: SortColumn::default(),
+        filter: String::new(),
+        editing_filter: false,
+        table_state: TableState::default().with_selected(0),
+        show_detail: false,
+    };
+
+    let mut terminal = ratatui::init();
+
+    loop {
+        terminal.draw(|frame| render(&mut tui_state, frame))?;
+
+        let Event::Key(key) = crossterm::event::read()? else {
+            continue;
+        };
+
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        if handle_key_event(&mut tui_state, key) {
+            break;
+        }
+    }
+
+    ratatui::restore();
+
+    Ok(())
+}
+
+fn handle_key_event(tui_state: &mut Tui, key: KeyEvent) -> bool {
+    if tui_state.editing_filter {
+        match key.code {
+            KeyCode::Enter | KeyCode::Esc => tui_state.editing_filter = false,
+            KeyCode::Backspace => {
+                tui_state.filter.pop();
+            }
+            KeyCode::Char(c) => tui_state.filter.push(c),
+            _ => {}
+        }
+        return false;
+    }
+
+    if tui_state.show_detail {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => tui_state.show_detail = false,
+            _ => {}
+        }
+        return false;
+    }
+
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => return true,
+        KeyCode::Char('/') => tui_state.editing_filter = true,
+        KeyCode::Tab | KeyCode::Char('s') => tui_state.sort = tui_state.sort.next(),
+        KeyCode::Down | KeyCode::Char('j') => {
+            let len = tui_state.filtered_sorted().len();
+            let next = tui_state
+                .table_state
+                .selected()
+                .map_or(0, |i| (i + 1).min(len.saturating_sub(1)));
+            tui_state.table_state.select(Some(next));
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            let next = tui_state
+                .table_state
+                .selected()
+                .map_or(0, |i| i.saturating_sub(1));
+            tui_state.table_state.select(Some(next));
+        }
+        KeyCode::Enter => tui_state.show_detail = true,
+        _ => {}
+    }
+
+    false
+}
+
+fn render(tui_state: &mut Tui, frame: &mut Frame) {
+    let rows = tui_state.filtered_sorted();
+
+    let header = Row::new(vec!["Proto", "Local", "Remote", "State", "PID", "Command"])
+        .style(Style::new().add_modifier(Modifier::BOLD));
+
+    let table_rows = rows.iter().map(|s| {
+        Row::new(vec![
+            format!("{}", s.socket_type()),
+            format!("{}:{}", s.local_addr(), s.local_port()),
+            remote_string(s),
+            format!("{}", s.state()),
+            s.pid().map(|p| p.to_string()).unwrap_or_default(),
+            s.exe().unwrap_or("").to_string(),
+        ])
+    });
+
+    let table = Table::new(
+        table_rows,
+        [
+            Constraint::Length(5),
+            Constraint::Length(22),
+            Constraint::Length(22),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(Style::new().bg(Color::DarkGray))
+    .block(Block::bordered().title(format!(
+        " jj ports — sort: {} — / to filter, Enter to inspect, q to quit ",
+        tui_state.sort.label()
+    )));
+
+    frame.render_stateful_widget(table, frame.area(), &mut tui_state.table_state);
+
+    if tui_state.editing_filter || !tui_state.filter.is_empty() {
+        let filter_area = Rect {
+            x: frame.area().x + 1,
+            y: frame.area().bottom().saturating_sub(1),
+            width: frame.area().width.saturating_sub(2),
+            height: 1,
+        };
+        frame.render_widget(
+            Paragraph::new(format!("/{}", tui_state.filter)),
+            filter_area,
+        );
+    }
+
+    if tui_state.show_detail {
+        render_detail(tui_state, frame);
+    }
+}
+
+fn render_detail(tui_state: &Tui, frame: &mut Frame) {
+    let area = centered_rect(80, 70, frame.area());
+
+    let Some(selected) = tui_state.selected() else {
+        return;
+    };
+
+    let pid = selected.pid();
+
+    let cwd = pid
+        .and_then(|pid| std::fs::read_link(format!("/proc/{pid}/cwd")).ok())
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| "-".to_string());
+
+    let open_fds = pid
+        .and_then(|pid| list_open_fds(pid).ok())
+        .unwrap_or_default();
+
+    let mut lines = vec![
+        Line::from(format!(
+            "{}:{} -> {}",
+            selected.local_addr(),
+            selected.local_port(),
+            remote_string(selected)
+        )),
+        Line::from(format!("state: {}", selected.state())),
+        Line::from(format!(
+            "pid: {}",
+            pid.map(|p| p.to_string()).unwrap_or_default()
+        )),
+        Line::from(format!("cmdline: {}", selected.cmdline().unwrap_or("-"))),
+        Line::from(format!("cwd: {cwd}")),
+        Line::from(""),
+        Line::from("open file descriptors:".bold()),
+    ];
+
+    lines.extend(open_fds.into_iter().map(Line::from));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(Text::from(lines))
+            .block(Block::bordered().title(" process detail — Esc/Enter to close ")),
+        area,
+    );
+}
+
+#[cfg(target_os = "linux")]
+fn list_open_fds(pid: u64) -> eyre::Result<Vec<String>> {
+    let mut fds = std::fs::read_dir(format!("/proc/{pid}/fd"))?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let target = std::fs::read_link(entry.path()).ok()?;
+            Some(format!(
+                "{}: {}",
+                entry.file_name().to_string_lossy(),
+                target.to_string_lossy()
+            ))
+        })
+        .collect::<Vec<_>>();
+    fds.sort();
+    Ok(fds)
+}
+
+#[cfg(windows)]
+fn list_open_fds(_pid: u64) -> eyre::Result<Vec<String>> {
+    Ok(vec![
+        "(open file descriptor listing unavailable on Windows)".to_string(),
+    ])
+}
+
+#[cfg(target_os = "macos")]
+fn list_open_fds(_pid: u64) -> eyre::Result<Vec<String>> {
+    Ok(vec![
+        "(open file descriptor listing unavailable on macOS)".to_string(),
+    ])
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
@@ -0,0 +1,258 @@
+use std::{
+    os::unix::fs::{MetadataExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+use colored::Colorize;
+use eyre::{Context, bail};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::utils::qx;
+
+/// Moves a suspicious file into a locked-down quarantine directory rather than deleting it
+/// outright, recording enough metadata to restore it to its original location later if it turns
+/// out to be a false positive
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Quarantine {
+    #[command(subcommand)]
+    command: QuarantineCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum QuarantineCommands {
+    /// Quarantine a file
+    #[command(visible_alias = "q")]
+    Put(PutArgs),
+
+    /// Restore a previously quarantined file to its original location
+    #[command(visible_alias = "r")]
+    Restore(RestoreArgs),
+
+    /// List everything currently in quarantine
+    #[command(visible_alias = "ls")]
+    List(ListArgs),
+}
+
+#[derive(Parser, Debug)]
+struct PutArgs {
+    /// File to quarantine
+    path: PathBuf,
+
+    /// Directory quarantined files and their metadata records are kept in
+    #[arg(long, default_value = "/var/lib/jj/quarantine")]
+    quarantine_dir: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+struct RestoreArgs {
+    /// Quarantine id to restore (as printed by `jj quarantine put` or `jj quarantine list`)
+    id: String,
+
+    /// Directory quarantined files and their metadata records are kept in
+    #[arg(long, default_value = "/var/lib/jj/quarantine")]
+    quarantine_dir: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+struct ListArgs {
+    /// Directory quarantined files and their metadata records are kept in
+    #[arg(long, default_value = "/var/lib/jj/quarantine")]
+    quarantine_dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct QuarantineRecord {
+    id: String,
+    original_path: PathBuf,
+    sha256: String,
+    mode: u32,
+    uid: u32,
+    gid: u32,
+    quarantined_at: String,
+    made_immutable: bool,
+}
+
+impl super::Command for Quarantine {
+    fn execute(self) -> eyre::Result<()> {
+        match self.command {
+            QuarantineCommands::Put(args) => put(args),
+            QuarantineCommands::Restore(args) => restore(args),
+            QuarantineCommands::List(args) => list(args),
+        }
+    }
+}
+
+fn put(args: PutArgs) -> eyre::Result<()> {
+    if !args.path.is_file() {
+        bail!("{} is not a regular file", args.path.display());
+    }
+
+    std::fs::create_dir_all(&args.quarantine_dir)
+        .with_context(|| format!("Could not create {}", args.quarantine_dir.display()))?;
+
+    let metadata = std::fs::metadata(&args.path)?;
+    let sha256 = sha256_file(&args.path)?;
+    let id = format!("{}-{}", Utc::now().format("%Y%m%dT%H%M%SZ"), &sha256[..12]);
+
+    let stored_path = args.quarantine_dir.join(&id);
+    std::fs::copy(&args.path, &stored_path)
+        .with_context(|| format!("Could not copy {} into quarantine", args.path.display()))?;
+    std::fs::set_permissions(&stored_path, std::fs::Permissions::from_mode(0o000))
+        .with_context(|| format!("Could not lock down {}", stored_path.display()))?;
+
+    let made_immutable = qx(&format!("chattr +i {}", stored_path.display()))
+        .is_ok_and(|(status, _)| status.success());
+
+    let record = QuarantineRecord {
+        id: id.clone(),
+        original_path: args.path.clone(),
+        sha256,
+        mode: metadata.mode(),
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+        quarantined_at: Utc::now().to_rfc3339(),
+        made_immutable,
+    };
+    std::fs::write(
+        record_path(&args.quarantine_dir, &id),
+        serde_json::to_string_pretty(&record)?,
+    )?;
+
+    std::fs::remove_file(&args.path)
+        .with_context(|| format!("Could not remove original {}", args.path.display()))?;
+
+    println!(
+        "{}",
+        format!(
+            "--- Quarantined {} as '{id}'{}",
+            args.path.display(),
+            if made_immutable { " (immutable)" } else { "" }
+        )
+        .green()
+    );
+
+    Ok(())
+}
+
+fn restore(args: RestoreArgs) -> eyre::Result<()> {
+    let record_path = record_path(&args.quarantine_dir, &args.id);
+    let record: QuarantineRecord = serde_json::from_str(
+        &std::fs::read_to_string(&record_path)
+            .with_context(|| format!("Could not read {}", record_path.display()))?,
+    )
+    .with_context(|| format!("Could not parse {}", record_path.display()))?;
+
+    let stored_path = args.quarantine_dir.join(&record.id);
+
+    if record.made_immutable {
+        qx(&format!("chattr -i {}", stored_path.display())).ok();
+    }
+    std::fs::set_permissions(
+        &stored_path,
+        std::fs::Permissions::from_mode(record.mode & 0o7777),
+    )?;
+
+    let actual_sha256 = sha256_file(&stored_path)?;
+    if actual_sha256 != record.sha256 {
+        bail!(
+            "Quarantined file '{}' has changed since it was quarantined, refusing to restore",
+            record.id
+        );
+    }
+
+    if let Some(parent) = record.original_path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    std::fs::copy(&stored_path, &record.original_path).with_context(|| {
+        format!(
+            "Could not restore to original path {}",
+            record.original_path.display()
+        )
+    })?;
+
+    use nix::unistd::{Gid, Uid, chown};
+    chown(
+        &record.original_path,
+        Some(Uid::from_raw(record.uid)),
+        Some(Gid::from_raw(record.gid)),
+    )
+    .ok();
+
+    std::fs::remove_file(&stored_path).ok();
+    std::fs::remove_file(&record_path).ok();
+
+    println!(
+        "{}",
+        format!(
+            "--- Restored '{}' to {}",
+            record.id,
+            record.original_path.display()
+        )
+        .green()
+    );
+
+    Ok(())
+}
+
+fn list(args: ListArgs) -> eyre::Result<()> {
+    let Ok(read_dir) = std::fs::read_dir(&args.quarantine_dir) else {
+        println!("Nothing quarantined yet");
+        return Ok(());
+    };
+
+    let mut found = false;
+    for entry in read_dir.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(record) = serde_json::from_str::<QuarantineRecord>(&contents) else {
+            continue;
+        };
+
+        found = true;
+        println!(
+            "{} {} (from {}, quarantined {})",
+            record.id.blue(),
+            record.sha256,
+            record.original_path.display(),
+            record.quarantined_at
+        );
+    }
+
+    if !found {
+        println!("Nothing quarantined yet");
+    }
+
+    Ok(())
+}
+
+fn record_path(quarantine_dir: &Path, id: &str) -> PathBuf {
+    quarantine_dir.join(format!("{id}.json"))
+}
+
+fn sha256_file(path: &Path) -> eyre::Result<String> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
@@ -0,0 +1,250 @@
+use std::{path::PathBuf, process::Command};
+
+use clap::Parser;
+use colored::Colorize;
+use eyre::Context;
+use serde::Deserialize;
+
+use crate::utils::{nft::Nft, ports};
+
+/// How urgently a finding deserves attention, used to sort the report so the most actionable
+/// items are always at the top regardless of which check produced them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    fn label(self) -> &'static str {
+        match self {
+            Severity::High => "HIGH",
+            Severity::Medium => "MEDIUM",
+            Severity::Low => "LOW",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Finding {
+    severity: Severity,
+    summary: String,
+}
+
+/// A single account flagged by `jj useradd audit --json`, mirroring the shape that command
+/// prints so we don't have to re-implement the account checks here
+#[derive(Deserialize)]
+struct AuditFinding {
+    user: String,
+    uid: u32,
+    shell: String,
+    issues: Vec<String>,
+}
+
+/// Runs `jj enum`, `jj ports`, `jj useradd audit`, and dumps the active firewall ruleset, then
+/// renders everything into one consolidated Markdown host report with the account-hygiene
+/// findings sorted by severity up top — meant to be pasted straight into an incident response
+/// ticket or inject response
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Report {
+    /// Where to write the report
+    #[arg(short, long, default_value = "/root/jj-report.md")]
+    output: PathBuf,
+}
+
+impl super::Command for Report {
+    fn execute(self) -> eyre::Result<()> {
+        let hostname = crate::utils::qx("hostname")
+            .map(|(_, s)| s.trim().to_string())
+            .unwrap_or_else(|_| "(unknown)".to_string());
+
+        let findings = collect_audit_findings().unwrap_or_else(|e| {
+            eprintln!("{} Could not run user audit: {e}", "warning:".yellow());
+            vec![]
+        });
+
+        let enum_output = run_jj(&["enum", "--no-pager"])
+            .unwrap_or_else(|e| format!("(could not run jj enum: {e})"));
+        let ports_output =
+            describe_ports().unwrap_or_else(|e| format!("(could not list ports: {e})"));
+        let firewall_output = describe_firewall()
+            .unwrap_or_else(|e| format!("(could not read firewall ruleset: {e})"));
+
+        let report = render_report(
+            &hostname,
+            &findings,
+            &enum_output,
+            &ports_output,
+            &firewall_output,
+        );
+
+        std::fs::write(&self.output, &report)
+            .with_context(|| format!("Could not write report to {}", self.output.display()))?;
+
+        println!(
+            "{}",
+            format!(
+                "--- Wrote report with {} finding(s) to {}",
+                findings.len(),
+                self.output.display()
+            )
+            .green()
+        );
+
+        Ok(())
+    }
+}
+
+/// Re-invoke this binary so we reuse the already-polished output of another `jj` subcommand
+/// instead of duplicating its logic here
+fn run_jj(args: &[&str]) -> eyre::Result<String> {
+    let exe = std::env::current_exe().context("Could not determine path to this binary")?;
+
+    let output = Command::new(&exe)
+        .args(args)
+        .output()
+        .with_context(|| format!("Could not spawn {} {}", exe.display(), args.join(" ")))?;
+
+    if !output.status.success() {
+        eyre::bail!(
+            "jj {} exited with {}: {}",
+            args.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn collect_audit_findings() -> eyre::Result<Vec<Finding>> {
+    let output = run_jj(&["useradd", "audit", "--json"])?;
+    let accounts: Vec<AuditFinding> =
+        serde_json::from_str(&output).context("Could not parse jj useradd audit --json output")?;
+
+    let mut findings = Vec::new();
+    for account in accounts {
+        for issue in &account.issues {
+            findings.push(Finding {
+                severity: classify_issue(issue),
+                summary: format!(
+                    "{} (uid {}, shell {}): {issue}",
+                    account.user, account.uid, account.shell
+                ),
+            });
+        }
+    }
+
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity));
+    Ok(findings)
+}
+
+fn classify_issue(issue: &str) -> Severity {
+    if issue.contains("UID 0") || issue.contains("empty password hash") {
+        Severity::High
+    } else if issue.contains("login shell") || issue.contains("password changed") {
+        Severity::Medium
+    } else {
+        Severity::Low
+    }
+}
+
+fn describe_ports() -> eyre::Result<String> {
+    let records = ports::list_ports()?;
+    if records.is_empty() {
+        return Ok("No open sockets found.".to_string());
+    }
+
+    let mut lines = vec![
+        "| Proto | Local | Remote | State | PID | Process |".to_string(),
+        "|---|---|---|---|---|---|".to_string(),
+    ];
+    for record in records {
+        let remote = match (record.remote_addr(), record.remote_port()) {
+            (Some(addr), Some(port)) => format!("{addr}:{port}"),
+            _ => "-".to_string(),
+        };
+
+        lines.push(format!(
+            "| {:?} | {}:{} | {remote} | {:?} | {} | {} |",
+            record.socket_type(),
+            record.local_addr(),
+            record.local_port(),
+            record.state(),
+            record
+                .pid()
+                .map(|pid| pid.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            record.cmdline().unwrap_or("-"),
+        ));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+fn describe_firewall() -> eyre::Result<String> {
+    let nft = Nft::new()?;
+    let output = nft
+        .command()
+        .arg("list ruleset")
+        .output()
+        .context("Could not run nft list ruleset")?;
+
+    if !output.status.success() {
+        eyre::bail!(
+            "nft list ruleset exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if stdout.trim().is_empty() {
+        Ok("Ruleset is empty.".to_string())
+    } else {
+        Ok(stdout)
+    }
+}
+
+fn render_report(
+    hostname: &str,
+    findings: &[Finding],
+    enum_output: &str,
+    ports_output: &str,
+    firewall_output: &str,
+) -> String {
+    let mut report = format!("# jj host report: {hostname}\n\n");
+
+    report.push_str(&format!(
+        "## Findings ({} total, highest severity first)\n\n",
+        findings.len()
+    ));
+    if findings.is_empty() {
+        report.push_str("No account-hygiene findings.\n\n");
+    } else {
+        for finding in findings {
+            report.push_str(&format!(
+                "- **{}**: {}\n",
+                finding.severity.label(),
+                finding.summary
+            ));
+        }
+        report.push('\n');
+    }
+
+    report.push_str("## Enumeration\n\n```\n");
+    report.push_str(enum_output.trim_end());
+    report.push_str("\n```\n\n");
+
+    report.push_str("## Open sockets\n\n");
+    report.push_str(ports_output.trim_end());
+    report.push_str("\n\n");
+
+    report.push_str("## Firewall ruleset\n\n```\n");
+    report.push_str(firewall_output.trim_end());
+    report.push_str("\n```\n");
+
+    report
+}
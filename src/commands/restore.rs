@@ -0,0 +1,75 @@
+use std::{fs::File, path::PathBuf};
+
+use clap::Parser;
+use colored::Colorize;
+use eyre::Context;
+use tar::Archive;
+
+use crate::utils::dry_run;
+
+/// Restore a backup created by `jj backup`
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Restore {
+    /// Archive to restore from
+    archive: PathBuf,
+
+    /// Directory to extract into
+    #[arg(short, long, default_value = ".")]
+    output: PathBuf,
+
+    /// List what would be extracted and where, without writing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl super::Command for Restore {
+    fn execute(self) -> eyre::Result<()> {
+        let file = File::open(&self.archive)
+            .with_context(|| format!("Could not open {}", self.archive.display()))?;
+
+        println!(
+            "{} {}...",
+            "--- Restoring from".blue(),
+            self.archive.display()
+        );
+
+        dry_run::step(
+            self.dry_run,
+            format!(
+                "extract {} into {}",
+                self.archive.display(),
+                self.output.display()
+            ),
+            || {
+                match self.archive.extension().and_then(|e| e.to_str()) {
+                    Some("zip") => Self::restore_zip(file, &self.output)?,
+                    Some("zst") => Archive::new(zstd::stream::read::Decoder::new(file)?)
+                        .unpack(&self.output)
+                        .context("Failed to extract zstd tarball")?,
+                    // Everything else (.tgz, .tar.gz, .tar, ...) is assumed to be a gzip
+                    // tarball, matching the default produced by `jj backup`
+                    _ => Archive::new(flate2::read::GzDecoder::new(file))
+                        .unpack(&self.output)
+                        .context("Failed to extract gzip tarball")?,
+                }
+                Ok(())
+            },
+        )?;
+
+        if !self.dry_run {
+            println!("{}", "Done restoring backup!".green().bold());
+        }
+        Ok(())
+    }
+}
+
+impl Restore {
+    fn restore_zip(file: File, output: &std::path::Path) -> eyre::Result<()> {
+        let mut archive = zip::ZipArchive::new(file).context("Failed to read zip archive")?;
+        archive
+            .extract(output)
+            .context("Failed to extract zip archive")?;
+        Ok(())
+    }
+}
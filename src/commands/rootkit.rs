@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+
+use clap::Parser;
+use colored::Colorize;
+
+use crate::utils::qx;
+
+/// Quick "is this box rooted" triage: cross-checks a handful of places rootkits commonly
+/// tamper with or hide from. Not a replacement for a real forensic sweep, just a fast signal
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Rootkit {
+    /// Only print checks that found something suspicious
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+impl super::Command for Rootkit {
+    fn execute(self) -> eyre::Result<()> {
+        let mut findings = 0;
+
+        findings += check_ld_preload(self.quiet)?;
+        findings += check_process_discrepancy(self.quiet)?;
+        findings += check_deleted_exes(self.quiet)?;
+        findings += check_hidden_modules(self.quiet)?;
+        findings += check_kernel_taint(self.quiet)?;
+
+        if findings == 0 {
+            println!("{}", "--- No rootkit indicators found".green());
+        } else {
+            println!(
+                "{}",
+                format!("--- {findings} rootkit indicator(s) found, investigate above").red()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn ok(quiet: bool, msg: &str) {
+    if !quiet {
+        println!("{} {msg}", "[ OK ]".green());
+    }
+}
+
+fn warn(msg: &str) {
+    println!("{} {msg}", "[WARN]".red());
+}
+
+/// `ld.so.preload` forces every dynamically linked process on the system to load the listed
+/// libraries, which is a classic way for a userland rootkit to hook libc functions like
+/// `readdir`/`getdents` to hide files and processes
+fn check_ld_preload(quiet: bool) -> eyre::Result<u32> {
+    match std::fs::read_to_string("/etc/ld.so.preload") {
+        Ok(contents) if !contents.trim().is_empty() => {
+            warn(&format!(
+                "/etc/ld.so.preload is non-empty: {}",
+                contents.trim()
+            ));
+            Ok(1)
+        }
+        _ => {
+            ok(quiet, "/etc/ld.so.preload is empty or absent");
+            Ok(0)
+        }
+    }
+}
+
+/// Compares PIDs the kernel hands back through `/proc`'s directory listing against what `ps`
+/// reports. A userland rootkit that hooks `readdir`/`getdents` to hide a process from `ps`
+/// still can't hide its numbered directory from a raw listing of `/proc`, so a mismatch here is
+/// a strong signal something is filtering process visibility
+fn check_process_discrepancy(quiet: bool) -> eyre::Result<u32> {
+    let proc_pids: HashSet<u32> = std::fs::read_dir("/proc")?
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .collect();
+
+    let (_, ps_out) = qx("ps -e -o pid --no-headers")?;
+    let ps_pids: HashSet<u32> = ps_out
+        .lines()
+        .filter_map(|l| l.trim().parse::<u32>().ok())
+        .collect();
+
+    let hidden_from_ps: Vec<&u32> = proc_pids.difference(&ps_pids).collect();
+
+    if hidden_from_ps.is_empty() {
+        ok(quiet, "/proc and `ps` agree on running PIDs");
+        Ok(0)
+    } else {
+        warn(&format!(
+            "PIDs present in /proc but not reported by `ps`: {hidden_from_ps:?}"
+        ));
+        Ok(1)
+    }
+}
+
+/// A process whose binary was deleted out from under it (common with self-deleting malware, or
+/// a legitimate upgrade that hasn't been restarted yet) shows "(deleted)" on the end of
+/// `/proc/<pid>/exe`'s link target
+fn check_deleted_exes(quiet: bool) -> eyre::Result<u32> {
+    let mut found = 0;
+
+    for entry in std::fs::read_dir("/proc")?.filter_map(Result::ok) {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+
+        let Ok(target) = std::fs::read_link(format!("/proc/{pid}/exe")) else {
+            continue;
+        };
+
+        if target.to_string_lossy().ends_with("(deleted)") {
+            warn(&format!(
+                "PID {pid} is running from a deleted binary: {}",
+                target.display()
+            ));
+            found += 1;
+        }
+    }
+
+    if found == 0 {
+        ok(quiet, "No running processes with a deleted exe");
+    }
+
+    Ok(found)
+}
+
+/// Kernel modules can be unlinked from one of `/proc/modules` or `/sys/module` while leaving
+/// the other intact, which is a known trick for hiding a loaded rootkit module from `lsmod`
+fn check_hidden_modules(quiet: bool) -> eyre::Result<u32> {
+    let normalize = |s: &str| s.replace('-', "_");
+
+    let proc_modules: HashSet<String> = std::fs::read_to_string("/proc/modules")
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| l.split_whitespace().next())
+        .map(normalize)
+        .collect();
+
+    let sys_modules: HashSet<String> = std::fs::read_dir("/sys/module")
+        .map(|rd| {
+            rd.filter_map(Result::ok)
+                .filter_map(|e| e.file_name().to_str().map(normalize))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let only_in_proc: Vec<&String> = proc_modules.difference(&sys_modules).collect();
+    let only_in_sys: Vec<&String> = sys_modules.difference(&proc_modules).collect();
+
+    if only_in_proc.is_empty() && only_in_sys.is_empty() {
+        ok(quiet, "/proc/modules and /sys/module agree");
+        Ok(0)
+    } else {
+        if !only_in_proc.is_empty() {
+            warn(&format!(
+                "Modules listed in /proc/modules but missing from /sys/module: {only_in_proc:?}"
+            ));
+        }
+        if !only_in_sys.is_empty() {
+            warn(&format!(
+                "Modules present in /sys/module but hidden from /proc/modules: {only_in_sys:?}"
+            ));
+        }
+        Ok(1)
+    }
+}
+
+/// The kernel taint bitmask is set when, among other things, a module is loaded that isn't
+/// signed or isn't in the upstream tree (bit 12) — not proof of compromise, but worth a look
+fn check_kernel_taint(quiet: bool) -> eyre::Result<u32> {
+    let tainted: u64 = std::fs::read_to_string("/proc/sys/kernel/tainted")
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0);
+
+    if tainted == 0 {
+        ok(quiet, "Kernel is not tainted");
+        return Ok(0);
+    }
+
+    let mut reasons = vec![];
+    if tainted & (1 << 12) != 0 {
+        reasons.push("out-of-tree module loaded");
+    }
+    if tainted & (1 << 13) != 0 {
+        reasons.push("unsigned module loaded");
+    }
+    if reasons.is_empty() {
+        warn(&format!("Kernel tainted (bitmask {tainted})"));
+    } else {
+        warn(&format!(
+            "Kernel tainted (bitmask {tainted}): {}",
+            reasons.join(", ")
+        ));
+    }
+
+    Ok(1)
+}
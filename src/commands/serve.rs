@@ -1,18 +1,31 @@
-use std::{fmt::Write, net::SocketAddr, path::PathBuf, str::FromStr};
+use std::{
+    fmt::Write,
+    io::Read,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
 use clap::Parser;
+use colored::Colorize;
 use eyre::Context;
 use futures_util::TryStreamExt;
 use http_body_util::{BodyExt, Full, StreamBody, combinators::BoxBody};
 use hyper::body::{Bytes, Frame};
+use hyper::header::{AUTHORIZATION, CONTENT_LENGTH};
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
-use hyper::{Request, Response, StatusCode};
+use hyper::{Method, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
+use rcgen::CertifiedKey;
+use rustls::ServerConfig;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, pem::PemObject};
 use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
 use tokio_stream::{StreamExt, wrappers::ReadDirStream};
 use tokio_util::io::ReaderStream;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -31,6 +44,302 @@ pub struct Serve {
     /// Path to store log entries to, if you don't trust your network
     #[arg(short, long)]
     log_file: Option<PathBuf>,
+
+    /// Serve over HTTPS instead of plaintext HTTP. Without --cert and --key, a self-signed
+    /// certificate is generated for the lifetime of this process
+    #[arg(long)]
+    tls: bool,
+
+    /// PEM-encoded TLS certificate (chain) to serve with instead of a self-signed one. Requires
+    /// --key
+    #[arg(long, requires = "key")]
+    cert: Option<PathBuf>,
+
+    /// PEM-encoded TLS private key to serve with instead of a self-signed one. Requires --cert
+    #[arg(long, requires = "cert")]
+    key: Option<PathBuf>,
+
+    /// Allow PUT/POST requests to write files into the served directory, so other hosts can
+    /// push backups, logs, or artifacts back with a single curl command
+    #[arg(long)]
+    allow_upload: bool,
+
+    /// Reject uploads larger than this many bytes
+    #[arg(long, default_value_t = 1024 * 1024 * 1024)]
+    max_upload_size: u64,
+
+    /// Require this bearer token in the Authorization header before accepting an upload
+    #[arg(long)]
+    upload_token: Option<String>,
+
+    /// Also expose the embedded busybox/nft/zsh/socat binaries and this jj binary at stable,
+    /// architecture-scoped paths (/jj-tools/<arch>/<name>) so other boxes can bootstrap with a
+    /// single wget
+    #[arg(long)]
+    tools: bool,
+
+    /// Accept `jj agent` reports at /jj-agent/report and serve the aggregated fleet view as
+    /// JSON at /jj-agent/fleet, so many hosts' ports/enum/stat summaries can be collected at
+    /// one central jj instance
+    #[arg(long)]
+    agent: bool,
+
+    /// Require this bearer token in the Authorization header before accepting an agent report
+    #[arg(long)]
+    agent_token: Option<String>,
+
+    /// Restrict connections to these IPv4 CIDR ranges (e.g. 10.0.0.0/8). May be given multiple
+    /// times; if never given, every client is allowed
+    #[arg(long = "allow-cidr")]
+    allow_cidr: Vec<String>,
+
+    /// Connections accepted per second per client IP before rate limiting kicks in
+    #[arg(long, default_value_t = 10.0)]
+    rate_limit: f64,
+
+    /// Burst of connections a client IP may make before rate limiting kicks in
+    #[arg(long, default_value_t = 20.0)]
+    rate_limit_burst: f64,
+
+    /// Mint a one-shot/expiring share link for this file and print it on startup, instead of (or
+    /// alongside) normal directory browsing. May be repeated
+    #[arg(long = "share")]
+    share: Vec<PathBuf>,
+
+    /// Number of downloads a --share link allows before it stops working
+    #[arg(long, default_value_t = 1)]
+    share_downloads: u32,
+
+    /// How long a --share link remains valid for, starting now. If omitted, the link only
+    /// expires once --share-downloads is exhausted
+    #[arg(long)]
+    share_expire: Option<humantime::Duration>,
+
+    /// Also render a QR code for each printed cheat-sheet URL, so the address can be scanned
+    /// from a phone instead of typed
+    #[arg(long)]
+    qr: bool,
+}
+
+/// Per-connection upload settings, threaded from the CLI args into every `respond` call
+#[derive(Clone)]
+struct UploadConfig {
+    enabled: bool,
+    max_size: u64,
+    token: Option<String>,
+}
+
+/// Parse an IPv4 CIDR such as `10.0.0.0/8` into a `(network, mask)` pair, both in host byte
+/// order, so membership can be tested with a single `&`
+fn parse_cidr(spec: &str) -> eyre::Result<(u32, u32)> {
+    let (ip, prefix) = spec.split_once('/').unwrap_or((spec, "32"));
+
+    let ip = ip
+        .parse::<std::net::Ipv4Addr>()
+        .with_context(|| format!("Could not parse IP in --allow-cidr {spec}"))?;
+    let prefix = prefix
+        .parse::<u32>()
+        .with_context(|| format!("Could not parse prefix length in --allow-cidr {spec}"))?;
+
+    if prefix > 32 {
+        eyre::bail!("Prefix length in --allow-cidr {spec} must be between 0 and 32");
+    }
+
+    let mask = if prefix == 0 {
+        0
+    } else {
+        0xFFFF_FFFFu32 << (32 - prefix)
+    };
+
+    Ok((u32::from(ip) & mask, mask))
+}
+
+/// Whether `addr` is covered by at least one of `allowed`, or `allowed` is empty (meaning no
+/// restriction is configured)
+fn cidr_allows(allowed: &[(u32, u32)], addr: SocketAddr) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+
+    let SocketAddr::V4(addr) = addr else {
+        return false;
+    };
+
+    let ip = u32::from(*addr.ip());
+    allowed.iter().any(|(net, mask)| (ip & mask) == *net)
+}
+
+/// A simple per-client-IP token bucket, used to keep the file server from being trivially
+/// hammered without needing a full reverse proxy in front of it
+struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    buckets:
+        std::sync::Mutex<std::collections::HashMap<std::net::IpAddr, (f64, std::time::Instant)>>,
+}
+
+impl RateLimiter {
+    fn new(rate: f64, burst: f64) -> Self {
+        Self {
+            rate,
+            burst,
+            buckets: std::sync::Mutex::new(std::collections::HashMap::new()),
+        }
+    }
+
+    /// Consumes a token for `ip`, returning whether it was available. Fails open (allows the
+    /// connection) if the internal lock is poisoned, since this is a best-effort protection
+    fn allow(&self, ip: std::net::IpAddr) -> bool {
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return true;
+        };
+
+        let now = std::time::Instant::now();
+        let (tokens, last_refill) = buckets.entry(ip).or_insert_with(|| (self.burst, now));
+
+        let elapsed = now.duration_since(*last_refill).as_secs_f64();
+        *tokens = (*tokens + elapsed * self.rate).min(self.burst);
+        *last_refill = now;
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A single minted share link: the file it serves, when it expires, and how many downloads it
+/// has left
+struct ShareLink {
+    path: PathBuf,
+    expires_at: Option<std::time::Instant>,
+    downloads_remaining: std::sync::Mutex<u32>,
+}
+
+impl ShareLink {
+    /// Atomically checks expiry and remaining downloads, consuming one download if the link is
+    /// still valid. Fails closed (rejects) on a poisoned lock, since unlike the rate limiter this
+    /// guards a one-shot secret rather than being a best-effort courtesy
+    fn try_consume(&self) -> bool {
+        if self
+            .expires_at
+            .is_some_and(|t| std::time::Instant::now() >= t)
+        {
+            return false;
+        }
+
+        let Ok(mut remaining) = self.downloads_remaining.lock() else {
+            return false;
+        };
+
+        if *remaining == 0 {
+            return false;
+        }
+
+        *remaining -= 1;
+        true
+    }
+}
+
+/// All share links minted for this server's lifetime, keyed by their random token
+struct ShareStore {
+    links: std::collections::HashMap<String, ShareLink>,
+}
+
+/// Most recent report received from each host running `jj agent --server`, keyed by hostname
+struct FleetStore {
+    reports: std::sync::Mutex<std::collections::HashMap<String, crate::utils::agent::AgentReport>>,
+}
+
+impl FleetStore {
+    fn new() -> Self {
+        Self {
+            reports: std::sync::Mutex::new(std::collections::HashMap::new()),
+        }
+    }
+}
+
+/// Mint a one-shot/expiring share token for each `--share` path, returning the store used to
+/// serve them plus the `(token, path)` pairs to log on startup
+fn build_share_store(
+    paths: &[PathBuf],
+    max_downloads: u32,
+    expire: Option<std::time::Duration>,
+) -> eyre::Result<(ShareStore, Vec<(String, PathBuf)>)> {
+    use rand::Rng;
+
+    let mut links = std::collections::HashMap::new();
+    let mut minted = Vec::new();
+    let mut rng = rand::rng();
+
+    for path in paths {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Could not find --share file {}", path.display()))?;
+
+        let token: String = (0..32)
+            .map(|_| rng.sample(rand::distr::Alphanumeric) as char)
+            .collect();
+
+        links.insert(
+            token.clone(),
+            ShareLink {
+                path: path.clone(),
+                expires_at: expire.map(|d| std::time::Instant::now() + d),
+                downloads_remaining: std::sync::Mutex::new(max_downloads),
+            },
+        );
+        minted.push((token, path));
+    }
+
+    Ok((ShareStore { links }, minted))
+}
+
+/// Best-effort guess at the address a client elsewhere on the network would use to reach this
+/// host, found by asking the OS which local address it would route a packet to a public IP from.
+/// No packet is actually sent; `UdpSocket::connect` only resolves a route. A box with several
+/// client-facing interfaces will still need to substitute the right one by hand
+fn primary_local_ip() -> Option<std::net::IpAddr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+/// Print ready-to-paste wget/curl/certutil/PowerShell one-liners for fetching a file from this
+/// server, for every address a client is likely able to reach it on, to make bootstrapping a
+/// fresh box faster. Printed directly to stdout (not routed through tracing) so the commands can
+/// be copy-pasted without log noise
+fn print_cheat_sheet(scheme: &str, port: u16, qr: bool) {
+    let mut addresses = vec![std::net::IpAddr::from(std::net::Ipv4Addr::LOCALHOST)];
+    if let Some(primary) = primary_local_ip()
+        && !addresses.contains(&primary)
+    {
+        addresses.push(primary);
+    }
+
+    for addr in addresses {
+        let url = format!("{scheme}://{addr}:{port}/<file>");
+
+        println!("{} {url}", "---".blue());
+        println!("wget {url}");
+        println!("curl -O {url}");
+        println!("certutil -urlcache -split -f {url} <file>");
+        println!("powershell -c \"iwr {url} -OutFile <file>\"");
+
+        if qr {
+            match qrcode::QrCode::new(&url) {
+                Ok(code) => println!(
+                    "{}",
+                    code.render::<qrcode::render::unicode::Dense1x2>()
+                        .quiet_zone(true)
+                        .build()
+                ),
+                Err(e) => tracing::warn!("Could not render QR code for {url}: {e}"),
+            }
+        }
+    }
 }
 
 impl super::Command for Serve {
@@ -100,29 +409,207 @@ async fn serve(args: Serve) -> eyre::Result<()> {
     path.extend(&root_server);
     let path = path.canonicalize()?;
 
-    tracing::info!("Serving HTTP on {addr} from {}", path.display());
+    let tls_acceptor = if args.tls {
+        Some(build_tls_acceptor(
+            args.cert.as_deref(),
+            args.key.as_deref(),
+        )?)
+    } else {
+        None
+    };
+
+    let upload = UploadConfig {
+        enabled: args.allow_upload,
+        max_size: args.max_upload_size,
+        token: args.upload_token,
+    };
+
+    let tools = args.tools;
+
+    let agent = args.agent;
+    let agent_token = args.agent_token;
+    let fleet_store = Arc::new(FleetStore::new());
+
+    let allow_cidr = args
+        .allow_cidr
+        .iter()
+        .map(|spec| parse_cidr(spec))
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    let rate_limiter = Arc::new(RateLimiter::new(args.rate_limit, args.rate_limit_burst));
+
+    let (share_store, minted_shares) = build_share_store(
+        &args.share,
+        args.share_downloads,
+        args.share_expire.map(Into::into),
+    )?;
+    let share_store = Arc::new(share_store);
+
+    tracing::info!(
+        "Serving {} on {addr} from {}{}{}",
+        if tls_acceptor.is_some() {
+            "HTTPS"
+        } else {
+            "HTTP"
+        },
+        path.display(),
+        if upload.enabled {
+            " (uploads allowed)"
+        } else {
+            ""
+        },
+        if tools { " (/jj-tools available)" } else { "" },
+    );
+
+    if agent {
+        tracing::info!(
+            "Accepting jj agent reports at /jj-agent/report, fleet view at /jj-agent/fleet"
+        );
+    }
+
+    let scheme = if tls_acceptor.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    for (token, path) in &minted_shares {
+        tracing::info!(
+            "Share link for {}: {scheme}://<host>:{}/jj-share/{token} ({} download(s){})",
+            path.display(),
+            args.port,
+            args.share_downloads,
+            args.share_expire
+                .map(|d| format!(", expires in {d}"))
+                .unwrap_or_default()
+        );
+    }
+
+    print_cheat_sheet(scheme, args.port, args.qr);
 
     loop {
         let (stream, client) = listener.accept().await?;
 
-        let io = TokioIo::new(stream);
+        if !cidr_allows(&allow_cidr, client) {
+            tracing::warn!(
+                client = client.to_string(),
+                "Rejected connection not covered by --allow-cidr"
+            );
+            continue;
+        }
+
+        if !rate_limiter.allow(client.ip()) {
+            tracing::warn!(
+                client = client.to_string(),
+                "Rejected connection; rate limited"
+            );
+            continue;
+        }
 
         let path = path.clone();
+        let tls_acceptor = tls_acceptor.clone();
+        let upload = upload.clone();
+        let share_store = share_store.clone();
+        let agent_token = agent_token.clone();
+        let fleet_store = fleet_store.clone();
 
         tokio::task::spawn(async move {
-            if let Err(err) = http1::Builder::new()
-                .serve_connection(
-                    io,
-                    service_fn(move |req| respond(path.clone(), req, client)),
-                )
-                .await
-            {
+            let result = match tls_acceptor {
+                Some(acceptor) => match acceptor.accept(stream).await {
+                    Ok(stream) => {
+                        let agent_token = agent_token.clone();
+                        let fleet_store = fleet_store.clone();
+                        http1::Builder::new()
+                            .serve_connection(
+                                TokioIo::new(stream),
+                                service_fn(move |req| {
+                                    respond(
+                                        path.clone(),
+                                        upload.clone(),
+                                        tools,
+                                        share_store.clone(),
+                                        agent,
+                                        agent_token.clone(),
+                                        fleet_store.clone(),
+                                        req,
+                                        client,
+                                    )
+                                }),
+                            )
+                            .await
+                    }
+                    Err(e) => {
+                        tracing::warn!(client = client.to_string(), "TLS handshake failed: {e}");
+                        return;
+                    }
+                },
+                None => {
+                    http1::Builder::new()
+                        .serve_connection(
+                            TokioIo::new(stream),
+                            service_fn(move |req| {
+                                respond(
+                                    path.clone(),
+                                    upload.clone(),
+                                    tools,
+                                    share_store.clone(),
+                                    agent,
+                                    agent_token.clone(),
+                                    fleet_store.clone(),
+                                    req,
+                                    client,
+                                )
+                            }),
+                        )
+                        .await
+                }
+            };
+
+            if let Err(err) = result {
                 eprintln!("Error serving connection: {err:?}");
             }
         });
     }
 }
 
+/// Build a rustls server TLS config from a provided cert/key pair, or a freshly generated
+/// self-signed certificate if neither is given
+fn build_tls_acceptor(cert: Option<&Path>, key: Option<&Path>) -> eyre::Result<TlsAcceptor> {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let (cert_chain, private_key): (Vec<CertificateDer<'static>>, PrivateKeyDer<'static>) =
+        match (cert, key) {
+            (Some(cert), Some(key)) => {
+                let cert_chain = CertificateDer::pem_file_iter(cert)
+                    .with_context(|| format!("Could not read certificate {}", cert.display()))?
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| format!("Could not parse certificate {}", cert.display()))?;
+                let private_key = PrivateKeyDer::from_pem_file(key)
+                    .with_context(|| format!("Could not read private key {}", key.display()))?;
+
+                (cert_chain, private_key)
+            }
+            _ => {
+                let CertifiedKey { cert, signing_key } =
+                    rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+                        .context("Could not generate a self-signed TLS certificate")?;
+
+                tracing::info!("Generated a self-signed TLS certificate for this session");
+
+                (
+                    vec![cert.der().clone()],
+                    PrivatePkcs8KeyDer::from(signing_key).into(),
+                )
+            }
+        };
+
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, private_key)
+        .context("Could not build TLS server configuration")?;
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
 type ServeResponse = eyre::Result<Response<BoxBody<Bytes, std::io::Error>>>;
 
 fn not_found() -> ServeResponse {
@@ -135,11 +622,45 @@ fn not_found() -> ServeResponse {
         .body(body)?)
 }
 
+fn simple_status(status: StatusCode, message: &'static str) -> ServeResponse {
+    let body = Full::new(Bytes::from(message))
+        .map_err(std::io::Error::other)
+        .boxed();
+
+    Ok(Response::builder().status(status).body(body)?)
+}
+
 async fn respond(
     root_path: PathBuf,
+    upload: UploadConfig,
+    tools: bool,
+    share: Arc<ShareStore>,
+    agent: bool,
+    agent_token: Option<String>,
+    fleet: Arc<FleetStore>,
     req: Request<hyper::body::Incoming>,
     client: SocketAddr,
 ) -> ServeResponse {
+    if tools && req.uri().path().starts_with("/jj-tools/") {
+        return respond_tool(req.uri().path()).await;
+    }
+
+    if let Some(token) = req.uri().path().strip_prefix("/jj-share/") {
+        return respond_share(&share, token, client).await;
+    }
+
+    if agent && req.uri().path() == "/jj-agent/report" {
+        return respond_agent_report(&fleet, agent_token.as_deref(), req, client).await;
+    }
+
+    if agent && req.uri().path() == "/jj-agent/fleet" {
+        return respond_agent_fleet(&fleet).await;
+    }
+
+    if matches!(req.method(), &Method::PUT | &Method::POST) {
+        return respond_upload(root_path, upload, req, client).await;
+    }
+
     let mut path = root_path.clone();
     let uri = req.uri();
 
@@ -549,3 +1070,328 @@ async fn respond_file(path: PathBuf) -> ServeResponse {
 
     Ok(response?)
 }
+
+async fn respond_upload(
+    root_path: PathBuf,
+    upload: UploadConfig,
+    req: Request<hyper::body::Incoming>,
+    client: SocketAddr,
+) -> ServeResponse {
+    if !upload.enabled {
+        tracing::warn!(
+            client = client.to_string(),
+            code = 405,
+            "Rejected upload; --allow-upload is not set"
+        );
+        return simple_status(StatusCode::METHOD_NOT_ALLOWED, "405");
+    }
+
+    if let Some(token) = &upload.token {
+        let authorized = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            == Some(format!("Bearer {token}").as_str());
+
+        if !authorized {
+            tracing::warn!(
+                client = client.to_string(),
+                code = 401,
+                "Rejected upload with a missing or incorrect token"
+            );
+            return simple_status(StatusCode::UNAUTHORIZED, "401");
+        }
+    }
+
+    if let Some(len) = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        && len > upload.max_size
+    {
+        tracing::warn!(
+            client = client.to_string(),
+            code = 413,
+            len,
+            "Rejected upload exceeding --max-upload-size"
+        );
+        return simple_status(StatusCode::PAYLOAD_TOO_LARGE, "413");
+    }
+
+    let Ok(uri) = urlencoding::decode(req.uri().path()) else {
+        tracing::warn!(
+            client = client.to_string(),
+            code = 400,
+            "Decoding URL components failed"
+        );
+        return simple_status(StatusCode::BAD_REQUEST, "400");
+    };
+    let uri = uri.to_string();
+
+    let mut path = root_path.clone();
+    path.push(uri.trim_start_matches('/'));
+
+    let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) else {
+        return simple_status(StatusCode::BAD_REQUEST, "400");
+    };
+
+    if let Err(e) = tokio::fs::create_dir_all(parent).await {
+        tracing::warn!(
+            client = client.to_string(),
+            code = 400,
+            uri,
+            "Could not create directory for upload: {e}"
+        );
+        return simple_status(StatusCode::BAD_REQUEST, "400");
+    }
+
+    let Ok(parent) = parent.canonicalize() else {
+        return simple_status(StatusCode::BAD_REQUEST, "400");
+    };
+
+    if !parent.starts_with(&root_path) {
+        tracing::warn!(
+            client = client.to_string(),
+            code = 404,
+            uri,
+            path = format!("{}", parent.display()),
+            root_path = format!("{}", root_path.display()),
+            "LFI attempted via upload; consider blocking client"
+        );
+        return not_found();
+    }
+
+    let Some(file_name) = path.file_name() else {
+        return simple_status(StatusCode::BAD_REQUEST, "400");
+    };
+    let path = parent.join(file_name);
+
+    let body = req
+        .into_body()
+        .collect()
+        .await
+        .context("Could not read upload body")?
+        .to_bytes();
+
+    if u64::try_from(body.len()).unwrap_or(u64::MAX) > upload.max_size {
+        tracing::warn!(
+            client = client.to_string(),
+            code = 413,
+            len = body.len(),
+            "Rejected upload exceeding --max-upload-size"
+        );
+        return simple_status(StatusCode::PAYLOAD_TOO_LARGE, "413");
+    }
+
+    tokio::fs::write(&path, &body)
+        .await
+        .with_context(|| format!("Could not write uploaded file {}", path.display()))?;
+
+    tracing::info!(
+        client = client.to_string(),
+        code = 201,
+        uri,
+        bytes = body.len(),
+        "Accepted upload"
+    );
+
+    simple_status(StatusCode::CREATED, "ok")
+}
+
+/// Accept a `jj agent` report, authenticated the same way `respond_upload` authenticates an
+/// upload, and store it keyed by hostname so the latest snapshot of every host is always what
+/// `/jj-agent/fleet` serves
+async fn respond_agent_report(
+    fleet: &FleetStore,
+    agent_token: Option<&str>,
+    req: Request<hyper::body::Incoming>,
+    client: SocketAddr,
+) -> ServeResponse {
+    if !matches!(req.method(), &Method::POST) {
+        return simple_status(StatusCode::METHOD_NOT_ALLOWED, "405");
+    }
+
+    if let Some(token) = agent_token {
+        let authorized = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            == Some(format!("Bearer {token}").as_str());
+
+        if !authorized {
+            tracing::warn!(
+                client = client.to_string(),
+                code = 401,
+                "Rejected agent report with a missing or incorrect token"
+            );
+            return simple_status(StatusCode::UNAUTHORIZED, "401");
+        }
+    }
+
+    let body = req
+        .into_body()
+        .collect()
+        .await
+        .context("Could not read agent report body")?
+        .to_bytes();
+
+    let report: crate::utils::agent::AgentReport = match serde_json::from_slice(&body) {
+        Ok(report) => report,
+        Err(e) => {
+            tracing::warn!(
+                client = client.to_string(),
+                code = 400,
+                "Could not parse agent report: {e}"
+            );
+            return simple_status(StatusCode::BAD_REQUEST, "400");
+        }
+    };
+
+    tracing::info!(
+        client = client.to_string(),
+        code = 201,
+        hostname = report.hostname,
+        "Accepted agent report"
+    );
+
+    let Ok(mut reports) = fleet.reports.lock() else {
+        return simple_status(StatusCode::INTERNAL_SERVER_ERROR, "500");
+    };
+    reports.insert(report.hostname.clone(), report);
+
+    simple_status(StatusCode::CREATED, "ok")
+}
+
+/// Serve the latest report from every host that has checked in, as one JSON array, so a fleet
+/// can be triaged from a single view instead of polling each host individually
+async fn respond_agent_fleet(fleet: &FleetStore) -> ServeResponse {
+    let Ok(reports) = fleet.reports.lock() else {
+        return simple_status(StatusCode::INTERNAL_SERVER_ERROR, "500");
+    };
+
+    let reports: Vec<_> = reports.values().collect();
+    let json = serde_json::to_vec(&reports).context("Could not serialize fleet report")?;
+
+    let body = Full::new(Bytes::from(json))
+        .map_err(std::io::Error::other)
+        .boxed();
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .body(body)?)
+}
+
+/// Serve a file minted with `--share`, consuming one of its remaining downloads. 404s (rather
+/// than any more specific status) whether the token is unknown, expired, or exhausted, so a
+/// client probing for valid tokens can't tell the difference
+async fn respond_share(share: &ShareStore, token: &str, client: SocketAddr) -> ServeResponse {
+    let Some(link) = share.links.get(token) else {
+        return not_found();
+    };
+
+    if !link.try_consume() {
+        tracing::warn!(
+            client = client.to_string(),
+            "Rejected expired or exhausted share link"
+        );
+        return not_found();
+    }
+
+    tracing::info!(
+        client = client.to_string(),
+        path = format!("{}", link.path.display()),
+        "Serving share link download"
+    );
+
+    respond_file(link.path.clone()).await
+}
+
+/// Serve one of the binaries bundled into this jj build from a stable path:
+/// `/jj-tools/<arch>/busybox`, `/jj-tools/<arch>/nft`, `/jj-tools/<arch>/zsh`,
+/// `/jj-tools/<arch>/socat`, or `/jj-tools/<arch>/jj` for this running binary itself. Every
+/// bundled tool carries both x86_64 and aarch64 variants, so `<arch>` can be either one
+/// regardless of which architecture `jj serve` itself is running on; only the `jj` binary itself
+/// is restricted to this build's own `std::env::consts::ARCH`
+async fn respond_tool(path: &str) -> ServeResponse {
+    let Some((arch, name)) = path
+        .strip_prefix("/jj-tools/")
+        .and_then(|rest| rest.split_once('/'))
+    else {
+        return not_found();
+    };
+
+    if name == "jj" {
+        // This running binary only ever contains its own architecture, unlike the bundled
+        // tools below, which carry both x86_64 and aarch64 variants
+        if arch != std::env::consts::ARCH {
+            return not_found();
+        }
+
+        let current_exe =
+            std::env::current_exe().context("Could not find the current jj binary")?;
+        return respond_file(current_exe).await;
+    }
+
+    let Some(gzipped) = embedded_tool_bytes(name, arch) else {
+        return not_found();
+    };
+
+    let mut raw = Vec::new();
+    flate2::read::GzDecoder::new(gzipped)
+        .read_to_end(&mut raw)
+        .context("Could not decompress embedded tool")?;
+
+    Ok(Response::builder().status(StatusCode::OK).body(
+        Full::new(Bytes::from(raw))
+            .map_err(std::io::Error::other)
+            .boxed(),
+    )?)
+}
+
+/// Gzipped bytes for an embedded tool by name and requested architecture (`x86_64`/`amd64` or
+/// `aarch64`/`arm64`). Every bundled tool carries both architectures, so a client doesn't need to
+/// match the arch `jj serve` itself happens to be running on. Busybox, nft, zsh, and socat are
+/// only bundled on Unix builds, so this always reports unknown on Windows
+fn embedded_tool_bytes(name: &str, arch: &str) -> Option<&'static [u8]> {
+    #[cfg(unix)]
+    {
+        match name {
+            "busybox" => {
+                return crate::utils::bytes_for_arch(
+                    crate::utils::busybox::BUSYBOX_BYTES_X86_64,
+                    crate::utils::busybox::BUSYBOX_BYTES_AARCH64,
+                    arch,
+                );
+            }
+            "nft" => {
+                return crate::utils::bytes_for_arch(
+                    crate::utils::nft::NFT_BYTES_X86_64,
+                    crate::utils::nft::NFT_BYTES_AARCH64,
+                    arch,
+                );
+            }
+            #[cfg(feature = "bundled-tools")]
+            "zsh" => {
+                return crate::utils::bytes_for_arch(
+                    super::zsh::ZSH_BYTES_X86_64,
+                    super::zsh::ZSH_BYTES_AARCH64,
+                    arch,
+                );
+            }
+            #[cfg(feature = "bundled-tools")]
+            "socat" => {
+                return crate::utils::bytes_for_arch(
+                    crate::utils::socat::SOCAT_BYTES_X86_64,
+                    crate::utils::socat::SOCAT_BYTES_AARCH64,
+                    arch,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let _ = (name, arch);
+    None
+}
@@ -0,0 +1,28 @@
+use clap::Parser;
+
+use crate::utils::socat;
+
+/// Runs an embedded copy of socat, for port forwarding, relays, and quick listeners on boxes
+/// that don't have any usable netcat
+///
+/// Use it by specifying -- and then arguments to pass to socat, e.g.:
+///
+/// ```sh
+/// jj socat -- tcp-listen:4444,reuseaddr,fork exec:/bin/sh
+/// ```
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Socat {
+    /// Arguments to pass to the socat binary
+    args: Vec<String>,
+}
+
+impl super::Command for Socat {
+    fn execute(self) -> eyre::Result<()> {
+        let socat = socat::Socat::new()?;
+
+        socat.command().args(self.args).spawn()?.wait()?;
+
+        Ok(())
+    }
+}
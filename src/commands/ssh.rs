@@ -1,4 +1,55 @@
-use clap::{Parser, Subcommand};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{copy, remove_file, rename},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use colored::Colorize;
+use eyre::{Context, bail};
+use nix::unistd::geteuid;
+use russh::keys::ssh_key::{
+    Algorithm, HashAlg, LineEnding, PrivateKey, PublicKey,
+    certificate::{self, CertType},
+    rand_core::OsRng,
+};
+use serde::Serialize;
+
+use crate::{
+    strvec,
+    utils::{
+        nft::Nft,
+        os_version::get_distro,
+        packages::{DownloadSettings, install_apt_packages, install_dnf_packages},
+        parallel::{TaskOutcome, run_bounded},
+        passwd::load_users,
+        qx,
+    },
+};
+
+/// Where `jj ssh harden` stashes the pre-hardening sshd_config for rollback
+const HARDEN_BACKUP: &str = "/etc/ssh/sshd_config.jj-backup";
+/// Touching this file before the rollback timer expires keeps the hardened config in place
+const HARDEN_MARKER: &str = "/etc/ssh/.jj-harden-confirmed";
+
+/// Modern, widely-supported algorithm lists; trims legacy ciphers/MACs/KEX known to be weak
+const SANE_KEX: &str =
+    "curve25519-sha256,curve25519-sha256@libssh.org,diffie-hellman-group16-sha512";
+const SANE_CIPHERS: &str =
+    "chacha20-poly1305@openssh.com,aes256-gcm@openssh.com,aes128-gcm@openssh.com";
+const SANE_MACS: &str = "hmac-sha2-512-etm@openssh.com,hmac-sha2-256-etm@openssh.com";
+
+/// Restart sshd across the handful of service/unit names it's commonly installed under
+fn restart_sshd() -> eyre::Result<()> {
+    crate::utils::system(
+        "systemctl restart sshd 2>/dev/null || systemctl restart ssh 2>/dev/null || service sshd restart 2>/dev/null || service ssh restart",
+    )
+    .context("Could not restart sshd")?;
+
+    Ok(())
+}
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
@@ -11,6 +62,52 @@ pub struct Ssh {
 pub enum SshCommands {
     /// Perform a service check against an SSH daemon
     Check(crate::checks::ssh::SshTroubleshooter),
+
+    /// Rewrite sshd_config with sane defaults, validated and backed out automatically if not
+    /// confirmed in time
+    Harden(Harden),
+
+    /// Confirm a hardened sshd_config from a previous `jj ssh harden`, cancelling its rollback
+    ConfirmHarden(ConfirmHarden),
+
+    /// Push a public key to many hosts concurrently for initial fleet setup
+    DeployKeys(DeployKeys),
+
+    /// Audit every user's authorized_keys against an approved set, and optionally prune the rest
+    KeysAudit(KeysAudit),
+
+    /// Provision TOTP two-factor authentication for SSH logins via pam_google_authenticator
+    Totp(Totp),
+
+    /// Generate a new SSH certificate authority keypair
+    CaInit(CaInit),
+
+    /// Sign a user or host public key with the CA, producing a time-limited certificate
+    SignCert(SignCert),
+
+    /// Configure sshd to trust certificates signed by a CA, so principals no longer need to be
+    /// listed in authorized_keys individually
+    TrustCa(TrustCa),
+
+    /// Snapshot the host keys of every team machine into a curated known_hosts file
+    KnownHostsSnapshot(KnownHostsSnapshot),
+
+    /// Re-scan host keys and alert on anything that doesn't match a previous snapshot
+    KnownHostsVerify(KnownHostsVerify),
+
+    /// Push a curated known_hosts file out to every operator
+    DistributeKnownHosts(DistributeKnownHosts),
+
+    /// Disable password authentication and restrict logins to a given user list in one shot,
+    /// with the same timed rollback safety net as `jj ssh harden`
+    Lockdown(Lockdown),
+
+    /// Generate ~/.ssh/config blocks that route every host in a team inventory through a bastion
+    BastionConfig(BastionConfig),
+
+    /// Configure pam_faillock thresholds for SSH, and optionally block repeat offenders at the
+    /// firewall, without installing fail2ban
+    FailLock(FailLock),
 }
 
 impl super::Command for Ssh {
@@ -22,6 +119,1669 @@ impl super::Command for Ssh {
                 t.run_cli(&ssh_troubleshooter)?;
                 Ok(())
             }
+            SshCommands::Harden(harden) => harden.execute(),
+            SshCommands::ConfirmHarden(confirm) => confirm.execute(),
+            SshCommands::DeployKeys(deploy_keys) => deploy_keys.execute(),
+            SshCommands::KeysAudit(keys_audit) => keys_audit.execute(),
+            SshCommands::Totp(totp) => totp.execute(),
+            SshCommands::CaInit(ca_init) => ca_init.execute(),
+            SshCommands::SignCert(sign_cert) => sign_cert.execute(),
+            SshCommands::TrustCa(trust_ca) => trust_ca.execute(),
+            SshCommands::KnownHostsSnapshot(snapshot) => snapshot.execute(),
+            SshCommands::KnownHostsVerify(verify) => verify.execute(),
+            SshCommands::DistributeKnownHosts(distribute) => distribute.execute(),
+            SshCommands::Lockdown(lockdown) => lockdown.execute(),
+            SshCommands::BastionConfig(bastion_config) => bastion_config.execute(),
+            SshCommands::FailLock(fail_lock) => fail_lock.execute(),
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct Harden {
+    /// Reject root logins over SSH entirely
+    #[arg(long, default_value_t = true)]
+    disable_root_login: bool,
+
+    /// Maximum authentication attempts per connection before sshd drops it
+    #[arg(long, default_value_t = 3)]
+    max_auth_tries: u32,
+
+    /// Restrict logins to only these users. Leave unset to not add an AllowUsers restriction
+    #[arg(long = "allow-user")]
+    allow_users: Vec<String>,
+
+    /// sshd_config to rewrite
+    #[arg(long, default_value = "/etc/ssh/sshd_config")]
+    config: PathBuf,
+
+    /// How long to wait before automatically rolling back, unless confirmed with
+    /// `jj ssh confirm-harden`. Protects against being locked out by a bad policy
+    #[arg(long, default_value = "5min")]
+    rollback_after: humantime::Duration,
+
+    /// Apply the hardened config without a timed rollback safety net
+    #[arg(long)]
+    no_rollback: bool,
+
+    /// Show the diff and validate with `sshd -t`, but don't install or restart anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl Harden {
+    fn execute(self) -> eyre::Result<()> {
+        let before = std::fs::read_to_string(&self.config)
+            .with_context(|| format!("Could not read {}", self.config.display()))?;
+        let after = Self::apply_hardening(&before, &self);
+
+        Self::print_diff(&self.config, &before, &after);
+
+        let staged = self.config.with_extension("jj-staged");
+        std::fs::write(&staged, &after)
+            .with_context(|| format!("Could not write {}", staged.display()))?;
+
+        let check = Command::new("sshd")
+            .args(["-t", "-f"])
+            .arg(&staged)
+            .output()
+            .context("Could not run `sshd -t` to validate the hardened config")?;
+
+        if !check.status.success() {
+            let _ = remove_file(&staged);
+            bail!(
+                "sshd rejected the hardened config, not installing it:\n{}",
+                String::from_utf8_lossy(&check.stderr)
+            );
+        }
+
+        if self.dry_run {
+            let _ = remove_file(&staged);
+            println!("{}", "--- sshd -t passed; not installing (dry run)".green());
+            return Ok(());
+        }
+
+        copy(&self.config, HARDEN_BACKUP).with_context(|| {
+            format!(
+                "Could not back up {} to {HARDEN_BACKUP}",
+                self.config.display()
+            )
+        })?;
+        rename(&staged, &self.config)
+            .with_context(|| format!("Could not install {}", self.config.display()))?;
+
+        restart_sshd()?;
+
+        println!(
+            "{}",
+            "--- Installed hardened sshd_config and restarted sshd".green()
+        );
+
+        if self.no_rollback {
+            return Ok(());
+        }
+
+        let _ = remove_file(HARDEN_MARKER);
+        Self::spawn_rollback_watcher(&self.config, *self.rollback_after)?;
+
+        println!(
+            "{} Run `jj ssh confirm-harden` within {} or sshd_config will be rolled back automatically",
+            "---".yellow(),
+            humantime::format_duration(*self.rollback_after)
+        );
+
+        Ok(())
+    }
+
+    fn apply_hardening(contents: &str, args: &Harden) -> String {
+        let mut contents = contents.to_string();
+
+        if args.disable_root_login {
+            contents = Self::set_directive(&contents, "PermitRootLogin", "no");
+        }
+        contents = Self::set_directive(&contents, "MaxAuthTries", &args.max_auth_tries.to_string());
+        contents = Self::set_directive(&contents, "Protocol", "2");
+        contents = Self::set_directive(&contents, "KexAlgorithms", SANE_KEX);
+        contents = Self::set_directive(&contents, "Ciphers", SANE_CIPHERS);
+        contents = Self::set_directive(&contents, "MACs", SANE_MACS);
+
+        if !args.allow_users.is_empty() {
+            contents = Self::set_directive(&contents, "AllowUsers", &args.allow_users.join(" "));
+        }
+
+        contents
+    }
+
+    /// Set a `Directive value` line in an sshd_config-style file, replacing an existing
+    /// (possibly commented-out) entry for `directive` if one is present, or appending a new one
+    fn set_directive(contents: &str, directive: &str, value: &str) -> String {
+        let mut found = false;
+
+        let mut lines = contents
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim_start().trim_start_matches('#').trim_start();
+                let matches = trimmed
+                    .split_whitespace()
+                    .next()
+                    .is_some_and(|d| d.eq_ignore_ascii_case(directive));
+
+                if matches {
+                    found = true;
+                    format!("{directive} {value}")
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if !found {
+            lines.push(format!("{directive} {value}"));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    fn print_diff(path: &Path, before: &str, after: &str) {
+        use imara_diff::{Algorithm, Diff, InternedInput};
+
+        println!("{} {}", "--- diff for".blue(), path.display());
+
+        let input = InternedInput::new(before, after);
+        let mut diff = Diff::compute(Algorithm::Histogram, &input);
+        diff.postprocess_lines(&input);
+
+        let before_lines = before.split('\n').collect::<Vec<_>>();
+        let after_lines = after.split('\n').collect::<Vec<_>>();
+
+        for hunk in diff.hunks() {
+            for line in &before_lines[hunk.before.start as usize..hunk.before.end as usize] {
+                println!("{}", format!("-{line}").red());
+            }
+            for line in &after_lines[hunk.after.start as usize..hunk.after.end as usize] {
+                println!("{}", format!("+{line}").green());
+            }
+        }
+    }
+
+    /// Spawn a detached watcher that restores `backup` over `config` if `HARDEN_MARKER` hasn't
+    /// shown up by the time `after` elapses. Backgrounding it with `sh` lets it outlive this
+    /// process, since there's nothing else in this repo to schedule a one-off delayed task
+    fn spawn_rollback_watcher(config: &Path, after: std::time::Duration) -> eyre::Result<()> {
+        let script = format!(
+            "sleep {secs}; if [ ! -f '{marker}' ]; then cp '{backup}' '{config}'; systemctl restart sshd 2>/dev/null || systemctl restart ssh 2>/dev/null || service sshd restart 2>/dev/null || service ssh restart 2>/dev/null || true; fi; rm -f '{marker}'",
+            secs = after.as_secs(),
+            marker = HARDEN_MARKER,
+            backup = HARDEN_BACKUP,
+            config = config.display(),
+        );
+
+        Command::new("sh")
+            .args(["-c", &script])
+            .spawn()
+            .context("Could not spawn the rollback watcher")?;
+
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct ConfirmHarden {
+    /// Marker file matching the one `jj ssh harden`'s rollback watcher is waiting on
+    #[arg(long, default_value = HARDEN_MARKER)]
+    marker: PathBuf,
+}
+
+impl ConfirmHarden {
+    fn execute(self) -> eyre::Result<()> {
+        std::fs::write(&self.marker, "")
+            .with_context(|| format!("Could not write {}", self.marker.display()))?;
+
+        println!(
+            "{}",
+            "--- Hardening confirmed, the pending rollback will no longer fire".green()
+        );
+        Ok(())
+    }
+}
+
+/// A parsed `user@host[:port]` line from a `--hosts` file
+struct DeployTarget {
+    /// The line as given, used to label per-host results
+    spec: String,
+    /// `user@host`, with any `:port` suffix stripped, as `ssh`/`scp` expect their target
+    user_host: String,
+    port: Option<u16>,
+}
+
+#[derive(Parser, Debug)]
+pub struct DeployKeys {
+    /// File with one `user@host[:port]` target per line. Blank lines and lines starting with
+    /// `#` are ignored
+    #[arg(long)]
+    hosts: PathBuf,
+
+    /// Public key to install into each host's authorized_keys. Defaults to
+    /// ~/.ssh/id_ed25519.pub for whichever user is running jj
+    #[arg(long)]
+    key: Option<PathBuf>,
+
+    /// Also copy this jj binary to /usr/local/bin/jj on each host
+    #[arg(long)]
+    push_binary: bool,
+
+    /// Extra options passed through to both ssh and scp
+    #[arg(long, default_values_t = strvec!["-o", "StrictHostKeyChecking=no", "-o", "ConnectTimeout=10"])]
+    ssh_opt: Vec<String>,
+
+    /// Maximum number of hosts to deploy to at once
+    #[arg(long, default_value_t = 16)]
+    concurrency: usize,
+}
+
+/// Hard backstop on top of `--ssh-opt`'s `ConnectTimeout`, covering the full per-host deployment
+/// (key install plus, if requested, pushing the jj binary) so one stuck host can't hold up the
+/// rest of the fleet forever
+const DEPLOY_TIMEOUT: Duration = Duration::from_secs(120);
+
+impl DeployKeys {
+    fn execute(self) -> eyre::Result<()> {
+        let key_path = match self.key {
+            Some(path) => path,
+            None => dirs::home_dir()
+                .context("Could not determine home directory")?
+                .join(".ssh/id_ed25519.pub"),
+        };
+
+        let targets = std::fs::read_to_string(&self.hosts)
+            .with_context(|| format!("Could not read {}", self.hosts.display()))?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(Self::parse_target)
+            .collect::<Vec<_>>();
+
+        println!(
+            "{} Deploying {} to {} host(s)",
+            "---".blue(),
+            key_path.display(),
+            targets.len()
+        );
+
+        let specs = targets
+            .iter()
+            .map(|target| target.spec.clone())
+            .collect::<Vec<_>>();
+
+        let tasks: Vec<Box<dyn FnOnce() -> eyre::Result<()> + Send>> = targets
+            .into_iter()
+            .map(|target| {
+                let key_path = key_path.clone();
+                let ssh_opt = self.ssh_opt.clone();
+                let push_binary = self.push_binary;
+
+                Box::new(move || Self::deploy_to_target(&target, &key_path, push_binary, &ssh_opt))
+                    as Box<dyn FnOnce() -> eyre::Result<()> + Send>
+            })
+            .collect();
+
+        let mut failures = 0;
+
+        for (spec, outcome) in
+            specs
+                .into_iter()
+                .zip(run_bounded(tasks, self.concurrency, Some(DEPLOY_TIMEOUT)))
+        {
+            match outcome {
+                TaskOutcome::Finished(Ok(())) => println!("{} {spec}", "succeeded".green()),
+                TaskOutcome::Finished(Err(e)) => {
+                    failures += 1;
+                    println!("{} {spec}: {e}", "failed".red());
+                }
+                TaskOutcome::TimedOut => {
+                    failures += 1;
+                    println!(
+                        "{} {spec}: did not finish within {DEPLOY_TIMEOUT:?}",
+                        "failed".red()
+                    );
+                }
+                TaskOutcome::Panicked => {
+                    failures += 1;
+                    eprintln!("{} {spec}: deployment thread panicked", "!!!".red());
+                }
+            }
+        }
+
+        if failures > 0 {
+            bail!("{failures} host(s) failed key deployment");
+        }
+
+        Ok(())
+    }
+
+    fn parse_target(line: &str) -> DeployTarget {
+        let (user_host, port) = match line.rsplit_once(':') {
+            Some((uh, p)) if !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()) => {
+                (uh.to_string(), p.parse().ok())
+            }
+            _ => (line.to_string(), None),
+        };
+
+        DeployTarget {
+            spec: line.to_string(),
+            user_host,
+            port,
+        }
+    }
+
+    fn deploy_to_target(
+        target: &DeployTarget,
+        key_path: &Path,
+        push_binary: bool,
+        ssh_opt: &[String],
+    ) -> eyre::Result<()> {
+        let key_file = std::fs::File::open(key_path)
+            .with_context(|| format!("Could not open {}", key_path.display()))?;
+
+        let status = Command::new("ssh")
+            .args(Self::ssh_args(ssh_opt, target.port, "-p"))
+            .arg(&target.user_host)
+            .arg(
+                "mkdir -p ~/.ssh && chmod 700 ~/.ssh && cat >> ~/.ssh/authorized_keys && \
+                 chmod 600 ~/.ssh/authorized_keys",
+            )
+            .stdin(Stdio::from(key_file))
+            .status()
+            .context("Could not spawn ssh")?;
+
+        if !status.success() {
+            bail!("ssh exited with {status} while installing the public key");
+        }
+
+        if push_binary {
+            Self::push_jj_binary(target, ssh_opt)?;
+        }
+
+        Ok(())
+    }
+
+    fn push_jj_binary(target: &DeployTarget, ssh_opt: &[String]) -> eyre::Result<()> {
+        let exe = std::env::current_exe().context("Could not determine path to this binary")?;
+
+        let status = Command::new("scp")
+            .args(Self::ssh_args(ssh_opt, target.port, "-P"))
+            .arg(&exe)
+            .arg(format!("{}:/tmp/jj", target.user_host))
+            .status()
+            .context("Could not spawn scp")?;
+
+        if !status.success() {
+            bail!("scp exited with {status} while pushing the jj binary");
+        }
+
+        let status = Command::new("ssh")
+            .args(Self::ssh_args(ssh_opt, target.port, "-p"))
+            .arg(&target.user_host)
+            .arg("sudo mv /tmp/jj /usr/local/bin/jj && sudo chmod 755 /usr/local/bin/jj")
+            .status()
+            .context("Could not spawn ssh")?;
+
+        if !status.success() {
+            bail!("ssh exited with {status} while installing the jj binary");
+        }
+
+        Ok(())
+    }
+
+    /// `ssh_opt` plus a `port_flag <port>` pair, if the target specified a non-default port
+    fn ssh_args(ssh_opt: &[String], port: Option<u16>, port_flag: &str) -> Vec<String> {
+        let mut args = ssh_opt.to_vec();
+        if let Some(port) = port {
+            args.push(port_flag.to_string());
+            args.push(port.to_string());
+        }
+        args
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AuthorizedKeyFinding {
+    user: String,
+    fingerprint: String,
+    key_type: String,
+    comment: String,
+    approved: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct KeysAudit {
+    /// File of approved OpenSSH public keys, one per line. Keys found in a user's
+    /// authorized_keys that aren't in this set are flagged
+    #[arg(long)]
+    approved: PathBuf,
+
+    /// Remove unapproved keys from each user's authorized_keys instead of just reporting them
+    #[arg(long)]
+    prune: bool,
+
+    /// Print findings as JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+impl KeysAudit {
+    fn execute(self) -> eyre::Result<()> {
+        let approved = Self::load_fingerprints(&self.approved)?;
+
+        let mut findings = Vec::new();
+
+        for user in load_users(None::<&str>)? {
+            let path = Path::new(&user.home).join(".ssh/authorized_keys");
+            let Ok(contents) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+
+            let kept = self.audit_user(&user.user, &contents, &approved, &mut findings);
+
+            if self.prune && kept.len() != contents.lines().count() {
+                std::fs::write(&path, kept.join("\n") + "\n")
+                    .with_context(|| format!("Could not write {}", path.display()))?;
+            }
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&findings)?);
+        } else {
+            Self::print_findings(&findings, self.prune);
+        }
+
+        Ok(())
+    }
+
+    /// Walk one user's authorized_keys, recording a finding per parseable key and returning the
+    /// lines that should be kept (every line when not pruning, only approved/unparseable lines
+    /// when pruning)
+    fn audit_user<'a>(
+        &self,
+        user: &str,
+        contents: &'a str,
+        approved: &HashSet<String>,
+        findings: &mut Vec<AuthorizedKeyFinding>,
+    ) -> Vec<&'a str> {
+        contents
+            .lines()
+            .filter(|line| {
+                let trimmed = line.trim();
+                if trimmed.is_empty() || trimmed.starts_with('#') {
+                    return true;
+                }
+
+                let Ok(key) = PublicKey::from_openssh(trimmed) else {
+                    eprintln!(
+                        "{} Could not parse a line in {user}'s authorized_keys, leaving it alone",
+                        "---".yellow()
+                    );
+                    return true;
+                };
+
+                let fingerprint = key.fingerprint(HashAlg::Sha256).to_string();
+                let is_approved = approved.contains(&fingerprint);
+
+                findings.push(AuthorizedKeyFinding {
+                    user: user.to_string(),
+                    fingerprint,
+                    key_type: key.algorithm().to_string(),
+                    comment: key.comment().to_string(),
+                    approved: is_approved,
+                });
+
+                !self.prune || is_approved
+            })
+            .collect()
+    }
+
+    fn load_fingerprints(path: &Path) -> eyre::Result<HashSet<String>> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+
+        Ok(contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| PublicKey::from_openssh(line).ok())
+            .map(|key| key.fingerprint(HashAlg::Sha256).to_string())
+            .collect())
+    }
+
+    fn print_findings(findings: &[AuthorizedKeyFinding], pruned: bool) {
+        for finding in findings {
+            let label = if finding.approved {
+                "approved".green()
+            } else if pruned {
+                "removed".red()
+            } else {
+                "unapproved".red()
+            };
+
+            println!(
+                "{label} {} {} ({}) {}",
+                finding.user, finding.fingerprint, finding.key_type, finding.comment
+            );
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct Totp {
+    /// User to provision a TOTP secret for
+    user: String,
+
+    /// Issuer name embedded in the otpauth:// URI shown to authenticator apps
+    #[arg(long, default_value = "jj-rs")]
+    issuer: String,
+
+    /// Regenerate the secret even if the user already has one
+    #[arg(long)]
+    force: bool,
+
+    /// Write the otpauth:// URI and raw secret to this file as well as printing them
+    #[arg(long)]
+    export: Option<PathBuf>,
+
+    /// pam.d file to wire pam_google_authenticator.so into
+    #[arg(long, default_value = "/etc/pam.d/sshd")]
+    pam_file: PathBuf,
+
+    /// sshd_config to enable keyboard-interactive authentication in
+    #[arg(long, default_value = "/etc/ssh/sshd_config")]
+    config: PathBuf,
+}
+
+impl Totp {
+    fn execute(self) -> eyre::Result<()> {
+        if !geteuid().is_root() {
+            bail!("You must be root to provision TOTP for another user");
+        }
+
+        Self::install_google_authenticator()?;
+
+        let home = load_users(self.user.as_str())?
+            .into_iter()
+            .next()
+            .with_context(|| format!("No such user: {}", self.user))?
+            .home;
+
+        let secret_file = Path::new(&home).join(".google_authenticator");
+
+        if self.force || !secret_file.exists() {
+            let status = Command::new("/bin/sh")
+                .args([
+                    "-c",
+                    &format!(
+                        "sudo -u {} -H google-authenticator -t -d -f -r 3 -R 30 -W -q",
+                        self.user
+                    ),
+                ])
+                .status()
+                .context("Could not spawn google-authenticator")?;
+
+            if !status.success() {
+                bail!("google-authenticator exited with {status}");
+            }
+        } else {
+            println!(
+                "{} {} already has a TOTP secret, reusing it (pass --force to regenerate)",
+                "---".blue(),
+                self.user
+            );
+        }
+
+        let secret = std::fs::read_to_string(&secret_file)
+            .with_context(|| format!("Could not read {}", secret_file.display()))?
+            .lines()
+            .next()
+            .with_context(|| format!("{} was empty", secret_file.display()))?
+            .to_string();
+
+        let uri = format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}",
+            urlencoding::encode(&self.issuer),
+            urlencoding::encode(&self.user),
+            secret,
+            urlencoding::encode(&self.issuer),
+        );
+
+        println!("{} TOTP secret for {}: {secret}", "---".blue(), self.user);
+        println!("{uri}");
+
+        if let Some(export) = &self.export {
+            std::fs::write(export, format!("{uri}\nsecret: {secret}\n"))
+                .with_context(|| format!("Could not write {}", export.display()))?;
+            println!("Wrote provisioning data to {}", export.display());
+        }
+
+        Self::wire_pam(&self.pam_file)?;
+
+        let sshd_config = std::fs::read_to_string(&self.config)
+            .with_context(|| format!("Could not read {}", self.config.display()))?;
+        let sshd_config =
+            Harden::set_directive(&sshd_config, "KbdInteractiveAuthentication", "yes");
+        let sshd_config = Harden::set_directive(&sshd_config, "UsePAM", "yes");
+        std::fs::write(&self.config, sshd_config)
+            .with_context(|| format!("Could not write {}", self.config.display()))?;
+
+        println!("{}", "--- TOTP provisioning complete".green());
+        restart_sshd()?;
+
+        Ok(())
+    }
+
+    fn install_google_authenticator() -> eyre::Result<()> {
+        let distro = get_distro()?;
+
+        if distro.is_deb_based() {
+            install_apt_packages(
+                DownloadSettings::NoContainer,
+                &["libpam-google-authenticator"],
+            )
+            .context("Could not install libpam-google-authenticator")
+        } else if distro.is_rhel_based() {
+            install_dnf_packages(DownloadSettings::NoContainer, &["google-authenticator"])
+                .context("Could not install google-authenticator")
+        } else {
+            bail!("No supported package manager found to install google-authenticator");
+        }
+    }
+
+    /// Ensure `pam_google_authenticator.so` runs as part of the auth stack, ahead of whatever's
+    /// already there, so a valid TOTP code is required alongside the existing auth method
+    fn wire_pam(path: &Path) -> eyre::Result<()> {
+        let contents = std::fs::read_to_string(path).unwrap_or_default();
+
+        if contents.contains("pam_google_authenticator.so") {
+            return Ok(());
+        }
+
+        let out = format!("auth       required     pam_google_authenticator.so nullok\n{contents}");
+
+        std::fs::write(path, out).with_context(|| format!("Could not write {}", path.display()))
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct CaInit {
+    /// Where to write the CA's private key. The matching public key is written alongside it
+    /// with a `.pub` suffix, same as `ssh-keygen`
+    #[arg(long, default_value = "/etc/ssh/ca")]
+    ca_key: PathBuf,
+
+    /// Overwrite an existing CA keypair at this path
+    #[arg(long)]
+    force: bool,
+}
+
+impl CaInit {
+    fn execute(self) -> eyre::Result<()> {
+        if self.ca_key.exists() && !self.force {
+            bail!(
+                "{} already exists, pass --force to replace it (this invalidates every \
+                 certificate it has signed)",
+                self.ca_key.display()
+            );
+        }
+
+        let ca_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519)
+            .context("Could not generate an Ed25519 CA keypair")?;
+
+        ca_key
+            .write_openssh_file(&self.ca_key, LineEnding::LF)
+            .with_context(|| format!("Could not write {}", self.ca_key.display()))?;
+
+        let public_path = self.ca_key.with_extension("pub");
+        std::fs::write(
+            &public_path,
+            ca_key
+                .public_key()
+                .to_openssh()
+                .context("Could not encode the CA public key")?,
+        )
+        .with_context(|| format!("Could not write {}", public_path.display()))?;
+
+        println!(
+            "{} Generated SSH CA keypair: {} (private), {} (public)",
+            "---".green(),
+            self.ca_key.display(),
+            public_path.display()
+        );
+        println!(
+            "Distribute {} to hosts via `jj ssh trust-ca`, and keep {} offline",
+            public_path.display(),
+            self.ca_key.display()
+        );
+
+        Ok(())
+    }
+}
+
+/// Mirrors [`CertType`], since it doesn't implement [`ValueEnum`] itself
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum CertKind {
+    User,
+    Host,
+}
+
+impl From<CertKind> for CertType {
+    fn from(kind: CertKind) -> Self {
+        match kind {
+            CertKind::User => CertType::User,
+            CertKind::Host => CertType::Host,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct SignCert {
+    /// Public key to sign, e.g. a user's ~/.ssh/id_ed25519.pub or a host key
+    public_key: PathBuf,
+
+    /// CA private key produced by `jj ssh ca-init`
+    #[arg(long, default_value = "/etc/ssh/ca")]
+    ca_key: PathBuf,
+
+    /// Whether this certifies a user or a host
+    #[arg(long, value_enum, default_value_t = CertKind::User)]
+    cert_type: CertKind,
+
+    /// Username (or hostname, for --cert-type host) this certificate is valid for. May be
+    /// repeated; if omitted entirely, the certificate is valid for all principals ("golden
+    /// ticket"), so prefer specifying at least one
+    #[arg(long = "principal")]
+    principals: Vec<String>,
+
+    /// How long the certificate remains valid for, starting now
+    #[arg(long, default_value = "52w")]
+    valid_for: humantime::Duration,
+
+    /// CA-specific identifier embedded in the certificate, shown in sshd's auth log on use
+    #[arg(long)]
+    key_id: Option<String>,
+
+    /// Where to write the signed certificate. Defaults to <public_key> with its extension
+    /// replaced by `-cert.pub`, matching `ssh-keygen -s`
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+impl SignCert {
+    fn execute(self) -> eyre::Result<()> {
+        let ca_key = PrivateKey::read_openssh_file(&self.ca_key)
+            .with_context(|| format!("Could not read CA key {}", self.ca_key.display()))?;
+
+        let subject = std::fs::read_to_string(&self.public_key)
+            .with_context(|| format!("Could not read {}", self.public_key.display()))?;
+        let subject = PublicKey::from_openssh(subject.trim())
+            .with_context(|| format!("Could not parse {}", self.public_key.display()))?;
+
+        let valid_after = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .context("System clock is before the Unix epoch")?
+            .as_secs();
+        let valid_before = valid_after + self.valid_for.as_secs();
+
+        let mut builder = certificate::Builder::new_with_random_nonce(
+            &mut OsRng,
+            subject,
+            valid_after,
+            valid_before,
+        )
+        .context("Could not start building the certificate")?;
+
+        builder
+            .cert_type(self.cert_type.into())
+            .context("Could not set the certificate type")?;
+        builder
+            .key_id(self.key_id.unwrap_or_default())
+            .context("Could not set the certificate key ID")?;
+
+        if self.principals.is_empty() {
+            println!(
+                "{} No --principal given, signing a certificate valid for ALL principals",
+                "---".yellow()
+            );
+            builder
+                .all_principals_valid()
+                .context("Could not mark the certificate valid for all principals")?;
+        } else {
+            for principal in &self.principals {
+                builder
+                    .valid_principal(principal)
+                    .with_context(|| format!("Could not add principal {principal}"))?;
+            }
+        }
+
+        let cert = builder
+            .sign(&ca_key)
+            .context("Could not sign the certificate")?;
+
+        let out = self.out.unwrap_or_else(|| {
+            self.public_key.with_file_name(format!(
+                "{}-cert.pub",
+                self.public_key
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("key")
+            ))
+        });
+
+        std::fs::write(
+            &out,
+            cert.to_openssh()
+                .context("Could not encode the signed certificate")?,
+        )
+        .with_context(|| format!("Could not write {}", out.display()))?;
+
+        println!(
+            "{} Signed {:?} certificate for {:?}, valid until {}, written to {}",
+            "---".green(),
+            self.cert_type,
+            self.principals,
+            humantime::format_rfc3339_seconds(
+                std::time::UNIX_EPOCH + std::time::Duration::from_secs(valid_before)
+            ),
+            out.display()
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct TrustCa {
+    /// CA public key that sshd should trust certificates signed by
+    ca_public_key: PathBuf,
+
+    /// sshd_config to configure
+    #[arg(long, default_value = "/etc/ssh/sshd_config")]
+    config: PathBuf,
+}
+
+impl TrustCa {
+    fn execute(self) -> eyre::Result<()> {
+        if !geteuid().is_root() {
+            bail!("You must be root to configure sshd's TrustedUserCAKeys");
+        }
+
+        if !self.ca_public_key.exists() {
+            bail!("{} does not exist", self.ca_public_key.display());
         }
+
+        let sshd_config = std::fs::read_to_string(&self.config)
+            .with_context(|| format!("Could not read {}", self.config.display()))?;
+        let sshd_config = Harden::set_directive(
+            &sshd_config,
+            "TrustedUserCAKeys",
+            &self.ca_public_key.display().to_string(),
+        );
+        std::fs::write(&self.config, sshd_config)
+            .with_context(|| format!("Could not write {}", self.config.display()))?;
+
+        println!(
+            "{} {} is now trusted as a certificate authority",
+            "---".green(),
+            self.ca_public_key.display()
+        );
+        restart_sshd()
+    }
+}
+
+/// A `host[:port]` line from a `--hosts` file, as accepted by `ssh-keyscan`
+struct ScanTarget {
+    host: String,
+    port: u16,
+}
+
+/// Run `ssh-keyscan` against one host, returning its raw `host keytype base64key` lines.
+/// An unreachable host just yields an empty list rather than an error, since scanning a fleet
+/// should keep going when one box is down
+fn scan_host(host: &str, port: u16, timeout: u64) -> eyre::Result<Vec<String>> {
+    let output = Command::new("ssh-keyscan")
+        .args(["-p", &port.to_string(), "-T", &timeout.to_string()])
+        .arg(host)
+        .output()
+        .context("Could not spawn ssh-keyscan")?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Parse one `host keytype base64key` known_hosts line
+fn parse_known_hosts_line(line: &str) -> Option<(String, String, String)> {
+    let mut fields = line.split_whitespace();
+    let host = fields.next()?.to_string();
+    let key_type = fields.next()?.to_string();
+    let key = fields.next()?.to_string();
+    Some((host, key_type, key))
+}
+
+fn parse_scan_target(line: &str) -> ScanTarget {
+    match line.rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+            ScanTarget {
+                host: host.to_string(),
+                port: port.parse().unwrap_or(22),
+            }
+        }
+        _ => ScanTarget {
+            host: line.to_string(),
+            port: 22,
+        },
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct KnownHostsSnapshot {
+    /// File with one host[:port] per line. Blank lines and lines starting with `#` are ignored
+    #[arg(long)]
+    hosts: PathBuf,
+
+    /// Where to write the resulting known_hosts-format snapshot
+    #[arg(long)]
+    out: PathBuf,
+
+    /// Seconds to wait for each host to respond before moving on
+    #[arg(long, default_value_t = 5)]
+    timeout: u64,
+
+    /// Maximum number of hosts to scan at once
+    #[arg(long, default_value_t = 16)]
+    concurrency: usize,
+}
+
+impl KnownHostsSnapshot {
+    fn execute(self) -> eyre::Result<()> {
+        let targets = std::fs::read_to_string(&self.hosts)
+            .with_context(|| format!("Could not read {}", self.hosts.display()))?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(parse_scan_target)
+            .collect::<Vec<_>>();
+
+        let tasks: Vec<Box<dyn FnOnce() -> eyre::Result<Vec<String>> + Send>> = targets
+            .iter()
+            .map(|target| {
+                let host = target.host.clone();
+                let port = target.port;
+                let timeout = self.timeout;
+
+                Box::new(move || scan_host(&host, port, timeout))
+                    as Box<dyn FnOnce() -> eyre::Result<Vec<String>> + Send>
+            })
+            .collect();
+
+        // `ssh-keyscan` is given `self.timeout` via `-T`, so this backstop only fires if the
+        // process itself hangs rather than ssh-keyscan's own handling of an unreachable host
+        let backstop = Duration::from_secs(self.timeout.saturating_add(10));
+
+        let mut snapshot = String::new();
+        let mut missing = 0;
+
+        for (target, outcome) in
+            targets
+                .iter()
+                .zip(run_bounded(tasks, self.concurrency, Some(backstop)))
+        {
+            let lines = match outcome {
+                TaskOutcome::Finished(Ok(lines)) => lines,
+                TaskOutcome::Finished(Err(e)) => return Err(e),
+                TaskOutcome::TimedOut => {
+                    missing += 1;
+                    eprintln!(
+                        "{} {} did not respond within the scan timeout",
+                        "---".yellow(),
+                        target.host
+                    );
+                    continue;
+                }
+                TaskOutcome::Panicked => {
+                    missing += 1;
+                    eprintln!(
+                        "{} Scan thread for {} panicked",
+                        "---".yellow(),
+                        target.host
+                    );
+                    continue;
+                }
+            };
+
+            if lines.is_empty() {
+                missing += 1;
+                eprintln!(
+                    "{} Could not fetch a host key from {}",
+                    "---".yellow(),
+                    target.host
+                );
+                continue;
+            }
+
+            for line in lines {
+                snapshot.push_str(&line);
+                snapshot.push('\n');
+            }
+        }
+
+        std::fs::write(&self.out, snapshot)
+            .with_context(|| format!("Could not write {}", self.out.display()))?;
+
+        println!(
+            "{} Snapshotted host keys for {}/{} host(s) to {}",
+            "---".green(),
+            targets.len() - missing,
+            targets.len(),
+            self.out.display()
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct KnownHostsVerify {
+    /// Previous snapshot (from `jj ssh known-hosts-snapshot`) to verify current host keys against
+    #[arg(long)]
+    baseline: PathBuf,
+
+    /// Re-scan this host list instead of every host already present in the baseline
+    #[arg(long)]
+    hosts: Option<PathBuf>,
+
+    /// Seconds to wait for each host to respond before moving on
+    #[arg(long, default_value_t = 5)]
+    timeout: u64,
+
+    /// Accept every key seen this run into the baseline, instead of just reporting drift
+    #[arg(long)]
+    update: bool,
+}
+
+impl KnownHostsVerify {
+    fn execute(self) -> eyre::Result<()> {
+        let baseline_contents = std::fs::read_to_string(&self.baseline)
+            .with_context(|| format!("Could not read {}", self.baseline.display()))?;
+
+        let mut baseline = HashMap::new();
+        for line in baseline_contents.lines() {
+            if let Some((host, key_type, key)) = parse_known_hosts_line(line) {
+                baseline.insert((host, key_type), key);
+            }
+        }
+
+        let hosts = match &self.hosts {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Could not read {}", path.display()))?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| parse_scan_target(line).host)
+                .collect::<Vec<_>>(),
+            None => {
+                let mut hosts = baseline
+                    .keys()
+                    .map(|(host, _)| host.clone())
+                    .collect::<Vec<_>>();
+                hosts.sort();
+                hosts.dedup();
+                hosts
+            }
+        };
+
+        let mut changed = 0;
+        let mut rescanned = Vec::new();
+
+        for host in &hosts {
+            let scanned = scan_host(host, 22, self.timeout)?;
+
+            if scanned.is_empty() {
+                eprintln!(
+                    "{} Could not fetch a host key from {host}, skipping",
+                    "---".yellow()
+                );
+                continue;
+            }
+
+            for line in scanned {
+                let Some((_, key_type, key)) = parse_known_hosts_line(&line) else {
+                    continue;
+                };
+
+                match baseline.get(&(host.clone(), key_type.clone())) {
+                    Some(known) if *known == key => {
+                        println!("{} {host} ({key_type})", "unchanged".green());
+                    }
+                    Some(_) => {
+                        changed += 1;
+                        println!(
+                            "{} {host} ({key_type}) host key changed! Possible MITM or a rebuilt box",
+                            "CHANGED".red().bold()
+                        );
+                    }
+                    None => {
+                        println!("{} {host} ({key_type})", "new".yellow());
+                    }
+                }
+
+                rescanned.push(line);
+            }
+        }
+
+        if self.update {
+            std::fs::write(&self.baseline, rescanned.join("\n") + "\n")
+                .with_context(|| format!("Could not write {}", self.baseline.display()))?;
+            println!(
+                "{}",
+                "--- Baseline updated with this run's host keys".green()
+            );
+        } else if changed > 0 {
+            bail!(
+                "{changed} host key(s) changed since the last snapshot; investigate before trusting them again"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct DistributeKnownHosts {
+    /// Curated known_hosts file, e.g. one produced by `jj ssh known-hosts-snapshot`
+    known_hosts: PathBuf,
+
+    /// File with one operator `user@host[:port]` target per line
+    #[arg(long)]
+    operators: PathBuf,
+
+    /// Extra options passed through to ssh
+    #[arg(long, default_values_t = strvec!["-o", "StrictHostKeyChecking=no", "-o", "ConnectTimeout=10"])]
+    ssh_opt: Vec<String>,
+}
+
+impl DistributeKnownHosts {
+    fn execute(self) -> eyre::Result<()> {
+        let targets = std::fs::read_to_string(&self.operators)
+            .with_context(|| format!("Could not read {}", self.operators.display()))?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(DeployKeys::parse_target)
+            .collect::<Vec<_>>();
+
+        println!(
+            "{} Distributing {} to {} operator(s)",
+            "---".blue(),
+            self.known_hosts.display(),
+            targets.len()
+        );
+
+        let mut threads = Vec::new();
+
+        for target in targets {
+            let known_hosts = self.known_hosts.clone();
+            let ssh_opt = self.ssh_opt.clone();
+
+            threads.push(std::thread::spawn(move || {
+                let result = Self::push_to_target(&target, &known_hosts, &ssh_opt);
+                (target.spec, result)
+            }));
+        }
+
+        let mut failures = 0;
+
+        for thread in threads {
+            match thread.join() {
+                Ok((spec, Ok(()))) => println!("{} {spec}", "succeeded".green()),
+                Ok((spec, Err(e))) => {
+                    failures += 1;
+                    println!("{} {spec}: {e}", "failed".red());
+                }
+                Err(_) => {
+                    failures += 1;
+                    eprintln!(
+                        "{}",
+                        "!!! Could not join distribution thread due to panic!".red()
+                    );
+                }
+            }
+        }
+
+        if failures > 0 {
+            bail!("{failures} operator(s) failed known_hosts distribution");
+        }
+
+        Ok(())
+    }
+
+    fn push_to_target(
+        target: &DeployTarget,
+        known_hosts: &Path,
+        ssh_opt: &[String],
+    ) -> eyre::Result<()> {
+        let file = std::fs::File::open(known_hosts)
+            .with_context(|| format!("Could not open {}", known_hosts.display()))?;
+
+        let status = Command::new("ssh")
+            .args(DeployKeys::ssh_args(ssh_opt, target.port, "-p"))
+            .arg(&target.user_host)
+            .arg(
+                "mkdir -p ~/.ssh && chmod 700 ~/.ssh && cat >> ~/.ssh/known_hosts && \
+                 chmod 600 ~/.ssh/known_hosts",
+            )
+            .stdin(Stdio::from(file))
+            .status()
+            .context("Could not spawn ssh")?;
+
+        if !status.success() {
+            bail!("ssh exited with {status} while installing known_hosts");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct Lockdown {
+    /// Restrict logins to exactly these users. At least one is required, since a lockdown with
+    /// no AllowUsers would lock everyone out
+    #[arg(long = "allow-user", required = true)]
+    allow_users: Vec<String>,
+
+    /// sshd_config to rewrite
+    #[arg(long, default_value = "/etc/ssh/sshd_config")]
+    config: PathBuf,
+
+    /// How long to wait before automatically rolling back, unless confirmed with
+    /// `jj ssh confirm-harden`. Protects against being locked out by a bad policy
+    #[arg(long, default_value = "5min")]
+    rollback_after: humantime::Duration,
+
+    /// Apply the lockdown without a timed rollback safety net
+    #[arg(long)]
+    no_rollback: bool,
+
+    /// Show the diff and validate with `sshd -t`, but don't install or restart anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl Lockdown {
+    fn execute(self) -> eyre::Result<()> {
+        let before = std::fs::read_to_string(&self.config)
+            .with_context(|| format!("Could not read {}", self.config.display()))?;
+
+        let mut after = Harden::set_directive(&before, "PasswordAuthentication", "no");
+        after = Harden::set_directive(&after, "AllowUsers", &self.allow_users.join(" "));
+
+        Harden::print_diff(&self.config, &before, &after);
+
+        let staged = self.config.with_extension("jj-staged");
+        std::fs::write(&staged, &after)
+            .with_context(|| format!("Could not write {}", staged.display()))?;
+
+        let check = Command::new("sshd")
+            .args(["-t", "-f"])
+            .arg(&staged)
+            .output()
+            .context("Could not run `sshd -t` to validate the lockdown config")?;
+
+        if !check.status.success() {
+            let _ = remove_file(&staged);
+            bail!(
+                "sshd rejected the lockdown config, not installing it:\n{}",
+                String::from_utf8_lossy(&check.stderr)
+            );
+        }
+
+        if self.dry_run {
+            let _ = remove_file(&staged);
+            println!("{}", "--- sshd -t passed; not installing (dry run)".green());
+            return Ok(());
+        }
+
+        copy(&self.config, HARDEN_BACKUP).with_context(|| {
+            format!(
+                "Could not back up {} to {HARDEN_BACKUP}",
+                self.config.display()
+            )
+        })?;
+        rename(&staged, &self.config)
+            .with_context(|| format!("Could not install {}", self.config.display()))?;
+
+        restart_sshd()?;
+
+        println!(
+            "{}",
+            "--- Locked down sshd_config and restarted sshd".green()
+        );
+
+        if self.no_rollback {
+            return Ok(());
+        }
+
+        let _ = remove_file(HARDEN_MARKER);
+        Harden::spawn_rollback_watcher(&self.config, *self.rollback_after)?;
+
+        println!(
+            "{} Run `jj ssh confirm-harden` within {} or sshd_config will be rolled back automatically",
+            "---".yellow(),
+            humantime::format_duration(*self.rollback_after)
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct BastionConfig {
+    /// Inventory file with one `alias user@host[:port]` line per machine. Blank lines and lines
+    /// starting with `#` are ignored
+    inventory: PathBuf,
+
+    /// Jump host every entry should proxy through, as `user@host[:port]`
+    #[arg(long)]
+    bastion: String,
+
+    /// Write the generated config here instead of printing it to stdout
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+impl BastionConfig {
+    fn execute(self) -> eyre::Result<()> {
+        let entries = std::fs::read_to_string(&self.inventory)
+            .with_context(|| format!("Could not read {}", self.inventory.display()))?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| self.entry_block(line))
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        let mut config = format!(
+            "# Generated by `jj ssh bastion-config`; every Host below proxies through {}\n",
+            self.bastion
+        );
+
+        for entry in &entries {
+            config.push('\n');
+            config.push_str(entry);
+        }
+
+        if let Some(out) = &self.out {
+            std::fs::write(out, &config)
+                .with_context(|| format!("Could not write {}", out.display()))?;
+            println!(
+                "{} Wrote {} host block(s) to {}",
+                "---".green(),
+                entries.len(),
+                out.display()
+            );
+        } else {
+            print!("{config}");
+        }
+
+        Ok(())
+    }
+
+    fn entry_block(&self, line: &str) -> eyre::Result<String> {
+        let (alias, target) = line
+            .split_once(char::is_whitespace)
+            .with_context(|| format!("Expected `alias user@host[:port]`, got: {line}"))?;
+
+        let target = DeployKeys::parse_target(target.trim());
+        let (user, host) = match target.user_host.split_once('@') {
+            Some((user, host)) => (Some(user), host),
+            None => (None, target.user_host.as_str()),
+        };
+
+        let mut block = format!("Host {alias}\n    HostName {host}\n");
+
+        if let Some(user) = user {
+            block.push_str(&format!("    User {user}\n"));
+        }
+        if let Some(port) = target.port {
+            block.push_str(&format!("    Port {port}\n"));
+        }
+        block.push_str(&format!("    ProxyJump {}\n", self.bastion));
+
+        Ok(block)
+    }
+}
+
+/// Name of the dedicated nft table this command uses for its blocklist, kept separate from
+/// `core_firewall` (see `firewall.rs`) so fail-lock enforcement doesn't depend on that table
+/// having been set up
+const FAILLOCK_NFT_TABLE: &str = "jj_faillock";
+const FAILLOCK_NFT_SET: &str = "ssh_blocklist";
+
+#[derive(Parser, Debug)]
+pub struct FailLock {
+    /// Failed attempts before pam_faillock locks the account
+    #[arg(long, default_value_t = 5)]
+    max_retry: u32,
+
+    /// Seconds a locked account stays locked before pam_faillock unlocks it automatically
+    #[arg(long, default_value_t = 900)]
+    unlock_time: u32,
+
+    /// pam.d file to wire pam_faillock.so into
+    #[arg(long, default_value = "/etc/pam.d/sshd")]
+    pam_file: PathBuf,
+
+    /// Also scan recent auth logs for repeat offenders and drop them at the firewall
+    #[arg(long)]
+    block_firewall: bool,
+
+    /// Failed SSH attempts from one source IP, within the scanned window, before it's added to
+    /// the firewall block set
+    #[arg(long, default_value_t = 10)]
+    ip_threshold: u32,
+
+    /// How far back to scan auth logs for --block-firewall
+    #[arg(long, default_value = "1h")]
+    lookback: humantime::Duration,
+}
+
+impl FailLock {
+    fn execute(self) -> eyre::Result<()> {
+        if !geteuid().is_root() {
+            bail!("You must be root to configure SSH fail-lock protection");
+        }
+
+        let faillock_conf = Path::new("/etc/security/faillock.conf");
+        Self::set_ini_value(faillock_conf, "deny", &self.max_retry.to_string())?;
+        Self::set_ini_value(faillock_conf, "unlock_time", &self.unlock_time.to_string())?;
+        Self::wire_faillock(&self.pam_file)?;
+
+        println!(
+            "{} pam_faillock configured: deny={}, unlock_time={}",
+            "---".green(),
+            self.max_retry,
+            self.unlock_time
+        );
+
+        if self.block_firewall {
+            self.block_offenders()?;
+        }
+
+        Ok(())
+    }
+
+    /// Set `key = value` in a simple `key = value` style config file, replacing an existing
+    /// (possibly commented-out) entry for `key` if one is present, or appending a new one
+    fn set_ini_value(path: &Path, key: &str, value: &str) -> eyre::Result<()> {
+        let contents = std::fs::read_to_string(path).unwrap_or_default();
+        let mut found = false;
+
+        let mut lines = contents
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim_start().trim_start_matches('#').trim();
+                let matches_key = trimmed
+                    .split_once('=')
+                    .map(|(k, _)| k.trim() == key)
+                    .unwrap_or(trimmed == key);
+
+                if matches_key {
+                    found = true;
+                    format!("{key} = {value}")
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if !found {
+            lines.push(format!("{key} = {value}"));
+        }
+
+        std::fs::write(path, lines.join("\n") + "\n")
+            .with_context(|| format!("Could not write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Wire `pam_faillock.so` into the `auth` stack of a pam.d file, deriving its deny/unlock
+    /// settings from `/etc/security/faillock.conf` rather than duplicating them here
+    fn wire_faillock(path: &Path) -> eyre::Result<()> {
+        let contents = std::fs::read_to_string(path).unwrap_or_default();
+
+        if contents.contains("pam_faillock.so") {
+            return Ok(());
+        }
+
+        let mut out = String::from("auth        required      pam_faillock.so preauth silent\n");
+        let mut authfail_inserted = false;
+
+        for line in contents.lines() {
+            out.push_str(line);
+            out.push('\n');
+
+            if !authfail_inserted
+                && line.trim_start().starts_with("auth")
+                && !line.contains("pam_faillock.so")
+            {
+                out.push_str("auth        [default=die]  pam_faillock.so authfail\n");
+                authfail_inserted = true;
+            }
+        }
+
+        if !authfail_inserted {
+            out.push_str("auth        [default=die]  pam_faillock.so authfail\n");
+        }
+
+        std::fs::write(path, out).with_context(|| format!("Could not write {}", path.display()))
+    }
+
+    /// Scan recent sshd journal entries for failed logins, and drop any source IP that's failed
+    /// at least `ip_threshold` times into a dedicated nft blocklist set
+    fn block_offenders(&self) -> eyre::Result<()> {
+        let (_, logs) = qx(&format!(
+            "journalctl -u sshd -u ssh --no-pager --since '-{}s' 2>/dev/null",
+            self.lookback.as_secs()
+        ))?;
+
+        let mut offenders: HashMap<String, u32> = HashMap::new();
+
+        for line in logs.lines() {
+            if !line.contains("Failed password") && !line.contains("Invalid user") {
+                continue;
+            }
+
+            if let Some(ip) = Self::extract_source_ip(line) {
+                *offenders.entry(ip).or_insert(0) += 1;
+            }
+        }
+
+        let to_block = offenders
+            .into_iter()
+            .filter(|(_, count)| *count >= self.ip_threshold)
+            .map(|(ip, _)| ip)
+            .collect::<Vec<_>>();
+
+        if to_block.is_empty() {
+            println!(
+                "{} No source IP crossed the {} failed-attempt threshold",
+                "---".blue(),
+                self.ip_threshold
+            );
+            return Ok(());
+        }
+
+        let nft = Nft::new()?;
+        Self::ensure_blocklist(&nft)?;
+
+        for ip in &to_block {
+            nft.exec(
+                format!("add element inet {FAILLOCK_NFT_TABLE} {FAILLOCK_NFT_SET} {{ {ip} }}"),
+                Stdio::null(),
+            )
+            .with_context(|| format!("Could not add {ip} to the firewall blocklist"))?;
+        }
+
+        println!(
+            "{} Blocked {} source IP(s) at the firewall: {}",
+            "---".red(),
+            to_block.len(),
+            to_block.join(", ")
+        );
+
+        Ok(())
+    }
+
+    /// Create the blocklist table/set/chain if they don't already exist. `nft add` is a no-op
+    /// when the object is already present, except for the drop rule itself, which has no
+    /// idempotent form, so its presence is checked explicitly first
+    fn ensure_blocklist(nft: &Nft) -> eyre::Result<()> {
+        nft.exec(
+            format!("add table inet {FAILLOCK_NFT_TABLE}"),
+            Stdio::null(),
+        )?;
+        nft.exec(
+            format!("add set inet {FAILLOCK_NFT_TABLE} {FAILLOCK_NFT_SET} {{ type ipv4_addr; }}"),
+            Stdio::null(),
+        )?;
+        nft.exec(
+            format!(
+                "add chain inet {FAILLOCK_NFT_TABLE} input {{ type filter hook input priority -10; policy accept; }}"
+            ),
+            Stdio::null(),
+        )?;
+
+        let existing = nft
+            .command()
+            .args(["list", "chain", "inet", FAILLOCK_NFT_TABLE, "input"])
+            .output()
+            .context("Could not list the fail-lock firewall chain")?;
+
+        if !String::from_utf8_lossy(&existing.stdout).contains(&format!("@{FAILLOCK_NFT_SET}")) {
+            nft.exec(
+                format!(
+                    "add rule inet {FAILLOCK_NFT_TABLE} input ip saddr @{FAILLOCK_NFT_SET} drop"
+                ),
+                Stdio::null(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Pull the IP address out of an sshd log line of the form `... from <ip> port ...`
+    fn extract_source_ip(line: &str) -> Option<String> {
+        let after = line.split_once(" from ")?.1;
+        let candidate = after.split_whitespace().next()?;
+        candidate
+            .parse::<std::net::IpAddr>()
+            .ok()
+            .map(|ip| ip.to_string())
     }
 }
@@ -1,5 +1,16 @@
-use crate::utils::system;
-use clap::{Parser, Subcommand};
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::utils::{command::Cmd, output::OutputFormat, qx, system};
 
 /* ============================== CLI ============================== */
 
@@ -20,6 +31,101 @@ pub enum StatCommands {
     Disk,
     /// Pretty human-readable summary
     Pretty,
+    /// Per-file size, mode, owner, MAC times, and SHA-256, suitable for baselining
+    File(FileStat),
+    /// Cross-reference files against the package manager's records to spot tampering
+    Verify(VerifyStat),
+    /// Sorted MAC-time timeline for a tree, for reconstructing an attacker's activity window
+    Timeline(TimelineStat),
+}
+
+#[derive(Parser, Debug)]
+pub struct VerifyStat {
+    /// Files to verify
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct TimelineStat {
+    /// Files or directories to inspect
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+
+    /// Recurse into directories
+    #[arg(long, short = 'r')]
+    recursive: bool,
+
+    /// Maximum recursion depth when --recursive is set
+    #[arg(long, value_name = "N", requires = "recursive")]
+    max_depth: Option<usize>,
+
+    /// Output format
+    #[arg(value_enum, long, short = 'F', default_value_t = TimelineFormat::Text)]
+    format: TimelineFormat,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineFormat {
+    /// One line per MAC-time event, sorted chronologically
+    Text,
+    /// Same events as CSV, for import into a spreadsheet or timeline tool
+    Csv,
+}
+
+#[derive(Parser, Debug)]
+pub struct FileStat {
+    /// Files or directories to inspect
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+
+    /// Recurse into directories
+    #[arg(long, short = 'r')]
+    recursive: bool,
+
+    /// Maximum recursion depth when --recursive is set
+    #[arg(long, value_name = "N", requires = "recursive")]
+    max_depth: Option<usize>,
+
+    /// Skip hashing files larger than this size, in bytes (they are still listed, just
+    /// without a hash)
+    #[arg(long, value_name = "BYTES")]
+    max_size: Option<u64>,
+
+    /// Output format
+    #[arg(value_enum, long, short = 'F', default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Also gather extended attributes, chattr flags, POSIX ACLs, and capabilities (Linux
+    /// only, and requires getfattr/lsattr/getfacl/getcap); slower, so opt-in
+    #[arg(long, short = 'x')]
+    extended: bool,
+}
+
+/// Size, mode, owner, MAC times, and hash for a single file, gathered for baselining or
+/// forensic comparison across hosts
+#[derive(Debug, Serialize)]
+pub struct FileRecord {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Permission bits; only available on Unix
+    pub mode: Option<u32>,
+    /// Owning uid/gid; only available on Unix
+    pub owner: Option<(u32, u32)>,
+    pub accessed: DateTime<Utc>,
+    pub modified: DateTime<Utc>,
+    /// Inode change time; only available on Unix (Windows has no equivalent to ctime)
+    pub changed: Option<DateTime<Utc>>,
+    pub sha256: Option<String>,
+    /// Extended attribute names and values; only populated when `--extended` is passed
+    pub xattrs: Option<Vec<String>>,
+    /// Whether the chattr immutable (+i) flag is set; only populated with `--extended`
+    pub immutable: Option<bool>,
+    /// POSIX ACL entries beyond the base owner/group/other permissions; only populated with
+    /// `--extended`
+    pub acl: Option<String>,
+    /// File capabilities (e.g. `cap_net_bind_service`); only populated with `--extended`
+    pub capabilities: Option<String>,
 }
 
 /* ============================== HELPERS ============================== */
@@ -41,6 +147,494 @@ fn pct(v: f64) -> String {
     format!("{v:.3}%")
 }
 
+fn sha256_file(path: &Path) -> eyre::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Symbolic permission string in the style of `ls -l`, e.g. `-rw-r--r--`
+fn format_mode(mode: u32) -> String {
+    let bit = |shift: u32, c: char| if mode & (1 << shift) != 0 { c } else { '-' };
+
+    format!(
+        "-{}{}{}{}{}{}{}{}{}",
+        bit(8, 'r'),
+        bit(7, 'w'),
+        bit(6, 'x'),
+        bit(5, 'r'),
+        bit(4, 'w'),
+        bit(3, 'x'),
+        bit(2, 'r'),
+        bit(1, 'w'),
+        bit(0, 'x'),
+    )
+}
+
+/// Extended attribute `name=value` pairs, via `getfattr`
+#[cfg(unix)]
+fn list_xattrs(path: &Path) -> Option<Vec<String>> {
+    let out = Cmd::new("getfattr")
+        .args(["-d", "--absolute-names"])
+        .arg(path.to_string_lossy())
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+
+    let names = out
+        .stdout
+        .lines()
+        .filter(|line| line.contains('='))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    (!names.is_empty()).then_some(names)
+}
+
+/// Whether the chattr immutable flag (`i`) is set, via `lsattr`
+#[cfg(unix)]
+fn is_immutable(path: &Path) -> Option<bool> {
+    let out = Cmd::new("lsattr")
+        .arg(path.to_string_lossy())
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+
+    let flags = out.stdout.split_whitespace().next()?.to_string();
+    Some(flags.contains('i'))
+}
+
+/// POSIX ACL entries beyond the base owner/group/other permissions, via `getfacl`
+#[cfg(unix)]
+fn get_acl(path: &Path) -> Option<String> {
+    let out = Cmd::new("getfacl")
+        .arg("--omit-header")
+        .arg(path.to_string_lossy())
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+
+    let entries = out
+        .stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    (!entries.is_empty()).then_some(entries)
+}
+
+/// File capabilities (e.g. `cap_net_bind_service`), via `getcap`
+#[cfg(unix)]
+fn get_capabilities(path: &Path) -> Option<String> {
+    let out = Cmd::new("getcap")
+        .arg(path.to_string_lossy())
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+
+    let cap = out
+        .stdout
+        .trim()
+        .split_once(' ')
+        .map(|(_, cap)| cap.trim().to_string())?;
+    (!cap.is_empty()).then_some(cap)
+}
+
+#[cfg(unix)]
+fn stat_file(path: &Path, max_size: Option<u64>, extended: bool) -> eyre::Result<FileRecord> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::symlink_metadata(path)?;
+
+    let sha256 = if metadata.is_file() && max_size.is_none_or(|max| metadata.size() <= max) {
+        sha256_file(path).ok()
+    } else {
+        None
+    };
+
+    let (xattrs, immutable, acl, capabilities) = if extended {
+        (
+            list_xattrs(path),
+            is_immutable(path),
+            get_acl(path),
+            get_capabilities(path),
+        )
+    } else {
+        (None, None, None, None)
+    };
+
+    Ok(FileRecord {
+        path: path.to_path_buf(),
+        size: metadata.size(),
+        mode: Some(metadata.mode() & 0o7777),
+        owner: Some((metadata.uid(), metadata.gid())),
+        accessed: DateTime::from_timestamp(metadata.atime(), 0).unwrap_or_default(),
+        modified: DateTime::from_timestamp(metadata.mtime(), 0).unwrap_or_default(),
+        changed: Some(DateTime::from_timestamp(metadata.ctime(), 0).unwrap_or_default()),
+        sha256,
+        xattrs,
+        immutable,
+        acl,
+        capabilities,
+    })
+}
+
+#[cfg(windows)]
+fn stat_file(path: &Path, max_size: Option<u64>, _extended: bool) -> eyre::Result<FileRecord> {
+    let metadata = fs::symlink_metadata(path)?;
+    let size = metadata.len();
+
+    let sha256 = if metadata.is_file() && max_size.is_none_or(|max| size <= max) {
+        sha256_file(path).ok()
+    } else {
+        None
+    };
+
+    Ok(FileRecord {
+        path: path.to_path_buf(),
+        size,
+        mode: None,
+        owner: None,
+        accessed: metadata
+            .accessed()
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_default(),
+        modified: metadata
+            .modified()
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_default(),
+        changed: None,
+        sha256,
+        xattrs: None,
+        immutable: None,
+        acl: None,
+        capabilities: None,
+    })
+}
+
+fn collect_file_records(args: &FileStat) -> Vec<FileRecord> {
+    let mut records = Vec::new();
+
+    for path in &args.paths {
+        let mut walker = WalkDir::new(path);
+        if !args.recursive {
+            walker = walker.max_depth(1);
+        } else if let Some(max_depth) = args.max_depth {
+            walker = walker.max_depth(max_depth);
+        }
+
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    continue;
+                }
+            };
+
+            if entry.file_type().is_dir() {
+                continue;
+            }
+
+            match stat_file(entry.path(), args.max_size, args.extended) {
+                Ok(record) => records.push(record),
+                Err(e) => eprintln!("Could not stat {}: {e}", entry.path().display()),
+            }
+        }
+    }
+
+    records
+}
+
+fn print_file_records(records: &[FileRecord]) {
+    for record in records {
+        let mode = record
+            .mode
+            .map(|m| format!("{} ({m:o})", format_mode(m)))
+            .unwrap_or_else(|| "-".to_string());
+        let owner = record
+            .owner
+            .map(|(uid, gid)| format!("{uid}:{gid}"))
+            .unwrap_or_else(|| "-".to_string());
+        let changed = record
+            .changed
+            .map(|c| c.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "-".to_string());
+
+        println!(
+            "{mode} {:>9} {:<9} {} {} {} {}",
+            record.size,
+            owner,
+            record.accessed.format("%Y-%m-%d %H:%M:%S"),
+            record.modified.format("%Y-%m-%d %H:%M:%S"),
+            changed,
+            record.sha256.as_deref().unwrap_or("-"),
+        );
+        println!("  {}", record.path.display());
+
+        if record.immutable == Some(true) {
+            println!("    immutable");
+        }
+        if let Some(acl) = &record.acl {
+            println!("    acl: {acl}");
+        }
+        if let Some(capabilities) = &record.capabilities {
+            println!("    capabilities: {capabilities}");
+        }
+        if let Some(xattrs) = &record.xattrs {
+            for xattr in xattrs {
+                println!("    xattr: {xattr}");
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum PackageVerifyStatus {
+    /// File matches what the package manager recorded at install time
+    Ok,
+    /// File differs from what the package manager recorded; holds a short description
+    Modified(String),
+    /// File is not owned by any installed package
+    Unowned,
+    /// No supported package manager was found, or the check itself failed
+    Unknown(String),
+}
+
+#[derive(Debug)]
+struct PackageVerifyResult {
+    path: PathBuf,
+    package: Option<String>,
+    status: PackageVerifyStatus,
+}
+
+/// Ask dpkg which package owns `path`, then run `dpkg --verify` on that package and pick out
+/// the line for `path`
+#[cfg(unix)]
+fn verify_dpkg(path: &Path) -> Option<PackageVerifyResult> {
+    let owner_out = Cmd::new("dpkg")
+        .arg("-S")
+        .arg(path.to_string_lossy())
+        .output()
+        .ok()?;
+    if !owner_out.status.success() {
+        return None;
+    }
+
+    let package = owner_out.stdout.split(':').next()?.trim().to_string();
+
+    let verify_out = Cmd::new("dpkg")
+        .arg("--verify")
+        .arg(&package)
+        .output()
+        .ok()?;
+    let path_str = path.display().to_string();
+
+    let diff = verify_out
+        .stdout
+        .lines()
+        .find(|line| line.ends_with(path_str.as_str()));
+
+    let status = match diff {
+        Some(line) => PackageVerifyStatus::Modified(line.trim().to_string()),
+        None => PackageVerifyStatus::Ok,
+    };
+
+    Some(PackageVerifyResult {
+        path: path.to_path_buf(),
+        package: Some(package),
+        status,
+    })
+}
+
+/// Run `rpm --verify --file` on `path` and pick out the line for it, if any
+#[cfg(unix)]
+fn verify_rpm(path: &Path) -> Option<PackageVerifyResult> {
+    let owner_out = Cmd::new("rpm")
+        .arg("-qf")
+        .arg(path.to_string_lossy())
+        .output()
+        .ok()?;
+    if !owner_out.status.success() {
+        return None;
+    }
+    let package = owner_out.stdout.trim().to_string();
+
+    let verify_out = Cmd::new("rpm")
+        .args(["--verify", "--file"])
+        .arg(path.to_string_lossy())
+        .output()
+        .ok()?;
+    let path_str = path.display().to_string();
+
+    let diff = verify_out
+        .stdout
+        .lines()
+        .find(|line| line.ends_with(path_str.as_str()));
+
+    let status = match diff {
+        Some(line) => PackageVerifyStatus::Modified(line.trim().to_string()),
+        None => PackageVerifyStatus::Ok,
+    };
+
+    Some(PackageVerifyResult {
+        path: path.to_path_buf(),
+        package: Some(package),
+        status,
+    })
+}
+
+#[cfg(unix)]
+fn verify_against_packages(paths: &[PathBuf]) -> Vec<PackageVerifyResult> {
+    paths
+        .iter()
+        .map(|path| {
+            verify_dpkg(path)
+                .or_else(|| verify_rpm(path))
+                .unwrap_or(PackageVerifyResult {
+                    path: path.to_path_buf(),
+                    package: None,
+                    status: if which_package_manager_missing() {
+                        PackageVerifyStatus::Unknown(
+                            "no dpkg or rpm found on this system".to_string(),
+                        )
+                    } else {
+                        PackageVerifyStatus::Unowned
+                    },
+                })
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn which_package_manager_missing() -> bool {
+    qx("command -v dpkg")
+        .map(|(s, _)| !s.success())
+        .unwrap_or(true)
+        && qx("command -v rpm")
+            .map(|(s, _)| !s.success())
+            .unwrap_or(true)
+}
+
+#[cfg(windows)]
+fn verify_against_packages(paths: &[PathBuf]) -> Vec<PackageVerifyResult> {
+    paths
+        .iter()
+        .map(|path| PackageVerifyResult {
+            path: path.to_path_buf(),
+            package: None,
+            status: PackageVerifyStatus::Unknown(
+                "package manager verification is not supported on Windows".to_string(),
+            ),
+        })
+        .collect()
+}
+
+fn print_verify_results(results: &[PackageVerifyResult]) {
+    for result in results {
+        let package = result.package.as_deref().unwrap_or("-");
+        let (tag, detail) = match &result.status {
+            PackageVerifyStatus::Ok => ("OK", String::new()),
+            PackageVerifyStatus::Modified(diff) => ("MODIFIED", diff.clone()),
+            PackageVerifyStatus::Unowned => ("UNOWNED", String::new()),
+            PackageVerifyStatus::Unknown(reason) => ("UNKNOWN", reason.clone()),
+        };
+
+        println!(
+            "{tag:<8} {:<20} {} {detail}",
+            package,
+            result.path.display()
+        );
+    }
+}
+
+struct TimelineEvent {
+    timestamp: DateTime<Utc>,
+    kind: &'static str,
+    path: PathBuf,
+}
+
+/// Gathers atime/mtime/ctime for a tree and flattens them into a single chronologically
+/// sorted list of events, suitable for reconstructing an attacker's activity window.
+/// Hashing is skipped since only the timestamps are needed.
+fn build_timeline(args: &TimelineStat) -> Vec<TimelineEvent> {
+    let records = collect_file_records(&FileStat {
+        paths: args.paths.clone(),
+        recursive: args.recursive,
+        max_depth: args.max_depth,
+        max_size: Some(0),
+        format: OutputFormat::Text,
+        extended: false,
+    });
+
+    let mut events = Vec::new();
+
+    for record in records {
+        events.push(TimelineEvent {
+            timestamp: record.accessed,
+            kind: "ACCESS",
+            path: record.path.clone(),
+        });
+        events.push(TimelineEvent {
+            timestamp: record.modified,
+            kind: "MODIFY",
+            path: record.path.clone(),
+        });
+        if let Some(changed) = record.changed {
+            events.push(TimelineEvent {
+                timestamp: changed,
+                kind: "CHANGE",
+                path: record.path,
+            });
+        }
+    }
+
+    events.sort_by_key(|e| e.timestamp);
+    events
+}
+
+fn print_timeline_text(events: &[TimelineEvent]) {
+    for event in events {
+        println!(
+            "{} {:<6} {}",
+            event.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            event.kind,
+            event.path.display()
+        );
+    }
+}
+
+fn print_timeline_csv(events: &[TimelineEvent]) {
+    println!("timestamp,kind,path");
+    for event in events {
+        println!(
+            "{},{},{}",
+            event.timestamp.to_rfc3339(),
+            event.kind,
+            event.path.display()
+        );
+    }
+}
+
 /* ============================== COMMAND ============================== */
 
 impl super::Command for Stat {
@@ -88,6 +682,24 @@ impl super::Command for Stat {
                 );
                 println!("└──────────────────────────");
             }
+            StatCommands::File(args) => {
+                let records = collect_file_records(&args);
+                match args.format {
+                    OutputFormat::Text => print_file_records(&records),
+                    OutputFormat::Json => crate::utils::output::print_json(&records)?,
+                }
+            }
+            StatCommands::Verify(args) => {
+                let results = verify_against_packages(&args.paths);
+                print_verify_results(&results);
+            }
+            StatCommands::Timeline(args) => {
+                let events = build_timeline(&args);
+                match args.format {
+                    TimelineFormat::Text => print_timeline_text(&events),
+                    TimelineFormat::Csv => print_timeline_csv(&events),
+                }
+            }
         }
 
         Ok(())
@@ -0,0 +1,219 @@
+use std::{net::Ipv4Addr, path::Path};
+
+use colored::Colorize;
+use eyre::{Context, bail};
+use nix::unistd::geteuid;
+
+use crate::utils::{
+    os_version::get_distro,
+    packages::{DownloadSettings, install_apt_packages, install_dnf_packages},
+    qx, system,
+};
+
+const CONF_PATH: &str = "/etc/rsyslog.d/60-jj-forward.conf";
+const STATS_PATH: &str = "/var/log/jj-forward-stats.json";
+
+/// Configures rsyslog to forward all local logs to a remote collector over TCP, optionally
+/// wrapped in TLS, with a disk-assisted queue so a flaky link doesn't drop logs
+#[derive(clap::Parser, Debug)]
+#[command(version, about)]
+pub struct SyslogForward {
+    /// IP address of the log collector to forward to
+    collector_ip: Ipv4Addr,
+
+    /// TCP port the collector is listening on
+    #[arg(long, short, default_value_t = 514)]
+    collector_port: u16,
+
+    /// Wrap the forwarded connection in TLS
+    #[arg(long, short)]
+    tls: bool,
+
+    /// CA certificate to trust when verifying the collector (required with --tls)
+    #[arg(long, short = 'c')]
+    ca_cert: Option<std::path::PathBuf>,
+
+    /// Maximum amount of disk space the on-disk queue is allowed to use if the collector is
+    /// unreachable
+    #[arg(long, short = 'q', default_value = "1g")]
+    max_queue_size: String,
+
+    /// Use the download container when installing rsyslog to circumvent the host based firewall
+    #[arg(long, short = 'd')]
+    use_download_shell: bool,
+
+    /// Use a specific IP address for source NAT when downloading through the container
+    #[arg(long, short = 'I')]
+    sneaky_ip: Option<Ipv4Addr>,
+
+    /// Report what would change without writing or loading anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl super::Command for SyslogForward {
+    fn execute(self) -> eyre::Result<()> {
+        if !geteuid().is_root() {
+            bail!("You must be root to configure syslog forwarding");
+        }
+
+        if self.tls && self.ca_cert.is_none() {
+            bail!("--ca-cert is required when --tls is set");
+        }
+
+        let conf = self.render_conf();
+
+        if self.dry_run {
+            println!("{} Would write {CONF_PATH}:", "---".blue());
+            print!("{conf}");
+            return Ok(());
+        }
+
+        let distro = get_distro()?;
+
+        if !distro.is_rhel_or_deb_based() {
+            bail!("Syslog forwarding setup is only supported on RHEL or Debian based systems");
+        }
+
+        let download_settings = self
+            .use_download_shell
+            .then_some(DownloadSettings::Container {
+                name: None,
+                sneaky_ip: self.sneaky_ip,
+            })
+            .unwrap_or(DownloadSettings::NoContainer);
+
+        println!("{}", "--- Installing rsyslog...".green());
+
+        let mut packages = vec!["rsyslog"];
+        if self.tls {
+            packages.push("rsyslog-gnutls");
+        }
+
+        if distro.is_deb_based() {
+            install_apt_packages(download_settings, &packages)?;
+        } else {
+            install_dnf_packages(download_settings, &packages)?;
+        }
+
+        println!("{}", "--- Writing forwarding config...".green());
+
+        std::fs::create_dir_all(
+            Path::new(CONF_PATH)
+                .parent()
+                .context("Config path has no parent directory")?,
+        )?;
+        std::fs::write(CONF_PATH, &conf).with_context(|| format!("Could not write {CONF_PATH}"))?;
+
+        let (status, out) = qx(&format!("rsyslogd -N1 -f {CONF_PATH}"))?;
+        if !status.success() {
+            bail!("rsyslogd rejected the generated config:\n{out}");
+        }
+
+        system("systemctl enable rsyslog")?;
+        system("systemctl restart rsyslog")?;
+
+        self.verify_forwarding_flows()?;
+
+        println!(
+            "{}",
+            "--- Syslog forwarding configured and verified!".green()
+        );
+
+        Ok(())
+    }
+}
+
+impl SyslogForward {
+    fn render_conf(&self) -> String {
+        let stream_driver = if self.tls {
+            format!(
+                r#"$DefaultNetstreamDriverCAFile {}
+$ActionSendStreamDriver gtls
+$ActionSendStreamDriverMode 1
+$ActionSendStreamDriverAuthMode anon
+"#,
+                self.ca_cert.as_ref().expect("checked in execute").display()
+            )
+        } else {
+            String::new()
+        };
+
+        format!(
+            r#"module(load="impstats" interval="10" format="json" file="{STATS_PATH}")
+
+{stream_driver}
+action(
+    type="omfwd"
+    name="jj-forward"
+    target="{}"
+    port="{}"
+    protocol="tcp"
+    {}
+    queue.type="LinkedList"
+    queue.filename="jj-forward-queue"
+    queue.maxDiskSpace="{}"
+    queue.saveOnShutdown="on"
+    action.resumeRetryCount="-1"
+)
+"#,
+            self.collector_ip,
+            self.collector_port,
+            self.tls
+                .then_some(
+                    r#"StreamDriver="gtls" StreamDriverMode="1" StreamDriverAuthMode="anon""#
+                )
+                .unwrap_or_default(),
+            self.max_queue_size,
+        )
+    }
+
+    /// Sends a tagged message through the local syslog socket, then polls rsyslog's own
+    /// impstats counters for the `jj-forward` action, so a silently-broken forward (bad queue
+    /// config, module failed to load, etc.) is caught immediately instead of discovered when the
+    /// collector comes up empty
+    fn verify_forwarding_flows(&self) -> eyre::Result<()> {
+        println!(
+            "{}",
+            "--- Verifying logs flow to the forwarding queue...".green()
+        );
+
+        let before = forwarded_count()?;
+
+        system("logger -t jj-forward-verify 'jj syslog forwarding test message'")?;
+
+        for attempt in 0..10 {
+            if attempt > 0 {
+                std::thread::sleep(std::time::Duration::from_secs(1));
+            }
+
+            if forwarded_count()? > before {
+                return Ok(());
+            }
+        }
+
+        bail!("Configured forwarding, but the jj-forward action never processed any messages");
+    }
+}
+
+/// Reads the most recent `jj-forward` action counter out of rsyslog's impstats log
+fn forwarded_count() -> eyre::Result<u64> {
+    let contents = std::fs::read_to_string(STATS_PATH).unwrap_or_default();
+
+    let count = contents
+        .lines()
+        .rev()
+        .filter(|l| l.contains(r#""name":"jj-forward""#))
+        .find_map(|l| {
+            let idx = l.find(r#""processed":"#)? + r#""processed":"#.len();
+            l[idx..]
+                .trim_start()
+                .split(|c: char| !c.is_ascii_digit())
+                .next()?
+                .parse::<u64>()
+                .ok()
+        })
+        .unwrap_or(0);
+
+    Ok(count)
+}
@@ -0,0 +1,247 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, NaiveDateTime, TimeZone, Utc};
+use clap::Parser;
+use colored::Colorize;
+use eyre::Context;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::utils::qx;
+
+/// auth logs read directly if none are given on the command line
+const DEFAULT_AUTH_LOGS: &[&str] = &["/var/log/auth.log", "/var/log/secure"];
+
+/// Ingests auth logs, the systemd journal, and (optionally) web server access logs over a time
+/// window, normalizes them into one merged timeline of logins, sudo usage, service restarts, and
+/// firewall drops, and writes it out sorted by time — meant to speed up reconstructing what
+/// happened during an incident instead of grepping five different log files by hand
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Timeline {
+    /// How far back to look, e.g. "2h", "1d"
+    #[arg(short, long, default_value = "1d")]
+    lookback: humantime::Duration,
+
+    /// Web server access logs to include (combined log format). None are read by default
+    #[arg(long)]
+    web_log: Vec<PathBuf>,
+
+    /// auth log files to read directly, instead of the defaults
+    #[arg(long)]
+    auth_log: Vec<PathBuf>,
+
+    /// Where to write the timeline
+    #[arg(short, long, default_value = "/root/jj-timeline.json")]
+    output: PathBuf,
+
+    /// Write CSV instead of JSON
+    #[arg(long)]
+    csv: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+struct TimelineEvent {
+    timestamp: DateTime<Utc>,
+    source: String,
+    kind: String,
+    summary: String,
+}
+
+impl super::Command for Timeline {
+    fn execute(self) -> eyre::Result<()> {
+        let since = Utc::now()
+            - chrono::Duration::from_std(self.lookback.into())
+                .context("Lookback duration is out of range")?;
+
+        let mut events = vec![];
+        events.extend(collect_journal_events(since)?);
+        events.extend(collect_firewall_events(since)?);
+        events.extend(collect_auth_log_events(&self.auth_log, since));
+        events.extend(collect_web_log_events(&self.web_log, since));
+
+        events.sort_by_key(|e| e.timestamp);
+
+        if self.csv {
+            write_csv(&self.output, &events)?;
+        } else {
+            std::fs::write(&self.output, serde_json::to_string_pretty(&events)?)
+                .with_context(|| format!("Could not write {}", self.output.display()))?;
+        }
+
+        println!(
+            "{}",
+            format!(
+                "--- Wrote {} event(s) to {}",
+                events.len(),
+                self.output.display()
+            )
+            .green()
+        );
+
+        Ok(())
+    }
+}
+
+fn collect_journal_events(since: DateTime<Utc>) -> eyre::Result<Vec<TimelineEvent>> {
+    let lookback_secs = (Utc::now() - since).num_seconds().max(0);
+    let (_, output) = qx(&format!(
+        "journalctl --no-pager --since '-{lookback_secs}s' 2>/dev/null"
+    ))?;
+
+    Ok(parse_syslog_lines("journal", &output, since))
+}
+
+fn collect_firewall_events(since: DateTime<Utc>) -> eyre::Result<Vec<TimelineEvent>> {
+    let lookback_secs = (Utc::now() - since).num_seconds().max(0);
+    let (_, output) = qx(&format!(
+        "journalctl -k --no-pager --since '-{lookback_secs}s' 2>/dev/null"
+    ))?;
+
+    Ok(parse_syslog_lines("kernel", &output, since)
+        .into_iter()
+        .filter(|e| e.kind == "firewall_drop")
+        .collect())
+}
+
+fn collect_auth_log_events(paths: &[PathBuf], since: DateTime<Utc>) -> Vec<TimelineEvent> {
+    let paths: Vec<PathBuf> = if paths.is_empty() {
+        DEFAULT_AUTH_LOGS.iter().map(PathBuf::from).collect()
+    } else {
+        paths.to_vec()
+    };
+
+    let mut events = vec![];
+    for path in paths {
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        events.extend(parse_syslog_lines("auth_log", &contents, since));
+    }
+    events
+}
+
+fn collect_web_log_events(paths: &[PathBuf], since: DateTime<Utc>) -> Vec<TimelineEvent> {
+    let re = Regex::new(r#"^(\S+) \S+ \S+ \[([^\]]+)\] "(\S+) (\S+)[^"]*" (\d{3}) (\d+|-)"#)
+        .expect("Static regex failed after testing");
+
+    let mut events = vec![];
+    for path in paths {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            continue;
+        };
+
+        for line in contents.lines() {
+            let Some(caps) = re.captures(line) else {
+                continue;
+            };
+            let Some(timestamp) = parse_web_timestamp(&caps[2]) else {
+                continue;
+            };
+            if timestamp < since {
+                continue;
+            }
+
+            events.push(TimelineEvent {
+                timestamp,
+                source: "web_log".to_string(),
+                kind: "web_request".to_string(),
+                summary: format!("{} {} {} -> {}", &caps[1], &caps[3], &caps[4], &caps[5]),
+            });
+        }
+    }
+    events
+}
+
+/// Parses classic `Mon DD HH:MM:SS hostname message` syslog lines (the format both `journalctl`
+/// without `-o json` and on-disk auth logs use), classifying and timestamping each one
+fn parse_syslog_lines(source: &str, text: &str, since: DateTime<Utc>) -> Vec<TimelineEvent> {
+    let re = Regex::new(r"^(\w{3}\s+\d{1,2}\s+\d{2}:\d{2}:\d{2})\s+\S+\s+(.*)$")
+        .expect("Static regex failed after testing");
+    let now = Utc::now();
+
+    text.lines()
+        .filter_map(|line| {
+            let caps = re.captures(line)?;
+            let timestamp = parse_syslog_timestamp(&caps[1], now)?;
+            if timestamp < since {
+                return None;
+            }
+
+            let (kind, summary) = classify_message(&caps[2])?;
+            Some(TimelineEvent {
+                timestamp,
+                source: source.to_string(),
+                kind: kind.to_string(),
+                summary,
+            })
+        })
+        .collect()
+}
+
+/// Syslog timestamps carry no year, so this assumes `reference`'s year and falls back a year if
+/// that lands more than a day in the future (a log from late December read in early January)
+fn parse_syslog_timestamp(ts: &str, reference: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let normalized = ts.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let try_year = |year: i32| -> Option<DateTime<Utc>> {
+        let naive =
+            NaiveDateTime::parse_from_str(&format!("{normalized} {year}"), "%b %e %H:%M:%S %Y")
+                .ok()?;
+        Some(Utc.from_utc_datetime(&naive))
+    };
+
+    let candidate = try_year(reference.year())?;
+    if candidate > reference + chrono::Duration::days(1) {
+        try_year(reference.year() - 1)
+    } else {
+        Some(candidate)
+    }
+}
+
+fn parse_web_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(s, "%d/%b/%Y:%H:%M:%S %z")
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn classify_message(message: &str) -> Option<(&'static str, String)> {
+    if message.contains("Accepted password") || message.contains("Accepted publickey") {
+        Some(("login", message.to_string()))
+    } else if message.contains("Failed password") || message.contains("Invalid user") {
+        Some(("failed_login", message.to_string()))
+    } else if message.contains("sudo:") && message.contains("COMMAND=") {
+        Some(("sudo", message.to_string()))
+    } else if message.contains("inbound-drop:") || message.contains("outbound-drop:") {
+        Some(("firewall_drop", message.to_string()))
+    } else if message.contains(".service")
+        && (message.contains("Started") || message.contains("Stopped"))
+    {
+        Some(("service_event", message.to_string()))
+    } else {
+        None
+    }
+}
+
+fn write_csv(path: &Path, events: &[TimelineEvent]) -> eyre::Result<()> {
+    let mut out = String::from("timestamp,source,kind,summary\n");
+    for event in events {
+        out.push_str(&format!(
+            "{},{},{},{}\n",
+            event.timestamp.to_rfc3339(),
+            csv_escape(&event.source),
+            csv_escape(&event.kind),
+            csv_escape(&event.summary),
+        ));
+    }
+
+    std::fs::write(path, out).with_context(|| format!("Could not write {}", path.display()))
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
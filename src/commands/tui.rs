@@ -0,0 +1,311 @@
+//! Combined host-health dashboard, for an operator who wants ports, firewall, and check/alert
+//! status on one screen instead of juggling `jj ports -T`, `jj fw`, and a handful of log tails
+
+use std::{collections::BTreeMap, io::BufRead, path::PathBuf};
+
+use clap::Parser;
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table},
+};
+
+use crate::utils::checks::CheckResultType;
+use crate::utils::ports::{self, SocketRecord};
+
+/// Combined host-health dashboard: ports, firewall status, and recent check/alert activity in
+/// one screen, reusing the same data `jj ports`/`jj fw`/`jj check-daemon` already expose
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Tui {
+    /// Tail this check-daemon `--log-file` (newline-delimited JSON) for the checks pane
+    #[arg(long)]
+    check_log: Option<PathBuf>,
+
+    /// Tail this alert log (e.g. a `jj fim`/`jj canary`/`jj honeypot` `--log-file`) for the
+    /// alerts pane, shown as raw lines
+    #[arg(long)]
+    alert_log: Option<PathBuf>,
+
+    /// How often to refresh every pane
+    #[arg(long, short, default_value = "2s")]
+    interval: humantime::Duration,
+}
+
+impl super::Command for Tui {
+    fn execute(self) -> eyre::Result<()> {
+        main(self)
+    }
+}
+
+/// Latest known status of a single check-daemon check, as last logged to `--check-log`
+struct CheckStatus {
+    timestamp: chrono::DateTime<chrono::Utc>,
+    result: CheckResultType,
+}
+
+fn main(args: Tui) -> eyre::Result<()> {
+    let mut terminal = ratatui::init();
+
+    loop {
+        let sockets = ports::list_ports().unwrap_or_default();
+        let checks = read_check_log(args.check_log.as_deref());
+        let alerts = tail_lines(args.alert_log.as_deref(), 20);
+        let firewall = firewall_status();
+
+        terminal.draw(|frame| render(frame, &sockets, &checks, &alerts, &firewall))?;
+
+        if crossterm::event::poll(*args.interval)? {
+            let Event::Key(key) = crossterm::event::read()? else {
+                continue;
+            };
+
+            if key.kind == KeyEventKind::Press
+                && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+            {
+                break;
+            }
+        }
+    }
+
+    ratatui::restore();
+
+    Ok(())
+}
+
+/// Reads a check-daemon `--log-file`, keeping only the most recently logged result for each
+/// `host.check` id. Tolerant of an unset path or an unreadable/partially-written file, since
+/// this pane is a nice-to-have, not the dashboard's reason for existing
+fn read_check_log(path: Option<&std::path::Path>) -> BTreeMap<String, CheckStatus> {
+    let mut latest = BTreeMap::new();
+
+    let Some(path) = path else {
+        return latest;
+    };
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return latest;
+    };
+
+    for line in std::io::BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+
+        let Some(check_id) = value.get("check_id").and_then(serde_json::Value::as_str) else {
+            continue;
+        };
+        let Some(result) = value
+            .get("overall_result")
+            .and_then(|v| serde_json::from_value::<CheckResultType>(v.clone()).ok())
+        else {
+            continue;
+        };
+        let Some(timestamp) = value
+            .get("timestamp")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|s| s.parse().ok())
+        else {
+            continue;
+        };
+
+        latest.insert(check_id.to_string(), CheckStatus { timestamp, result });
+    }
+
+    latest
+}
+
+/// Returns the last `n` lines of `path`, or an explanatory placeholder if it's unset or
+/// unreadable
+fn tail_lines(path: Option<&std::path::Path>, n: usize) -> Vec<String> {
+    let Some(path) = path else {
+        return vec!["(no --alert-log configured)".to_string()];
+    };
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return vec![format!("(could not read {})", path.display())];
+    };
+
+    let lines = std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .collect::<Vec<_>>();
+
+    lines[lines.len().saturating_sub(n)..].to_vec()
+}
+
+#[cfg(unix)]
+fn firewall_status() -> Vec<String> {
+    let Ok(nft) = crate::utils::nft::Nft::new() else {
+        return vec!["(could not load bundled nft)".to_string()];
+    };
+
+    let Ok(output) = nft.command().arg("list ruleset").output() else {
+        return vec!["(could not run nft list ruleset)".to_string()];
+    };
+
+    let ruleset = String::from_utf8_lossy(&output.stdout);
+
+    let tables = ruleset
+        .lines()
+        .filter(|l| l.trim_start().starts_with("table "))
+        .count();
+    let chains = ruleset
+        .lines()
+        .filter(|l| l.trim_start().starts_with("chain "))
+        .count();
+    let panic_mode = ruleset.contains("table inet fw_panic");
+    let blocklist = ruleset.contains("table inet jj_blocklist");
+
+    vec![
+        format!("tables: {tables}, chains: {chains}"),
+        format!(
+            "panic mode: {}",
+            if panic_mode { "ACTIVE" } else { "inactive" }
+        ),
+        format!(
+            "jj_blocklist: {}",
+            if blocklist { "present" } else { "absent" }
+        ),
+    ]
+}
+
+#[cfg(windows)]
+fn firewall_status() -> Vec<String> {
+    vec!["(firewall pane is only implemented for Linux)".to_string()]
+}
+
+fn remote_string(s: &SocketRecord) -> String {
+    match (s.remote_addr(), s.remote_port()) {
+        (Some(addr), Some(port)) => format!("{addr}:{port}"),
+        _ => "-".to_string(),
+    }
+}
+
+fn result_style(result: &CheckResultType) -> Style {
+    match result {
+        CheckResultType::Success => Style::new().fg(Color::Green),
+        CheckResultType::Warning => Style::new().fg(Color::Yellow),
+        CheckResultType::Failure => Style::new().fg(Color::Red),
+        CheckResultType::NotRun => Style::new().fg(Color::Cyan),
+    }
+}
+
+fn render(
+    frame: &mut Frame,
+    sockets: &[SocketRecord],
+    checks: &BTreeMap<String, CheckStatus>,
+    alerts: &[String],
+    firewall: &[String],
+) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(frame.area());
+
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[0]);
+
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[1]);
+
+    render_ports(frame, top[0], sockets);
+    render_checks(frame, top[1], checks);
+    render_firewall(frame, bottom[0], firewall);
+    render_alerts(frame, bottom[1], alerts);
+}
+
+fn render_ports(frame: &mut Frame, area: ratatui::layout::Rect, sockets: &[SocketRecord]) {
+    let header = Row::new(vec!["Proto", "Local", "Remote", "State", "Command"])
+        .style(Style::new().add_modifier(Modifier::BOLD));
+
+    let rows = sockets.iter().map(|s| {
+        Row::new(vec![
+            format!("{}", s.socket_type()),
+            format!("{}:{}", s.local_addr(), s.local_port()),
+            remote_string(s),
+            format!("{}", s.state()),
+            s.exe().unwrap_or("").to_string(),
+        ])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(5),
+            Constraint::Length(22),
+            Constraint::Length(22),
+            Constraint::Length(12),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .block(Block::bordered().title(" ports "));
+
+    frame.render_widget(table, area);
+}
+
+fn render_checks(
+    frame: &mut Frame,
+    area: ratatui::layout::Rect,
+    checks: &BTreeMap<String, CheckStatus>,
+) {
+    let lines = if checks.is_empty() {
+        vec![Line::from(
+            "(no --check-log configured, or no results logged yet)",
+        )]
+    } else {
+        checks
+            .iter()
+            .map(|(check_id, status)| {
+                Line::from(format!(
+                    "{} {check_id} ({})",
+                    match status.result {
+                        CheckResultType::Success => "[ok]  ",
+                        CheckResultType::Warning => "[warn]",
+                        CheckResultType::Failure => "[fail]",
+                        CheckResultType::NotRun => "[    ]",
+                    },
+                    status.timestamp.format("%H:%M:%S")
+                ))
+                .style(result_style(&status.result))
+            })
+            .collect()
+    };
+
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" checks ")),
+        area,
+    );
+}
+
+fn render_firewall(frame: &mut Frame, area: ratatui::layout::Rect, firewall: &[String]) {
+    let lines = firewall
+        .iter()
+        .map(|l| Line::from(l.as_str()))
+        .collect::<Vec<_>>();
+
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" firewall ")),
+        area,
+    );
+}
+
+fn render_alerts(frame: &mut Frame, area: ratatui::layout::Rect, alerts: &[String]) {
+    let lines = alerts
+        .iter()
+        .map(|l| Line::from(l.as_str()))
+        .collect::<Vec<_>>();
+
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" alerts — q to quit ")),
+        area,
+    );
+}
@@ -0,0 +1,157 @@
+use std::{
+    fs::{self, File},
+    io::prelude::*,
+    os::unix::fs::{PermissionsExt, symlink},
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use colored::Colorize;
+use eyre::Context;
+
+#[cfg(feature = "bundled-tools")]
+use crate::utils::socat;
+use crate::utils::{busybox, embedded_tool_bytes_for_current_arch, nft};
+
+/// Extracts every embedded tool binary (busybox, nft, zsh, socat) into a target directory with
+/// correct modes, along with a PATH-setting activation script, for a one-shot toolbox drop onto a
+/// box that doesn't have much installed
+///
+/// Use it like so:
+///
+/// ```sh
+/// jj unpack -o /tmp/jj-tools
+/// source /tmp/jj-tools/activate.sh
+/// ```
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Unpack {
+    /// Directory to extract the embedded tools into
+    #[arg(short, long, default_value = "/tmp/jj-tools")]
+    output: PathBuf,
+
+    /// Also symlink standard tool names (sh, nft) alongside the bundled binaries, so the toolbox
+    /// directory can stand in for a normal PATH entry
+    #[arg(short, long)]
+    symlinks: bool,
+}
+
+impl super::Command for Unpack {
+    fn execute(self) -> eyre::Result<()> {
+        fs::create_dir_all(&self.output)
+            .with_context(|| format!("Could not create {}", self.output.display()))?;
+
+        extract_tool(
+            &self.output,
+            "busybox",
+            embedded_tool_bytes_for_current_arch(
+                busybox::BUSYBOX_BYTES_X86_64,
+                busybox::BUSYBOX_BYTES_AARCH64,
+            )?,
+        )?;
+        extract_tool(
+            &self.output,
+            "nft",
+            embedded_tool_bytes_for_current_arch(nft::NFT_BYTES_X86_64, nft::NFT_BYTES_AARCH64)?,
+        )?;
+        #[cfg(feature = "bundled-tools")]
+        {
+            extract_tool(
+                &self.output,
+                "zsh",
+                embedded_tool_bytes_for_current_arch(
+                    super::zsh::ZSH_BYTES_X86_64,
+                    super::zsh::ZSH_BYTES_AARCH64,
+                )?,
+            )?;
+            extract_tool(
+                &self.output,
+                "socat",
+                embedded_tool_bytes_for_current_arch(
+                    socat::SOCAT_BYTES_X86_64,
+                    socat::SOCAT_BYTES_AARCH64,
+                )?,
+            )?;
+        }
+
+        #[cfg(not(feature = "bundled-tools"))]
+        {
+            extract_fetched_tool(&self.output, "zsh")?;
+            extract_fetched_tool(&self.output, "socat")?;
+        }
+
+        if self.symlinks {
+            // busybox provides a `sh` applet when invoked under that name; the rest already use
+            // their standard names, so only this one needs linking
+            symlink_tool(&self.output, "busybox", "sh")?;
+        }
+
+        write_activation_script(&self.output)?;
+
+        println!(
+            "{} Unpacked tools to {}",
+            "---".green(),
+            self.output.display()
+        );
+        println!(
+            "Run `source {}/activate.sh` to add them to your PATH",
+            self.output.display()
+        );
+
+        Ok(())
+    }
+}
+
+/// Decompresses an embedded tool's gzipped bytes and writes it to `<dir>/<name>` with executable
+/// permissions
+fn extract_tool(dir: &Path, name: &str, gzipped: &[u8]) -> eyre::Result<()> {
+    let mut raw = Vec::new();
+    flate2::read::GzDecoder::new(gzipped)
+        .read_to_end(&mut raw)
+        .with_context(|| format!("Could not decompress embedded {name}"))?;
+
+    write_tool_file(dir, name, &raw)
+}
+
+/// Fetches a tool not bundled in this slim build and writes it to `<dir>/<name>` with executable
+/// permissions. Tools fetched this way (from a `jj serve --tools` instance) are already
+/// decompressed, unlike the embedded gzipped payloads `extract_tool` handles
+#[cfg(not(feature = "bundled-tools"))]
+fn extract_fetched_tool(dir: &Path, name: &str) -> eyre::Result<()> {
+    let raw = crate::utils::fetch_tool_bytes(name)?;
+    write_tool_file(dir, name, &raw)
+}
+
+/// Writes `bytes` to `<dir>/<name>` with executable permissions
+fn write_tool_file(dir: &Path, name: &str, bytes: &[u8]) -> eyre::Result<()> {
+    let path = dir.join(name);
+    let mut file =
+        File::create(&path).with_context(|| format!("Could not create {}", path.display()))?;
+    file.write_all(bytes)
+        .with_context(|| format!("Could not write {}", path.display()))?;
+    file.set_permissions(PermissionsExt::from_mode(0o755))
+        .with_context(|| format!("Could not set permissions on {}", path.display()))?;
+
+    Ok(())
+}
+
+/// Symlinks `<dir>/<link_name>` to an already-extracted `<dir>/<target_name>`
+fn symlink_tool(dir: &Path, target_name: &str, link_name: &str) -> eyre::Result<()> {
+    let link_path = dir.join(link_name);
+    let _ = fs::remove_file(&link_path);
+    symlink(target_name, &link_path)
+        .with_context(|| format!("Could not symlink {}", link_path.display()))
+}
+
+/// Writes a small shell script that prepends the toolbox directory to `PATH`
+fn write_activation_script(dir: &Path) -> eyre::Result<()> {
+    let script_path = dir.join("activate.sh");
+    let mut file = File::create(&script_path)
+        .with_context(|| format!("Could not create {}", script_path.display()))?;
+    writeln!(file, "#!/bin/sh")?;
+    writeln!(file, r#"export PATH="{}:$PATH""#, dir.display())?;
+    file.set_permissions(PermissionsExt::from_mode(0o755))
+        .with_context(|| format!("Could not set permissions on {}", script_path.display()))?;
+
+    Ok(())
+}
@@ -1,46 +1,897 @@
-use eyre::bail;
-use nix::unistd::geteuid;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{create_dir_all, remove_file, rename},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+use clap::Subcommand;
+use colored::Colorize;
+use eyre::{Context, bail};
+use nix::unistd::{User, chown};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     strvec,
-    utils::{busybox::Busybox, os_version::get_distro},
+    utils::{
+        busybox::Busybox,
+        dry_run,
+        os_version::get_distro,
+        passwd::{self, HashMethod, load_groups, load_users},
+        privilege,
+    },
 };
 
+#[derive(Subcommand, Debug)]
+enum UseraddCommands {
+    /// Lock or expire every login-capable account not in an allowlist
+    Lockdown(Lockdown),
+
+    /// Grant sudo access to a user via a drop-in file in /etc/sudoers.d
+    SudoGrant(SudoGrant),
+
+    /// Revoke a sudo grant previously installed by `sudo-grant`
+    SudoRevoke(SudoRevoke),
+
+    /// List every sudo grant currently installed under /etc/sudoers.d
+    SudoAudit(SudoAudit),
+
+    /// Read-only scan for risky account hygiene: UID 0 impostors, empty password hashes,
+    /// service accounts with login shells, recent password changes, and suspicious home
+    /// directory dotfiles
+    Audit(Audit),
+
+    /// Reconcile group membership (e.g. wheel/sudo/docker) against a desired-state file,
+    /// adding missing members and removing ones that shouldn't be there
+    Reconcile(Reconcile),
+}
+
+/// Directory sudo itself reads drop-in grants from
+const SUDOERS_DIR: &str = "/etc/sudoers.d";
+
+#[derive(clap::Parser, Debug)]
+struct SudoGrant {
+    /// User to grant sudo access to
+    user: String,
+
+    /// Sudoers rule to grant, as it would appear after the username in a sudoers file
+    #[arg(long, default_value = "ALL=(ALL:ALL) ALL")]
+    rule: String,
+
+    /// Grant the rule without requiring the user to re-enter their password
+    #[arg(long)]
+    nopasswd: bool,
+}
+
+impl SudoGrant {
+    fn execute(self) -> eyre::Result<()> {
+        privilege::require_root("grant sudo access")?;
+
+        create_dir_all(SUDOERS_DIR).with_context(|| format!("Could not create {SUDOERS_DIR}"))?;
+
+        let rule = if self.nopasswd {
+            format!("NOPASSWD: {}", self.rule)
+        } else {
+            self.rule
+        };
+        let contents = format!(
+            "# Installed by jj useradd sudo-grant\n{} {rule}\n",
+            self.user
+        );
+
+        let staged = Path::new(SUDOERS_DIR).join(format!(".jj-{}.staged", self.user));
+        std::fs::write(&staged, contents)
+            .with_context(|| format!("Could not write {}", staged.display()))?;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o440))?;
+
+        let check = std::process::Command::new("visudo")
+            .args(["-c", "-f"])
+            .arg(&staged)
+            .output()
+            .context("Could not run visudo to validate the sudoers drop-in")?;
+
+        if !check.status.success() {
+            let _ = remove_file(&staged);
+            bail!(
+                "visudo rejected the generated sudoers drop-in, not installing it:\n{}",
+                String::from_utf8_lossy(&check.stderr)
+            );
+        }
+
+        let target = Path::new(SUDOERS_DIR).join(format!("jj-{}", self.user));
+        rename(&staged, &target)
+            .with_context(|| format!("Could not install {}", target.display()))?;
+
+        println!(
+            "{} Granted {} sudo access via {}",
+            "---".blue(),
+            self.user,
+            target.display()
+        );
+        Ok(())
+    }
+}
+
+#[derive(clap::Parser, Debug)]
+struct SudoRevoke {
+    /// User to revoke a previously granted sudo drop-in from
+    user: String,
+}
+
+impl SudoRevoke {
+    fn execute(self) -> eyre::Result<()> {
+        privilege::require_root("revoke sudo access")?;
+
+        let target = Path::new(SUDOERS_DIR).join(format!("jj-{}", self.user));
+        if !target.exists() {
+            bail!(
+                "No jj-managed sudo grant found for {} at {}",
+                self.user,
+                target.display()
+            );
+        }
+
+        remove_file(&target).with_context(|| format!("Could not remove {}", target.display()))?;
+        println!("{} Revoked sudo access for {}", "---".blue(), self.user);
+        Ok(())
+    }
+}
+
+#[derive(clap::Parser, Debug)]
+struct SudoAudit {}
+
+impl SudoAudit {
+    fn execute(self) -> eyre::Result<()> {
+        let entries = std::fs::read_dir(SUDOERS_DIR)
+            .with_context(|| format!("Could not read {SUDOERS_DIR}"))?;
+
+        let mut found = false;
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            // sudoers.d ignores dotfiles and backup files, same as visudo/sudo do
+            if name.starts_with('.') || name.ends_with('~') {
+                continue;
+            }
+
+            let contents = std::fs::read_to_string(entry.path())
+                .with_context(|| format!("Could not read {}", entry.path().display()))?;
+            for line in contents.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                found = true;
+                println!("{} {}: {}", "---".blue(), name, line);
+            }
+        }
+
+        if !found {
+            println!("No sudo grants found under {SUDOERS_DIR}");
+        }
+
+        Ok(())
+    }
+}
+
+/// Shells that mark an account as not login-capable, so lockdown leaves them alone
+const NOLOGIN_SHELLS: &[&str] = &[
+    "/sbin/nologin",
+    "/usr/sbin/nologin",
+    "/bin/false",
+    "/usr/bin/false",
+];
+
+#[derive(clap::Parser, Debug)]
+struct Lockdown {
+    /// File listing usernames (one per line, '#' comments allowed) that must never be locked
+    #[arg(long)]
+    allow: PathBuf,
+
+    /// Accounts with a UID below this are treated as system accounts and always excluded
+    #[arg(long, default_value_t = 1000)]
+    min_uid: u32,
+
+    /// Report what would be locked without changing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Where to record the accounts locked by this run, so the team has a list to unlock later
+    #[arg(long, default_value = "/root/jj-lockdown-undo.txt")]
+    undo_file: PathBuf,
+}
+
+impl Lockdown {
+    fn execute(self) -> eyre::Result<()> {
+        privilege::require_root("lock down accounts")?;
+
+        let allowlist = Self::read_allowlist(&self.allow)?;
+
+        let mut locked = Vec::new();
+        let mut allowlisted = 0;
+
+        for user in load_users(None::<&str>)? {
+            if user.uid < self.min_uid || allowlist.contains(&user.user) {
+                if allowlist.contains(&user.user) {
+                    allowlisted += 1;
+                }
+                continue;
+            }
+            if NOLOGIN_SHELLS.contains(&user.shell.as_str()) {
+                continue;
+            }
+            if user.password.starts_with('!') || user.password.starts_with('*') {
+                // Already locked. Only catches this when /etc/passwd carries the real hash
+                // instead of the usual shadow placeholder, but it's a free check either way
+                continue;
+            }
+
+            if self.dry_run {
+                println!("{} {}", "would lock".yellow(), user.user);
+            } else {
+                passwd::lock_account(&user.user)?;
+                println!("{} {}", "locked".red(), user.user);
+            }
+            locked.push(user.user);
+        }
+
+        println!(
+            "{} {} account(s) {}, {allowlisted} allowlisted",
+            "---".blue(),
+            locked.len(),
+            if self.dry_run {
+                "would be locked"
+            } else {
+                "locked"
+            }
+        );
+
+        if !self.dry_run && !locked.is_empty() {
+            std::fs::write(&self.undo_file, format!("{}\n", locked.join("\n")))
+                .with_context(|| format!("Could not write {}", self.undo_file.display()))?;
+            println!("Wrote undo file to {}", self.undo_file.display());
+        }
+
+        Ok(())
+    }
+
+    fn read_allowlist(path: &Path) -> eyre::Result<HashSet<String>> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read allowlist {}", path.display()))?;
+
+        Ok(contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect())
+    }
+}
+
+/// A single account flagged by `jj useradd audit`, with the reasons it was flagged
+#[derive(Debug, Serialize)]
+struct AuditFinding {
+    user: String,
+    uid: u32,
+    home: String,
+    shell: String,
+    issues: Vec<String>,
+}
+
+/// Dotfiles that are expected to show up directly under `$HOME` on a normal account
+const EXPECTED_HOME_DOTFILES: &[&str] = &[
+    ".bashrc",
+    ".bash_profile",
+    ".bash_logout",
+    ".profile",
+    ".ssh",
+    ".cache",
+    ".config",
+    ".local",
+    ".viminfo",
+    ".lesshst",
+];
+
+#[derive(clap::Parser, Debug)]
+struct Audit {
+    /// UID below which an account is treated as a service account, which shouldn't have a
+    /// usable login shell
+    #[arg(long, default_value_t = 1000)]
+    min_uid: u32,
+
+    /// Flag accounts whose password was changed within this many days
+    #[arg(long, default_value_t = 7)]
+    recent_days: i64,
+
+    /// Print findings as a JSON array instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+impl Audit {
+    fn execute(self) -> eyre::Result<()> {
+        let shadow = Self::read_shadow().unwrap_or_else(|e| {
+            eprintln!(
+                "{} Could not read /etc/shadow ({e}), skipping password-hash and password-age checks",
+                "warning:".yellow()
+            );
+            HashMap::new()
+        });
+        let today = Self::days_since_epoch();
+
+        let mut findings = Vec::new();
+
+        for user in load_users(None::<&str>)? {
+            let mut issues = Vec::new();
+
+            if user.uid == 0 && user.user != "root" {
+                issues.push("UID 0 but not named root".to_string());
+            }
+
+            if let Some(shadow_entry) = shadow.get(&user.user) {
+                if shadow_entry.password.is_empty() {
+                    issues.push("empty password hash in /etc/shadow".to_string());
+                }
+
+                if let Some(lastchange) = shadow_entry.lastchange {
+                    let age = today - lastchange;
+                    if age >= 0 && age <= self.recent_days {
+                        issues.push(format!("password changed {age} day(s) ago"));
+                    }
+                }
+            }
+
+            if user.uid != 0
+                && user.uid < self.min_uid
+                && !NOLOGIN_SHELLS.contains(&user.shell.as_str())
+            {
+                issues.push(format!(
+                    "service account (uid {}) has a login shell: {}",
+                    user.uid, user.shell
+                ));
+            }
+
+            issues.extend(Self::suspicious_home_files(&user.home));
+
+            if !issues.is_empty() {
+                findings.push(AuditFinding {
+                    user: user.user,
+                    uid: user.uid,
+                    home: user.home,
+                    shell: user.shell,
+                    issues,
+                });
+            }
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&findings)?);
+        } else if findings.is_empty() {
+            println!("{}", "--- No issues found".green());
+        } else {
+            for finding in &findings {
+                println!(
+                    "{} {} {}",
+                    "---".yellow(),
+                    finding.user,
+                    format!("(uid {})", finding.uid).dimmed()
+                );
+                for issue in &finding.issues {
+                    println!("    {issue}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Flag dotfiles directly under `$HOME` that aren't part of the normal account setup, plus
+    /// a `.bash_history` that's been symlinked away (a common way to stop shell history logging)
+    fn suspicious_home_files(home: &str) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        let history = Path::new(home).join(".bash_history");
+        if std::fs::symlink_metadata(&history)
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false)
+        {
+            issues.push(".bash_history is a symlink, likely to suppress shell history".to_string());
+        }
+
+        let Ok(entries) = std::fs::read_dir(home) else {
+            return issues;
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+
+            if !name.starts_with('.')
+                || name == "."
+                || name == ".."
+                || EXPECTED_HOME_DOTFILES.contains(&name.as_ref())
+            {
+                continue;
+            }
+
+            issues.push(format!("unexpected dotfile in home directory: {name}"));
+        }
+
+        issues
+    }
+
+    fn days_since_epoch() -> i64 {
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("1970-01-01 is a valid date");
+        chrono::Utc::now()
+            .date_naive()
+            .signed_duration_since(epoch)
+            .num_days()
+    }
+
+    fn read_shadow() -> eyre::Result<HashMap<String, ShadowEntry>> {
+        let contents = std::fs::read_to_string("/etc/shadow")
+            .context("Could not read /etc/shadow, are you root?")?;
+
+        Ok(contents
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split(':');
+                let user = fields.next()?.to_string();
+                let password = fields.next()?.to_string();
+                let lastchange = fields.next()?.parse::<i64>().ok();
+
+                Some((
+                    user,
+                    ShadowEntry {
+                        password,
+                        lastchange,
+                    },
+                ))
+            })
+            .collect())
+    }
+}
+
+/// The fields of `/etc/shadow` relevant to `jj useradd audit`
+struct ShadowEntry {
+    password: String,
+    lastchange: Option<i64>,
+}
+
+#[derive(clap::Parser, Debug)]
+struct Reconcile {
+    /// Desired-state file (YAML or JSON) mapping group name to the list of members it should
+    /// have. Groups on the system but not mentioned in this file are left alone
+    config: PathBuf,
+
+    /// Report additions/removals without changing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Only reconcile these groups, ignoring every other group mentioned in the config. Repeat
+    /// the flag for more than one. Defaults to every group the config file lists
+    #[arg(long = "group")]
+    groups: Vec<String>,
+}
+
+impl Reconcile {
+    fn execute(self) -> eyre::Result<()> {
+        privilege::require_root("reconcile group membership")?;
+
+        let desired = Self::read_desired_state(&self.config)?;
+        let current = load_groups(None::<&str>)?;
+        let bb = Busybox::new()?;
+
+        let wanted_groups: Vec<&String> = if self.groups.is_empty() {
+            desired.keys().collect()
+        } else {
+            self.groups.iter().collect()
+        };
+
+        for group_name in wanted_groups {
+            let Some(wanted_members) = desired.get(group_name) else {
+                eprintln!(
+                    "{} No desired state for group {group_name}, skipping",
+                    "warning:".yellow()
+                );
+                continue;
+            };
+
+            let Some(group) = current.iter().find(|g| &g.name == group_name) else {
+                eprintln!(
+                    "{} Group {group_name} does not exist on this system, skipping",
+                    "warning:".yellow()
+                );
+                continue;
+            };
+
+            let wanted: HashSet<&str> = wanted_members.iter().map(String::as_str).collect();
+            let actual: HashSet<&str> = group
+                .user_list
+                .iter()
+                .map(String::as_str)
+                .filter(|m| !m.is_empty())
+                .collect();
+
+            for extra in actual.difference(&wanted) {
+                if self.dry_run {
+                    println!("{} would remove {extra} from {group_name}", "---".yellow());
+                } else {
+                    bb.command_checked("delgroup")?
+                        .arg(extra)
+                        .arg(group_name)
+                        .spawn()?
+                        .wait()?;
+                    println!("{} removed {extra} from {group_name}", "---".red());
+                }
+            }
+
+            for missing in wanted.difference(&actual) {
+                if self.dry_run {
+                    println!("{} would add {missing} to {group_name}", "---".yellow());
+                } else {
+                    bb.command_checked("addgroup")?
+                        .arg(missing)
+                        .arg(group_name)
+                        .spawn()?
+                        .wait()?;
+                    println!("{} added {missing} to {group_name}", "---".green());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_desired_state(path: &Path) -> eyre::Result<HashMap<String, Vec<String>>> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&contents)
+                .with_context(|| format!("Could not parse {} as JSON", path.display())),
+            _ => serde_yaml_ng::from_str(&contents)
+                .with_context(|| format!("Could not parse {} as YAML", path.display())),
+        }
+    }
+}
+
+/// A single row from a `--users-file`: only `name` is required, everything else falls back to
+/// the same defaults as `--users`
+#[derive(Debug, Deserialize)]
+struct BulkUser {
+    name: String,
+    #[serde(default)]
+    groups: Vec<String>,
+    shell: Option<String>,
+    key: Option<String>,
+}
+
 /// Add backup users to the system
 #[derive(clap::Parser, Debug)]
 #[command(version, about)]
 pub struct Useradd {
+    #[command(subcommand)]
+    command: Option<UseraddCommands>,
+
     /// Backup users to add
     #[arg(
         short, long,
         default_values_t = strvec!["redboi", "blueguy"]
     )]
     users: Vec<String>,
+
+    /// CSV or YAML file listing users to create in bulk, idempotently. Columns/keys: name
+    /// (required), groups (';'-separated), shell, key (an SSH public key to authorize)
+    #[arg(short = 'f', long)]
+    users_file: Option<PathBuf>,
+
+    /// Generate a strong unique password for each user touched by this run (new or already
+    /// present), set it non-interactively, and record it in --credential-sheet instead of
+    /// prompting for a password
+    #[arg(long)]
+    generate_passwords: bool,
+
+    /// Where to write the printable credential sheet produced by --generate-passwords. Written
+    /// with 0600 permissions, but is plaintext: move it to encrypted storage promptly
+    #[arg(long, default_value = "/root/jj-credentials.txt")]
+    credential_sheet: PathBuf,
+
+    /// SSH public key to install into authorized_keys for every user created via --users.
+    /// Repeat the flag to install more than one key
+    #[arg(long = "ssh-key")]
+    ssh_keys: Vec<String>,
+
+    /// Lock password login for every user touched by this run, so an installed SSH key (via
+    /// --ssh-key or a CSV/YAML `key` column) is the only way in
+    #[arg(long)]
+    disable_password_login: bool,
+
+    /// Report which users from --users would be created, without creating anything. Does not
+    /// cover --users-file, since its idempotency check and credential generation aren't
+    /// meaningful to preview
+    #[arg(long)]
+    dry_run: bool,
 }
 
 impl super::Command for Useradd {
     fn execute(self) -> eyre::Result<()> {
-        if !geteuid().is_root() {
-            bail!("You must be root to add backup users");
+        match self.command {
+            Some(UseraddCommands::Lockdown(lockdown)) => return lockdown.execute(),
+            Some(UseraddCommands::SudoGrant(grant)) => return grant.execute(),
+            Some(UseraddCommands::SudoRevoke(revoke)) => return revoke.execute(),
+            Some(UseraddCommands::SudoAudit(audit)) => return audit.execute(),
+            Some(UseraddCommands::Audit(audit)) => return audit.execute(),
+            Some(UseraddCommands::Reconcile(reconcile)) => return reconcile.execute(),
+            None => {}
         }
 
+        privilege::require_root("add backup users")?;
+
         let bb = Busybox::new()?;
 
         let sudo_group = if get_distro()?.is_deb_based() {
             "sudo"
         } else {
             "wheel"
-        };
+        }
+        .to_string();
+
+        let mut credentials = Vec::new();
+
+        for user in &self.users {
+            dry_run::step(self.dry_run, format!("create user {user}"), || {
+                bb.command_checked("adduser")?
+                    .args(["-S", "-s", "/bin/sh", "-G", &sudo_group, user])
+                    .spawn()?
+                    .wait()?;
+
+                for key in &self.ssh_keys {
+                    Self::install_authorized_key(user, key)?;
+                }
+
+                if self.disable_password_login {
+                    passwd::lock_account(user)?;
+                } else if self.generate_passwords {
+                    let password = Self::generate_password();
+                    passwd::set_password(user, &password, HashMethod::Yescrypt)?;
+                    credentials.push((user.clone(), password));
+                } else {
+                    bb.command_checked("passwd")?.arg(user).spawn()?.wait()?;
+                }
+
+                Ok(())
+            })?;
+        }
+
+        if let Some(path) = &self.users_file {
+            if self.dry_run {
+                println!(
+                    "{} would process --users-file {}",
+                    "would".yellow(),
+                    path.display()
+                );
+            } else {
+                self.create_bulk_users(&bb, path, &sudo_group, &mut credentials)?;
+            }
+        }
+
+        if self.generate_passwords && !credentials.is_empty() {
+            Self::write_credential_sheet(&self.credential_sheet, &credentials)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Useradd {
+    fn create_bulk_users(
+        &self,
+        bb: &Busybox,
+        path: &Path,
+        default_group: &str,
+        credentials: &mut Vec<(String, String)>,
+    ) -> eyre::Result<()> {
+        let users = Self::parse_users_file(path)?;
+
+        let mut created = Vec::new();
+        let mut present = Vec::new();
+
+        for user in users {
+            let groups = if user.groups.is_empty() {
+                vec![default_group.to_string()]
+            } else {
+                user.groups
+            };
+            let shell = user.shell.as_deref().unwrap_or("/bin/sh");
+
+            if Self::add_user(bb, &user.name, &groups, shell)? {
+                created.push(user.name.clone());
+            } else {
+                present.push(user.name.clone());
+            }
+
+            if let Some(key) = &user.key {
+                Self::install_authorized_key(&user.name, key)?;
+            }
 
-        for user in self.users {
-            println!("Adding user {user}");
-            bb.command("adduser")
-                .args(["-S", "-s", "/bin/sh", "-G", sudo_group, &user])
+            if self.disable_password_login {
+                passwd::lock_account(&user.name)?;
+            } else if self.generate_passwords {
+                let password = Self::generate_password();
+                passwd::set_password(&user.name, &password, HashMethod::Yescrypt)?;
+                credentials.push((user.name.clone(), password));
+            }
+        }
+
+        println!(
+            "{} {} created, {} already present",
+            "---".blue(),
+            created.len(),
+            present.len()
+        );
+        if !created.is_empty() {
+            println!("Created: {}", created.join(", "));
+        }
+        if !present.is_empty() {
+            println!("Already present: {}", present.join(", "));
+        }
+
+        Ok(())
+    }
+
+    /// Create the user with the given groups/shell if it does not already exist. Returns
+    /// whether a new user was created.
+    fn add_user(bb: &Busybox, name: &str, groups: &[String], shell: &str) -> eyre::Result<bool> {
+        if User::from_name(name)?.is_some() {
+            println!("User {name} already exists, skipping");
+            return Ok(false);
+        }
+
+        println!("Adding user {name}");
+        let mut args = vec!["-S", "-s", shell];
+        if let Some(primary_group) = groups.first() {
+            args.extend(["-G", primary_group]);
+        }
+        args.push(name);
+        bb.command_checked("adduser")?.args(args).spawn()?.wait()?;
+
+        for group in groups.iter().skip(1) {
+            bb.command_checked("addgroup")?
+                .args([name, group])
                 .spawn()?
                 .wait()?;
-            bb.command("passwd").arg(user).spawn()?.wait()?;
         }
 
+        Ok(true)
+    }
+
+    /// A 20-character alphanumeric password, strong enough for a service account and short
+    /// enough to retype by hand if the credential sheet isn't available
+    fn generate_password() -> String {
+        use rand::Rng;
+
+        let mut rng = rand::rng();
+        (0..20)
+            .map(|_| rng.sample(rand::distr::Alphanumeric) as char)
+            .collect()
+    }
+
+    /// Write the printable credential sheet. Plaintext, but restricted to 0600 so only root can
+    /// read it until the team moves it to encrypted storage
+    fn write_credential_sheet(path: &Path, credentials: &[(String, String)]) -> eyre::Result<()> {
+        let mut sheet = String::from("# jj useradd generated credentials\n");
+        for (name, password) in credentials {
+            sheet.push_str(&format!("{name}: {password}\n"));
+        }
+
+        std::fs::write(path, &sheet)
+            .with_context(|| format!("Could not write credential sheet to {}", path.display()))?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+        println!(
+            "Wrote credential sheet for {} user(s) to {}",
+            credentials.len(),
+            path.display()
+        );
         Ok(())
     }
+
+    /// Append `key` to the user's authorized_keys, creating ~/.ssh with correct ownership and
+    /// permissions if needed. No-op if the key is already present.
+    fn install_authorized_key(name: &str, key: &str) -> eyre::Result<()> {
+        let user = User::from_name(name)?
+            .ok_or_else(|| eyre::eyre!("No such user {name} to install an SSH key for"))?;
+
+        let ssh_dir = user.dir.join(".ssh");
+        create_dir_all(&ssh_dir)
+            .with_context(|| format!("Could not create {}", ssh_dir.display()))?;
+
+        let authorized_keys = ssh_dir.join("authorized_keys");
+        let mut contents = std::fs::read_to_string(&authorized_keys).unwrap_or_default();
+        let key = key.trim();
+
+        if !contents.lines().any(|line| line.trim() == key) {
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                contents.push('\n');
+            }
+            contents.push_str(key);
+            contents.push('\n');
+            std::fs::write(&authorized_keys, &contents)
+                .with_context(|| format!("Could not write {}", authorized_keys.display()))?;
+        }
+
+        std::fs::set_permissions(&ssh_dir, std::fs::Permissions::from_mode(0o700))?;
+        std::fs::set_permissions(&authorized_keys, std::fs::Permissions::from_mode(0o600))?;
+        chown(&ssh_dir, Some(user.uid), Some(user.gid))?;
+        chown(&authorized_keys, Some(user.uid), Some(user.gid))?;
+
+        Ok(())
+    }
+
+    fn parse_users_file(path: &Path) -> eyre::Result<Vec<BulkUser>> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml" | "yml") => serde_yaml_ng::from_str(&contents)
+                .with_context(|| format!("Could not parse {} as YAML", path.display())),
+            _ => Self::parse_users_csv(&contents)
+                .with_context(|| format!("Could not parse {} as CSV", path.display())),
+        }
+    }
+
+    /// Hand-rolled CSV parsing (no quoting support) matching the repo's existing low bar for
+    /// delimited text; a bundled public key has no commas, so this is sufficient here
+    fn parse_users_csv(contents: &str) -> eyre::Result<Vec<BulkUser>> {
+        let mut lines = contents.lines().filter(|l| !l.trim().is_empty());
+
+        let header: Vec<String> = lines
+            .next()
+            .ok_or_else(|| eyre::eyre!("CSV file has no header row"))?
+            .split(',')
+            .map(|h| h.trim().to_lowercase())
+            .collect();
+        let column = |name: &str| header.iter().position(|h| h == name);
+
+        let name_col =
+            column("name").ok_or_else(|| eyre::eyre!("CSV header is missing a name column"))?;
+        let groups_col = column("groups");
+        let shell_col = column("shell");
+        let key_col = column("key");
+
+        let mut users = Vec::new();
+        for line in lines {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let Some(name) = fields.get(name_col).filter(|n| !n.is_empty()) else {
+                continue;
+            };
+
+            users.push(BulkUser {
+                name: (*name).to_string(),
+                groups: groups_col
+                    .and_then(|i| fields.get(i))
+                    .map(|g| {
+                        g.split(';')
+                            .map(str::trim)
+                            .filter(|g| !g.is_empty())
+                            .map(String::from)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                shell: shell_col
+                    .and_then(|i| fields.get(i))
+                    .filter(|s| !s.is_empty())
+                    .map(|s| (*s).to_string()),
+                key: key_col
+                    .and_then(|i| fields.get(i))
+                    .filter(|k| !k.is_empty())
+                    .map(|s| (*s).to_string()),
+            });
+        }
+
+        Ok(users)
+    }
 }
@@ -0,0 +1,135 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use clap::Subcommand;
+use colored::Colorize;
+use eyre::bail;
+
+use crate::strvec;
+
+#[derive(Subcommand, Debug)]
+enum UseraddCommands {
+    /// Disable a local account, leaving it in place but unable to log in
+    Disable(Disable),
+}
+
+#[derive(clap::Parser, Debug)]
+struct Disable {
+    /// User to disable
+    user: String,
+}
+
+impl Disable {
+    fn execute(self) -> eyre::Result<()> {
+        println!("{} Disabling {}", "---".blue(), self.user);
+
+        let status = Command::new("net")
+            .args(["user", &self.user, "/active:no"])
+            .spawn()?
+            .wait()?;
+
+        if !status.success() {
+            bail!("Could not disable {}, does the account exist?", self.user);
+        }
+
+        Ok(())
+    }
+}
+
+/// Add backup users on Windows, mirroring the Linux `useradd` UX: local accounts created via
+/// `net user` (a thin wrapper over the NetUserAdd API), optional generated passwords recorded
+/// to a credential sheet, and Administrators membership via `net localgroup`
+/// (NetLocalGroupAddMembers)
+#[derive(clap::Parser, Debug)]
+#[command(version, about)]
+pub struct Useradd {
+    #[command(subcommand)]
+    command: Option<UseraddCommands>,
+
+    /// Backup users to add
+    #[arg(short, long, default_values_t = strvec!["redboi", "blueguy"])]
+    users: Vec<String>,
+
+    /// Add every user created by this run to the local Administrators group
+    #[arg(long)]
+    admin: bool,
+
+    /// Record the generated password for each user in --credential-sheet instead of just
+    /// printing it once
+    #[arg(long)]
+    generate_passwords: bool,
+
+    /// Where to write the printable credential sheet produced by --generate-passwords. Written
+    /// plaintext, so move it to encrypted storage promptly
+    #[arg(long, default_value = r"C:\jj-credentials.txt")]
+    credential_sheet: PathBuf,
+}
+
+impl super::Command for Useradd {
+    fn execute(self) -> eyre::Result<()> {
+        if let Some(UseraddCommands::Disable(disable)) = self.command {
+            return disable.execute();
+        }
+
+        let admin_group = "Administrators";
+        let mut credentials = Vec::new();
+
+        for user in &self.users {
+            println!("{} Adding user {user}", "---".blue());
+
+            let password = Self::generate_password();
+
+            let status = Command::new("net")
+                .args(["user", user, &password, "/add"])
+                .spawn()?
+                .wait()?;
+
+            if !status.success() {
+                bail!("Could not add user {user}, does it already exist?");
+            }
+
+            if self.admin {
+                Command::new("net")
+                    .args(["localgroup", admin_group, user, "/add"])
+                    .spawn()?
+                    .wait()?;
+            }
+
+            if self.generate_passwords {
+                credentials.push((user.clone(), password));
+            } else {
+                println!("Generated password for {user}: {password}");
+            }
+        }
+
+        if self.generate_passwords && !credentials.is_empty() {
+            Self::write_credential_sheet(&self.credential_sheet, &credentials)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Useradd {
+    fn generate_password() -> String {
+        use rand::prelude::*;
+
+        let mut rng = rand::rng();
+        (0..20)
+            .map(|_| rng.sample(rand::distr::Alphanumeric) as char)
+            .collect()
+    }
+
+    fn write_credential_sheet(path: &Path, credentials: &[(String, String)]) -> eyre::Result<()> {
+        let mut sheet = String::from("# jj useradd generated credentials\n");
+        for (user, password) in credentials {
+            sheet.push_str(&format!("{user}: {password}\n"));
+        }
+
+        std::fs::write(path, sheet)?;
+        println!("Wrote credential sheet to {}", path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use clap::Parser;
+use colored::Colorize;
+use eyre::Context;
+
+use crate::utils::sha256_hex;
+#[cfg(unix)]
+use crate::utils::{busybox, nft};
+#[cfg(all(unix, feature = "bundled-tools"))]
+use crate::utils::{pamtester, socat, yara};
+
+/// Checks embedded tool payloads against hashes baked in at build time, and reports the SHA-256
+/// of the running jj binary itself, so operators can confirm neither jj nor its bundled tools
+/// have been swapped out by the red team
+///
+/// The running jj binary's own hash can't be baked into itself, so it's only reported here, not
+/// checked — compare it against a known-good value from a separate, trusted channel
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Verify {}
+
+impl super::Command for Verify {
+    fn execute(self) -> eyre::Result<()> {
+        let mut all_ok = true;
+
+        // busybox and nft are bundled regardless of the `bundled-tools` feature, since the
+        // download container needs them even in a slim build
+        #[cfg(unix)]
+        {
+            all_ok &= check_tool(
+                "busybox",
+                "x86_64",
+                busybox::BUSYBOX_BYTES_X86_64,
+                busybox::BUSYBOX_SHA256_X86_64,
+            );
+            all_ok &= check_tool(
+                "busybox",
+                "aarch64",
+                busybox::BUSYBOX_BYTES_AARCH64,
+                busybox::BUSYBOX_SHA256_AARCH64,
+            );
+            all_ok &= check_tool(
+                "nft",
+                "x86_64",
+                nft::NFT_BYTES_X86_64,
+                nft::NFT_SHA256_X86_64,
+            );
+            all_ok &= check_tool(
+                "nft",
+                "aarch64",
+                nft::NFT_BYTES_AARCH64,
+                nft::NFT_SHA256_AARCH64,
+            );
+        }
+
+        #[cfg(all(unix, feature = "bundled-tools"))]
+        {
+            all_ok &= check_tool(
+                "zsh",
+                "x86_64",
+                super::zsh::ZSH_BYTES_X86_64,
+                super::zsh::ZSH_SHA256_X86_64,
+            );
+            all_ok &= check_tool(
+                "zsh",
+                "aarch64",
+                super::zsh::ZSH_BYTES_AARCH64,
+                super::zsh::ZSH_SHA256_AARCH64,
+            );
+            all_ok &= check_tool(
+                "socat",
+                "x86_64",
+                socat::SOCAT_BYTES_X86_64,
+                socat::SOCAT_SHA256_X86_64,
+            );
+            all_ok &= check_tool(
+                "socat",
+                "aarch64",
+                socat::SOCAT_BYTES_AARCH64,
+                socat::SOCAT_SHA256_AARCH64,
+            );
+            all_ok &= check_tool(
+                "pamtester",
+                "x86_64",
+                pamtester::PAMTESTER_BYTES_X86_64,
+                pamtester::PAMTESTER_SHA256_X86_64,
+            );
+            all_ok &= check_tool(
+                "pamtester",
+                "aarch64",
+                pamtester::PAMTESTER_BYTES_AARCH64,
+                pamtester::PAMTESTER_SHA256_AARCH64,
+            );
+            all_ok &= check_tool(
+                "yara",
+                "x86_64",
+                yara::YARA_BYTES_X86_64,
+                yara::YARA_SHA256_X86_64,
+            );
+            all_ok &= check_tool(
+                "yara",
+                "aarch64",
+                yara::YARA_BYTES_AARCH64,
+                yara::YARA_SHA256_AARCH64,
+            );
+        }
+
+        #[cfg(not(feature = "bundled-tools"))]
+        println!(
+            "{} zsh, socat, and pamtester are not bundled in this slim build; fetched copies are \
+             not covered by this check",
+            "---".blue()
+        );
+
+        let current_exe =
+            std::env::current_exe().context("Could not find the current jj binary")?;
+        let jj_bytes = std::fs::read(&current_exe)
+            .with_context(|| format!("Could not read {}", current_exe.display()))?;
+        println!(
+            "{} Running jj binary ({}): {}",
+            "---".blue(),
+            current_exe.display(),
+            sha256_hex(&jj_bytes)
+        );
+
+        if !all_ok {
+            eyre::bail!("One or more embedded tool payloads did not match their expected hash");
+        }
+
+        Ok(())
+    }
+}
+
+/// Hashes an embedded tool's gzipped payload and compares it to the expected hash, printing a
+/// pass/fail line and returning whether it matched
+#[cfg(unix)]
+fn check_tool(name: &str, arch: &str, gzipped: &[u8], expected: &str) -> bool {
+    let actual = sha256_hex(gzipped);
+    let ok = actual.eq_ignore_ascii_case(expected);
+
+    if ok {
+        println!("{} {name} ({arch}): {actual}", "OK".green());
+    } else {
+        println!(
+            "{} {name} ({arch}): expected {expected}, got {actual}",
+            "FAIL".red()
+        );
+    }
+
+    ok
+}
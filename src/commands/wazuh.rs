@@ -13,11 +13,11 @@ use eyre::Context;
 use libc::getuid;
 
 use crate::{
-    pcre,
+    pcre, strvec,
     utils::{
         busybox::Busybox,
         download_container::DownloadContainer,
-        download_file, get_public_ip,
+        download_file, download_file_mirrors, get_public_ip,
         os_version::{Distro, get_distro},
         packages::{install_apt_packages, install_dnf_packages},
         passwd, qx, system,
@@ -65,13 +65,17 @@ pub struct WazuhSubcommandArgs {
     #[arg(long, short = 'S', default_value = "9.3.0")]
     pub jj_elastic_version: String,
 
-    /// URL to download Logstash from; used when setting up independent logstash
-    #[arg(long, default_value = "https://artifacts.elastic.co/downloads")]
-    pub download_url: String,
+    /// URL to download Logstash from; used when setting up independent logstash. Can be
+    /// repeated to give an ordered list of mirrors, each one tried in turn if the previous one
+    /// fails
+    #[arg(long, default_values_t = strvec!["https://artifacts.elastic.co/downloads"])]
+    pub download_url: Vec<String>,
 
-    /// URL to download Auditbeat, Filebeat, Packetbeat, Metricbeat, and Winlogbeat from; used when setting up independent logstash
-    #[arg(long, default_value = "https://artifacts.elastic.co/downloads/beats")]
-    pub beats_download_url: String,
+    /// URL to download Auditbeat, Filebeat, Packetbeat, Metricbeat, and Winlogbeat from; used
+    /// when setting up independent logstash. Can be repeated to give an ordered list of
+    /// mirrors, each one tried in turn if the previous one fails
+    #[arg(long, default_values_t = strvec!["https://artifacts.elastic.co/downloads/beats"])]
+    pub beats_download_url: Vec<String>,
 
     /// Public NAT IP for Wazuh and Logstash
     #[arg(long, short = 'N')]
@@ -130,6 +134,36 @@ pub struct WazuhAgentCommandArgs {
     dont_install_clamav: bool,
 }
 
+/// A parsed `user@host[:port]` line from a `--hosts` file
+struct DeployTarget {
+    /// The line as given, used to label per-host results
+    spec: String,
+    /// `user@host`, with any `:port` suffix stripped, as `ssh`/`scp` expect their target
+    user_host: String,
+    port: Option<u16>,
+}
+
+#[derive(Parser, Debug)]
+pub struct WazuhDeployAgentsArgs {
+    /// File with one `user@host[:port]` target per line. Blank lines and lines starting with
+    /// `#` are ignored
+    #[arg(long)]
+    hosts: PathBuf,
+
+    /// The IP address agents should report to, and register against, once installed
+    #[arg(long, short = 'i')]
+    wazuh_ip: Ipv4Addr,
+
+    /// Where to find the already-downloaded `wazuh-agent.rpm`/`wazuh-agent.deb` packages, e.g.
+    /// the `jj-elastic-share-location` an earlier `jj wazuh download-files` populated
+    #[arg(long, short = 'p', default_value = "/opt/es-share")]
+    package_dir: PathBuf,
+
+    /// Extra options passed through to both ssh and scp
+    #[arg(long, default_values_t = strvec!["-o", "StrictHostKeyChecking=no", "-o", "ConnectTimeout=10"])]
+    ssh_opt: Vec<String>,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum WazuhCommands {
     /// Install Wazuh completely
@@ -199,6 +233,10 @@ pub enum WazuhCommands {
     /// Install and configure agents and beats on an endpoint
     #[command(visible_alias = "agents")]
     InstallAgents(WazuhAgentCommandArgs),
+
+    /// Push the agent package to a fleet of hosts over SSH, install it, and verify it connects
+    #[command(visible_alias = "da")]
+    DeployAgents(WazuhDeployAgentsArgs),
 }
 
 /// Install, configure, and manage Wazuh on this server
@@ -211,13 +249,19 @@ pub struct Wazuh {
 
 impl super::Command for Wazuh {
     fn execute(self) -> eyre::Result<()> {
+        use WazuhCommands as WC;
+
+        // Runs from an operator's own machine over SSH, not on the target hosts, so it doesn't
+        // need to run as root or on a supported distro itself
+        if let WC::DeployAgents(args) = &self.command {
+            return deploy_agents(args);
+        }
+
         if unsafe { getuid() } != 0 {
             eprintln!("{}", "!!! This script requires you to run as root".red());
             return Ok(());
         }
 
-        use WazuhCommands as WC;
-
         let distro = get_distro()?;
 
         if !distro.is_rhel_or_deb_based() {
@@ -578,13 +622,20 @@ fn download_files(args: &WazuhSubcommandArgs, os: &Distro) -> eyre::Result<()> {
 
             let download_package = {
                 let mut dest_path = args.jj_elastic_share_location.clone();
-                let url = format!(
-                    "{}/logstash/logstash-{}-linux-x86_64.tar.gz",
-                    args.download_url, args.jj_elastic_version
-                );
+                let urls = args
+                    .download_url
+                    .iter()
+                    .map(|base| {
+                        format!(
+                            "{base}/logstash/logstash-{}-linux-x86_64.tar.gz",
+                            args.jj_elastic_version
+                        )
+                    })
+                    .collect::<Vec<_>>();
                 move || {
                     dest_path.push(format!("logstash.tar.gz"));
-                    let res = download_file(&url, dest_path);
+                    let mirrors = urls.iter().map(String::as_str).collect::<Vec<_>>();
+                    let res = download_file_mirrors(&mirrors, dest_path);
                     println!("Done downloading logstash!");
                     res
                 }
@@ -597,16 +648,23 @@ fn download_files(args: &WazuhSubcommandArgs, os: &Distro) -> eyre::Result<()> {
 
             for beat in ["auditbeat", "filebeat", "packetbeat", "metricbeat"] {
                 let download_package = {
-                    let url = format!(
-                        "{}/{}/{}-{}-linux-x86_64.tar.gz",
-                        args.beats_download_url, beat, beat, args.jj_elastic_version
-                    );
+                    let urls = args
+                        .beats_download_url
+                        .iter()
+                        .map(|base| {
+                            format!(
+                                "{base}/{beat}/{beat}-{}-linux-x86_64.tar.gz",
+                                args.jj_elastic_version
+                            )
+                        })
+                        .collect::<Vec<_>>();
                     let mut dest_path = args.jj_elastic_share_location.clone();
                     let beat = beat.to_string();
 
                     move || {
                         dest_path.push(format!("{beat}.tar.gz"));
-                        let res = download_file(&url, dest_path);
+                        let mirrors = urls.iter().map(String::as_str).collect::<Vec<_>>();
+                        let res = download_file_mirrors(&mirrors, dest_path);
                         println!("Done downloading {beat} for Linux!");
                         res
                     }
@@ -621,15 +679,22 @@ fn download_files(args: &WazuhSubcommandArgs, os: &Distro) -> eyre::Result<()> {
             for beat in ["winlogbeat", "filebeat", "packetbeat", "metricbeat"] {
                 let download_package = {
                     let mut dest_path = args.jj_elastic_share_location.clone();
-                    let url = format!(
-                        "{}/{}/{}-{}-windows-x86_64.zip",
-                        args.beats_download_url, beat, beat, args.jj_elastic_version
-                    );
+                    let urls = args
+                        .beats_download_url
+                        .iter()
+                        .map(|base| {
+                            format!(
+                                "{base}/{beat}/{beat}-{}-windows-x86_64.zip",
+                                args.jj_elastic_version
+                            )
+                        })
+                        .collect::<Vec<_>>();
                     let beat = beat.to_string();
 
                     move || {
                         dest_path.push(format!("{beat}.zip"));
-                        let res = download_file(&url, dest_path);
+                        let mirrors = urls.iter().map(String::as_str).collect::<Vec<_>>();
+                        let res = download_file_mirrors(&mirrors, dest_path);
                         println!("Done downloading {beat} for Windows!");
                         res
                     }
@@ -660,7 +725,7 @@ fn download_files(args: &WazuhSubcommandArgs, os: &Distro) -> eyre::Result<()> {
     };
 
     if args.use_download_shell {
-        let container = DownloadContainer::new(None, args.sneaky_ip)?;
+        let container = DownloadContainer::new(None, args.sneaky_ip, None, None)?;
 
         container.run(download_files_internal)??;
     } else {
@@ -2074,7 +2139,7 @@ fn forward_jj_logstash_stage1(
     };
 
     if args.use_download_shell {
-        let container = DownloadContainer::new(None, args.sneaky_ip)?;
+        let container = DownloadContainer::new(None, args.sneaky_ip, None, None)?;
 
         container.run(install_logstash_opensearch)??;
     } else {
@@ -2686,6 +2751,187 @@ fn tweak_max_compilations_rate(bb: &Busybox, wazuh_password: &str, rate: u32) ->
     Ok(())
 }
 
+fn deploy_agents(args: &WazuhDeployAgentsArgs) -> eyre::Result<()> {
+    let targets = std::fs::read_to_string(&args.hosts)
+        .with_context(|| format!("Could not read {}", args.hosts.display()))?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_deploy_target)
+        .collect::<Vec<_>>();
+
+    println!(
+        "{} Deploying Wazuh agents to {} host(s)",
+        "---".blue(),
+        targets.len()
+    );
+
+    let mut threads = Vec::new();
+
+    for target in targets {
+        let wazuh_ip = args.wazuh_ip;
+        let package_dir = args.package_dir.clone();
+        let ssh_opt = args.ssh_opt.clone();
+
+        threads.push(std::thread::spawn(move || {
+            let result = deploy_agent_to_target(&target, wazuh_ip, &package_dir, &ssh_opt);
+            (target.spec, result)
+        }));
+    }
+
+    let mut failures = 0;
+
+    for thread in threads {
+        match thread.join() {
+            Ok((spec, Ok(()))) => println!("{} {spec}", "succeeded".green()),
+            Ok((spec, Err(e))) => {
+                failures += 1;
+                println!("{} {spec}: {e}", "failed".red());
+            }
+            Err(_) => {
+                failures += 1;
+                eprintln!(
+                    "{}",
+                    "!!! Could not join deployment thread due to panic!".red()
+                );
+            }
+        }
+    }
+
+    if failures > 0 {
+        eyre::bail!("{failures} host(s) failed agent deployment");
+    }
+
+    Ok(())
+}
+
+fn parse_deploy_target(line: &str) -> DeployTarget {
+    let (user_host, port) = match line.rsplit_once(':') {
+        Some((uh, p)) if !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()) => {
+            (uh.to_string(), p.parse().ok())
+        }
+        _ => (line.to_string(), None),
+    };
+
+    DeployTarget {
+        spec: line.to_string(),
+        user_host,
+        port,
+    }
+}
+
+/// `ssh_opt` plus a `port_flag <port>` pair, if the target specified a non-default port
+fn deploy_ssh_args(ssh_opt: &[String], port: Option<u16>, port_flag: &str) -> Vec<String> {
+    let mut args = ssh_opt.to_vec();
+    if let Some(port) = port {
+        args.push(port_flag.to_string());
+        args.push(port.to_string());
+    }
+    args
+}
+
+fn ssh_run(target: &DeployTarget, ssh_opt: &[String], remote_cmd: &str) -> eyre::Result<String> {
+    let output = Command::new("ssh")
+        .args(deploy_ssh_args(ssh_opt, target.port, "-p"))
+        .arg(&target.user_host)
+        .arg(remote_cmd)
+        .output()
+        .context("Could not spawn ssh")?;
+
+    if !output.status.success() {
+        eyre::bail!(
+            "ssh exited with {} running `{remote_cmd}`: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn deploy_agent_to_target(
+    target: &DeployTarget,
+    wazuh_ip: Ipv4Addr,
+    package_dir: &std::path::Path,
+    ssh_opt: &[String],
+) -> eyre::Result<()> {
+    let os_release = ssh_run(
+        target,
+        ssh_opt,
+        ". /etc/os-release 2>/dev/null; echo \"$ID $ID_LIKE\"",
+    )
+    .context("Could not detect the remote distro")?;
+
+    let is_rhel_like = os_release
+        .split_whitespace()
+        .any(|id| matches!(id, "rhel" | "centos" | "fedora" | "rocky" | "almalinux"));
+
+    let (package_file, remote_package, install_cmd) = if is_rhel_like {
+        (
+            "wazuh-agent.rpm",
+            "/tmp/wazuh-agent.rpm",
+            "rpm -ivh /tmp/wazuh-agent.rpm",
+        )
+    } else {
+        (
+            "wazuh-agent.deb",
+            "/tmp/wazuh-agent.deb",
+            "dpkg -i /tmp/wazuh-agent.deb",
+        )
+    };
+
+    let package_path = package_dir.join(package_file);
+
+    println!("Copying {} to {}...", package_path.display(), target.spec);
+
+    let status = Command::new("scp")
+        .args(deploy_ssh_args(ssh_opt, target.port, "-P"))
+        .arg(&package_path)
+        .arg(format!("{}:{remote_package}", target.user_host))
+        .status()
+        .context("Could not spawn scp")?;
+
+    if !status.success() {
+        eyre::bail!("scp exited with {status} while pushing the agent package");
+    }
+
+    let hostname =
+        ssh_run(target, ssh_opt, "hostname").context("Could not read remote hostname")?;
+
+    println!("Installing Wazuh agent on {}...", target.spec);
+    ssh_run(
+        target,
+        ssh_opt,
+        &format!(
+            "sudo env WAZUH_MANAGER={wazuh_ip} WAZUH_AGENT_NAME={hostname} {install_cmd} && \
+             sudo systemctl daemon-reload && sudo systemctl enable wazuh-agent && \
+             sudo systemctl restart wazuh-agent"
+        ),
+    )
+    .context("Could not install and start the Wazuh agent")?;
+
+    println!("Verifying {} connected to {wazuh_ip}...", target.spec);
+    for attempt in 0..12 {
+        if attempt > 0 {
+            std::thread::sleep(std::time::Duration::from_secs(5));
+        }
+
+        let connected = ssh_run(
+            target,
+            ssh_opt,
+            "sudo systemctl is-active --quiet wazuh-agent && \
+             sudo grep -q 'Connected to the server' /var/ossec/logs/ossec.log",
+        )
+        .is_ok();
+
+        if connected {
+            return Ok(());
+        }
+    }
+
+    eyre::bail!("agent installed but never reported connecting to {wazuh_ip}")
+}
+
 fn install_agents(bb: &Busybox, distro: &Distro, args: &WazuhAgentCommandArgs) -> eyre::Result<()> {
     println!("--- Downloading Wazuh agent installer...");
 
@@ -2696,7 +2942,7 @@ fn install_agents(bb: &Busybox, distro: &Distro, args: &WazuhAgentCommandArgs) -
     };
 
     if args.use_download_shell {
-        let container = DownloadContainer::new(None, args.sneaky_ip)?;
+        let container = DownloadContainer::new(None, args.sneaky_ip, None, None)?;
 
         container.run(|| {
             download_file(
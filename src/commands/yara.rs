@@ -0,0 +1,174 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use colored::Colorize;
+use eyre::Context;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::utils::{sha256_hex, yara::Yara as YaraTool};
+
+include!(concat!(env!("OUT_DIR"), "/yara_default_rules.rs"));
+
+/// Scans files (and optionally running process memory) against a bundled set of common
+/// webshell/implant rules plus any user-supplied rule files, using an embedded copy of yara
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Yara {
+    /// Files or directories to scan
+    paths: Vec<PathBuf>,
+
+    /// Additional .yar rule files to scan with, on top of the bundled default ruleset
+    #[arg(short, long)]
+    rules: Vec<PathBuf>,
+
+    /// Also scan the memory of every running process
+    #[arg(long)]
+    scan_processes: bool,
+
+    /// Write matches as JSON to this path, in addition to printing them
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Serialize, Debug)]
+struct Match {
+    rule: String,
+    target: String,
+    offset: String,
+    identifier: String,
+    sha256: Option<String>,
+}
+
+impl super::Command for Yara {
+    fn execute(self) -> eyre::Result<()> {
+        if self.paths.is_empty() && !self.scan_processes {
+            eyre::bail!("Nothing to scan; give one or more paths or pass --scan-processes");
+        }
+
+        let rules_dir = std::env::temp_dir().join(format!("jj-yara-rules-{}", std::process::id()));
+        std::fs::create_dir_all(&rules_dir)
+            .with_context(|| format!("Could not create {}", rules_dir.display()))?;
+
+        let mut rule_paths = vec![];
+        for (name, contents) in YARA_DEFAULT_RULES {
+            let path = rules_dir.join(name);
+            std::fs::write(&path, contents)
+                .with_context(|| format!("Could not write bundled rule {name}"))?;
+            rule_paths.push(path);
+        }
+        rule_paths.extend(self.rules.iter().cloned());
+
+        let yara = YaraTool::new()?;
+        let mut matches = vec![];
+
+        for path in &self.paths {
+            matches.extend(scan_target(&yara, &rule_paths, path, Some(path))?);
+        }
+
+        if self.scan_processes {
+            for entry in std::fs::read_dir("/proc")?.filter_map(Result::ok) {
+                let Some(pid) = entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|n| n.parse::<u32>().ok())
+                else {
+                    continue;
+                };
+
+                matches.extend(scan_target(&yara, &rule_paths, &pid.to_string(), None)?);
+            }
+        }
+
+        if matches.is_empty() {
+            println!("{}", "--- No matches found".green());
+        } else {
+            for m in &matches {
+                println!(
+                    "{} {} in {} ({} @ {})",
+                    "[MATCH]".red(),
+                    m.rule,
+                    m.target,
+                    m.identifier,
+                    m.offset
+                );
+            }
+            println!(
+                "{}",
+                format!("--- {} match(es) found, investigate above", matches.len()).red()
+            );
+        }
+
+        if let Some(output) = &self.output {
+            std::fs::write(output, serde_json::to_string_pretty(&matches)?)
+                .with_context(|| format!("Could not write {}", output.display()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs yara against one target (a file, directory, or pid) and parses its matches. `hash_path`
+/// is `Some` when the target is a real file worth hashing, `None` for process-memory targets
+fn scan_target(
+    yara: &YaraTool,
+    rule_paths: &[PathBuf],
+    target: &str,
+    hash_path: Option<&Path>,
+) -> eyre::Result<Vec<Match>> {
+    let recurse = hash_path.is_some_and(|p| p.is_dir());
+
+    let mut command = yara.command();
+    command.arg("-s");
+    if recurse {
+        command.arg("-r");
+    }
+    command.args(rule_paths).arg(target);
+
+    let output = command
+        .output()
+        .with_context(|| format!("Could not run yara against {target}"))?;
+
+    let sha256 = hash_path
+        .filter(|p| p.is_file())
+        .and_then(|p| std::fs::read(p).ok())
+        .map(|bytes| sha256_hex(&bytes));
+
+    Ok(parse_matches(
+        &String::from_utf8_lossy(&output.stdout),
+        sha256,
+    ))
+}
+
+/// Parses yara's `-s` plain-text output:
+///
+/// ```text
+/// rule_identifier target
+/// 0x10:$a: eval(base64_decode(
+/// ```
+fn parse_matches(output: &str, sha256: Option<String>) -> Vec<Match> {
+    let header_re = Regex::new(r"^(\S+) (.+)$").expect("Static regex failed after testing");
+    let string_re =
+        Regex::new(r"^(0x[0-9a-fA-F]+):(\S+):").expect("Static regex failed after testing");
+
+    let mut matches = vec![];
+    let mut current: Option<(String, String)> = None;
+
+    for line in output.lines() {
+        if let Some(caps) = string_re.captures(line) {
+            if let Some((rule, target)) = &current {
+                matches.push(Match {
+                    rule: rule.clone(),
+                    target: target.clone(),
+                    offset: caps[1].to_string(),
+                    identifier: caps[2].to_string(),
+                    sha256: sha256.clone(),
+                });
+            }
+        } else if let Some(caps) = header_re.captures(line) {
+            current = Some((caps[1].to_string(), caps[2].to_string()));
+        }
+    }
+
+    matches
+}
@@ -12,6 +12,7 @@ use std::{
 
 use clap::Parser;
 use eyre::Context;
+#[cfg(feature = "bundled-tools")]
 use flate2::write::GzDecoder;
 use nix::{
     sys::memfd::{MFdFlags, memfd_create},
@@ -49,7 +50,17 @@ pub struct Zsh {
     args: Vec<String>,
 }
 
-pub const ZSH_BYTES: &[u8] = include_bytes!(std::env!("ZSH_GZIPPED"));
+#[cfg(feature = "bundled-tools")]
+pub const ZSH_BYTES_X86_64: &[u8] = include_bytes!(std::env!("ZSH_GZIPPED_X86_64"));
+#[cfg(feature = "bundled-tools")]
+pub const ZSH_BYTES_AARCH64: &[u8] = include_bytes!(std::env!("ZSH_GZIPPED_AARCH64"));
+
+/// Expected SHA-256 hashes of the gzipped payloads above, baked in at build time so `jj verify`
+/// can detect a tampered binary
+#[cfg(feature = "bundled-tools")]
+pub(crate) const ZSH_SHA256_X86_64: &str = std::env!("ZSH_SHA256_X86_64");
+#[cfg(feature = "bundled-tools")]
+pub(crate) const ZSH_SHA256_AARCH64: &str = std::env!("ZSH_SHA256_AARCH64");
 
 impl super::Command for Zsh {
     fn execute(self) -> eyre::Result<()> {
@@ -120,16 +131,33 @@ impl super::Command for Zsh {
 
         let fd = temp_fd.into_raw_fd();
 
-        let temp_file = unsafe { File::from_raw_fd(fd) };
-        let mut decoder = GzDecoder::new(temp_file);
+        let mut temp_file = unsafe { File::from_raw_fd(fd) };
+
+        #[cfg(feature = "bundled-tools")]
+        {
+            let zsh_bytes = crate::utils::embedded_tool_bytes_for_current_arch(
+                ZSH_BYTES_X86_64,
+                ZSH_BYTES_AARCH64,
+            )?;
+
+            let mut decoder = GzDecoder::new(temp_file);
+            decoder
+                .write_all(zsh_bytes)
+                .context("Could not write all zsh bytes")?;
+            temp_file = decoder
+                .finish()
+                .context("Could not finish writing decompressing zsh")?;
+        }
 
-        decoder
-            .write_all(ZSH_BYTES)
-            .context("Could not write all zsh bytes")?;
+        #[cfg(not(feature = "bundled-tools"))]
+        {
+            let zsh_bytes = crate::utils::fetch_tool_bytes("zsh")?;
+            temp_file
+                .write_all(&zsh_bytes)
+                .context("Could not write all zsh bytes")?;
+        }
 
-        let zsh_file = decoder
-            .finish()
-            .context("Could not finish writing decompressing zsh")?;
+        let zsh_file = temp_file;
 
         let args = str_to_cstr(&[vec!["zsh".to_string()], self.args].concat())?;
 
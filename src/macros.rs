@@ -2,12 +2,12 @@
 /// clap argument parser
 #[macro_export]
 macro_rules! define_commands {
-    ($mname:ident::$cname:ident { $($(#[$($attr:tt)*])* $([$($cfg:tt),+$(,)?])? $cmd:ident$(, $alias:ident)? => $mod:ident::$struct:ident),+$(,)? }) => {
+    ($mname:ident::$cname:ident { $($(#[$($attr:tt)*])* $([$cfg:meta])? $cmd:ident$(, $alias:ident)? => $mod:ident::$struct:ident),+$(,)? }) => {
         mod $mname {
             $(
-                $($(
+                $(
                     #[cfg($cfg)]
-                )*)?
+                )?
                 mod $mod;
             )+
 
@@ -15,9 +15,9 @@ macro_rules! define_commands {
             pub enum $cname {
                 $(
                     $(#[$($attr)*])*
-                    $($(
+                    $(
                         #[cfg($cfg)]
-                    )*)?
+                    )?
                     $(#[command(visible_alias(stringify!($alias)))])?
                     $cmd($mod::$struct)
                 ),+,
@@ -32,9 +32,9 @@ macro_rules! define_commands {
                     match self {
                         $(
                             $(#[$($attr)*])*
-                            $($(
+                            $(
                                 #[cfg($cfg)]
-                            )*)?
+                            )?
                             Self::$cmd(inner) => {
                                 _type_check(&inner);
                                 inner.execute()
@@ -47,9 +47,9 @@ macro_rules! define_commands {
                     match self {
                         $(
                             $(#[$($attr)*])*
-                            $($(
+                            $(
                                 #[cfg($cfg)]
-                            )*)?
+                            )?
                             Self::$cmd(inner) => {
                                 inner.setup_tracing()
                             }
@@ -366,6 +366,68 @@ macro_rules! pcre {
     }};
 }
 
+#[macro_export]
+#[doc(hidden)]
+macro_rules! pcre_format_fancy_regex {
+    ($($tt:tt)*) => {{
+        let (global, other_flags, regex, replace_with) = $crate::pcre_regex!($($tt)*);
+        (
+            global,
+            if other_flags.is_empty() {
+                ::fancy_regex::Regex::new(&regex)
+            } else {
+                ::fancy_regex::Regex::new(&format!("(?{other_flags}){}", &regex))
+            }.expect(&format!("Regex provided is invalid: {}", &regex)),
+            replace_with
+        )
+    }};
+}
+
+/// Same syntax as [`pcre!`], but built on `fancy_regex` instead of `regex`, for the patterns
+/// `pcre!` can't express: lookarounds (`(?=)`, `(?!)`, `(?<=)`, `(?<!)`) and backreferences
+/// (`\1`). Matching is backtracking and not linear-time, so reach for `pcre!` first and only
+/// fall back to this when the pattern genuinely needs one of those constructs.
+///
+/// Only the `qr` (match test) and `m` (captures) verbs are supported; `fancy_regex` captures
+/// aren't `regex` crate captures, so pull in [`crate::utils::regex::FancyCapturesExt`] to get
+/// the same `.extract::<N>()` ergonomics:
+///
+/// ```
+/// # use jj_rs::pcre_fancy;
+/// # use jj_rs::utils::regex::FancyCapturesExt;
+/// assert!(pcre_fancy!("foofoo" =~ qr/r"(foo)\1"/));
+/// assert!(!pcre_fancy!("foobar" =~ qr/r"(foo)\1"/));
+///
+/// let caps = pcre_fancy!("password: hunter2" =~ m/r"(?<=password: )(\w+)"/);
+/// assert_eq!(caps[0].extract::<1>().1, ["hunter2"]);
+/// ```
+#[macro_export]
+macro_rules! pcre_fancy {
+    (($inp:expr) =~ m $($tt:tt)*) => {{
+        let (global, re, _) = $crate::pcre_format_fancy_regex!($($tt)*);
+
+        let captures = re.captures_iter($inp).filter_map(Result::ok);
+        if global {
+            captures.collect::<Vec<_>>()
+        } else {
+            captures.take(1).collect()
+        }
+    }};
+
+    (($inp:expr) =~ qr $($tt:tt)*) => {{
+        let (_, re, _) = $crate::pcre_format_fancy_regex!($($tt)*);
+        re.is_match($inp).unwrap_or(false)
+    }};
+
+    (& $inp:tt $($tt:tt)*) => {{
+        $crate::pcre_fancy!((&$inp) $($tt)*)
+    }};
+
+    ($inp:tt $($tt:tt)*) => {{
+        $crate::pcre_fancy!(($inp) $($tt)*)
+    }};
+}
+
 /// Spawn a tokio runtime on the current thread and run the provided Future to completion
 ///
 /// # Example
@@ -23,10 +23,13 @@ define_commands! {
         // utility commands
         Check, c => check::Check,
         CheckDaemon, cd => check_daemon::CheckDaemon,
+        [unix] Completions => completions::Completions,
         Durkee => durkee::Durkee,
         Serve, s => serve::Serve,
         Get, g => get::Get,
+        Curl, cl => curl::Curl,
         [unix] DownloadShell, ds => download_shell::DownloadShell,
+        [windows] DownloadShell, ds => download_shell_windows::DownloadShell,
         [unix] Elk => elk::Elk,
         [windows] Elk => elk_winbeats::WinBeats,
         [unix] Wazuh, wz => wazuh::Wazuh,
@@ -37,17 +40,38 @@ define_commands! {
         // sysinfo commands
         Stat, st => stat::Stat,
         [windows] Enum, e => enum_windows::Enum,
-        [unix] Enum, e => enum_linux::Enum,
+        [target_os = "linux"] Enum, e => enum_linux::Enum,
+        [target_os = "macos"] Enum, e => enum_macos::Enum,
         Ports, p => ports::Ports,
+        Scan, sc => scan::Scan,
+        Tui => tui::Tui,
 
         // admin commands
         Backup, bu => backup::Backup,
+        Restore, rs => restore::Restore,
         File, f => file::File,
         [unix] Useradd, ua => useradd::Useradd,
+        [windows] Useradd, ua => useradd_windows::Useradd,
         [unix] Firewall, fw => firewall::Firewall,
         [unix] AptInstall, ai => apt::AptInstall,
         [unix] DnfInstall, di => dnf::DnfInstall,
         [unix] Ssh => ssh::Ssh,
+        [unix] PamPolicy, pp => pam_policy::PamPolicy,
+        [unix] Auditd, ad => auditd::Auditd,
+        [unix] SyslogForward, sf => syslog_forward::SyslogForward,
+        [unix] Harden, hd => harden::Harden,
+        [unix] Fim => fim::Fim,
+        [unix] Rootkit, rk => rootkit::Rootkit,
+        [unix] Ir => ir::Ir,
+        [unix] Hunter, hu => hunter::Hunter,
+        [unix] Quarantine, qt => quarantine::Quarantine,
+        [unix] Rotate => rotate::Rotate,
+        [unix] Report => report::Report,
+        Agent => agent::Agent,
+        [unix] Drift => drift::Drift,
+        [unix] Honeypot, hp => honeypot::Honeypot,
+        [unix] Canary => canary::Canary,
+        [unix] Timeline, tl => timeline::Timeline,
         Smtp => smtp::Smtp,
         [unix] ClamAv, cav => clamav_linux::ClamAv,
         [windows] ClamAv, cav => clamav_windows::ClamAv,
@@ -57,6 +81,10 @@ define_commands! {
         [unix] Nft => nft::Nft,
         [unix] Zsh => zsh::Zsh,
         [unix] Busybox, bb => busybox::Busybox,
+        [unix] Socat => socat::Socat,
+        [unix] Unpack, up => unpack::Unpack,
+        [unix] Yara, ya => yara::Yara,
+        Verify, vf => verify::Verify,
     }
 }
 
@@ -74,6 +102,10 @@ define_checks! {
         Smtp, "smtp" => smtp::SmtpTroubleshooter,
         Pop3, "pop3" => pop3::Pop3Troubleshooter,
         Command, "command" => command::CommandTroubleshooter,
+        /// Run a check implemented by an externally built plugin library
+        Plugin, "plugin" => plugin::PluginTroubleshooter,
+        /// Run a sandboxed Rhai script as a check, optionally remediating on failure
+        Script, "script" => script::ScriptTroubleshooter,
     }
 }
 
@@ -0,0 +1,18 @@
+//! Shared report type pushed by `jj agent` and collected by `jj serve --agent`, so a fleet of
+//! hosts can be triaged from one central view instead of logging into each one individually
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One host's periodic check-in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentReport {
+    pub hostname: String,
+    pub timestamp: DateTime<Utc>,
+    /// `jj ports --format json` output for this host
+    pub ports: serde_json::Value,
+    /// `jj enum --no-pager` output for this host, as plain text
+    pub enum_summary: String,
+    /// CPU/memory/disk snapshot for this host
+    pub stat: serde_json::Value,
+}
@@ -19,6 +19,7 @@
 //! # test_busybox().expect("could not run busybox test");
 //! ```
 use std::{
+    cell::OnceCell,
     ffi::{CString, OsStr},
     fs::File,
     io::prelude::*,
@@ -26,6 +27,7 @@ use std::{
         fd::{AsRawFd, FromRawFd, IntoRawFd},
         unix::process::CommandExt,
     },
+    path::PathBuf,
     process::{Command, Stdio},
     str::FromStr,
 };
@@ -37,7 +39,14 @@ use nix::{
     unistd::execv,
 };
 
-const BUSYBOX_BYTES: &[u8] = include_bytes!(std::env!("BUSYBOX_GZIPPED"));
+pub(crate) const BUSYBOX_BYTES_X86_64: &[u8] = include_bytes!(std::env!("BUSYBOX_GZIPPED_X86_64"));
+pub(crate) const BUSYBOX_BYTES_AARCH64: &[u8] =
+    include_bytes!(std::env!("BUSYBOX_GZIPPED_AARCH64"));
+
+/// Expected SHA-256 hashes of the gzipped payloads above, baked in at build time so `jj verify`
+/// can detect a tampered binary
+pub(crate) const BUSYBOX_SHA256_X86_64: &str = std::env!("BUSYBOX_SHA256_X86_64");
+pub(crate) const BUSYBOX_SHA256_AARCH64: &str = std::env!("BUSYBOX_SHA256_AARCH64");
 
 /// Utility function for converting a list of Strings or strs to a list `CStrings`
 pub fn str_to_cstr<R: AsRef<str>>(args: &[R]) -> eyre::Result<Vec<CString>> {
@@ -67,6 +76,9 @@ pub fn str_to_cstr<R: AsRef<str>>(args: &[R]) -> eyre::Result<Vec<CString>> {
 /// ```
 pub struct Busybox {
     busybox_file: File,
+    /// Lazily filled in by [`Busybox::applets`] the first time some caller needs to know what
+    /// this build actually supports
+    applets: OnceCell<Vec<String>>,
 }
 
 impl Busybox {
@@ -81,15 +93,22 @@ impl Busybox {
         let temp_file = unsafe { File::from_raw_fd(fd) };
         let mut decoder = GzDecoder::new(temp_file);
 
+        let busybox_bytes = crate::utils::embedded_tool_bytes_for_current_arch(
+            BUSYBOX_BYTES_X86_64,
+            BUSYBOX_BYTES_AARCH64,
+        )?;
         decoder
-            .write_all(BUSYBOX_BYTES)
+            .write_all(busybox_bytes)
             .context("Could not write all busybox bytes")?;
 
         let busybox_file = decoder
             .finish()
             .context("Could not finish writing decompressing busybox")?;
 
-        Ok(Self { busybox_file })
+        Ok(Self {
+            busybox_file,
+            applets: OnceCell::new(),
+        })
     }
 
     /// Replaces the current process with busybox
@@ -147,6 +166,74 @@ impl Busybox {
         cmd_obj.arg0(cmd);
         cmd_obj
     }
+
+    /// Lists every applet this embedded busybox build actually provides (`busybox --list`),
+    /// caching the result after the first call since it can't change for the lifetime of
+    /// this handle
+    pub fn applets(&self) -> eyre::Result<&[String]> {
+        if let Some(applets) = self.applets.get() {
+            return Ok(applets);
+        }
+
+        let output = self
+            .command("busybox")
+            .arg("--list")
+            .output()
+            .context("Could not list busybox applets")?;
+
+        let applets = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+
+        // Ignore the error if something else already raced us to fill the cell; either way
+        // it holds the same thing, since it's always computed the same way
+        let _ = self.applets.set(applets);
+
+        Ok(self
+            .applets
+            .get()
+            .expect("just populated above, one way or another"))
+    }
+
+    /// Whether this embedded busybox build provides `applet`
+    pub fn supports(&self, applet: &str) -> eyre::Result<bool> {
+        Ok(self.applets()?.iter().any(|a| a == applet))
+    }
+
+    /// Like [`Busybox::command`], but checks first that this build actually provides `applet`.
+    /// If it doesn't (a slim build, or a fork missing an optional applet), falls back to a host
+    /// binary of the same name on `PATH`, with a warning printed so the difference isn't silent.
+    /// Errors out if neither is available, instead of leaving the caller to spawn a command that
+    /// busybox itself will reject with a cryptic "applet not found"
+    pub fn command_checked(&self, applet: &str) -> eyre::Result<Command> {
+        if self.supports(applet)? {
+            return Ok(self.command(applet));
+        }
+
+        let host_path = find_on_path(applet).with_context(|| {
+            format!(
+                "`{applet}` isn't built into this busybox, and no host `{applet}` binary was \
+                 found on PATH either"
+            )
+        })?;
+
+        eprintln!(
+            "warning: busybox doesn't provide `{applet}`, falling back to {}",
+            host_path.display()
+        );
+
+        Ok(Command::new(host_path))
+    }
+}
+
+/// Looks for `name` on `$PATH`, the way a shell would, without shelling out to `which`
+/// (which may itself be missing on a stripped-down host)
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
 }
 
 /// Utility function for easily running a single busybox command
@@ -97,8 +97,8 @@ pub mod check_fns;
 pub use check_fns::*;
 
 #[cfg(unix)]
+use super::command::Cmd;
 use super::download_container::DownloadContainer;
-use super::qx;
 
 /// Represents a value that can be used as a richer parameter type
 /// than just String for checks. This struct provides the
@@ -777,6 +777,10 @@ where
     }
 }
 
+/// How long helper commands spawned by checks (`journalctl`, `which`, ...) are given before
+/// they're killed, so a hung one can't stall the check thread that's waiting on it
+const CHECK_COMMAND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
 /// Utility function to get logs between two timestamps. It returns only a
 /// [`serde_json::value::Value`] to make it easy for inclusion in extra details
 ///
@@ -785,8 +789,11 @@ where
 pub fn get_system_logs(start: DateTime<Utc>, end: DateTime<Utc>) -> serde_json::value::Value {
     use serde_json::value::Value;
 
-    if let Ok((_, path)) = qx("which journalctl 2>/dev/null")
-        && !path.is_empty()
+    if let Ok(out) = Cmd::new("which")
+        .arg("journalctl")
+        .timeout(CHECK_COMMAND_TIMEOUT)
+        .output()
+        && !out.stdout.is_empty()
     {
         return match get_logs_systemd(start, end) {
             Ok(v) => v.into_iter().map(Value::String).collect::<Value>(),
@@ -803,17 +810,24 @@ fn get_logs_systemd(start: DateTime<Utc>, end: DateTime<Utc>) -> eyre::Result<Ve
 
     let format = "%Y-%m-%d %H:%M:%S";
 
-    qx(&format!(
-        "journalctl --no-pager '--since={}' '--until={}' --utc",
-        start.format(format),
-        // journalctl will go up to but not including the time, and has second precision
-        // This includes the final second of logs, or all the logs if the start and end
-        //   datetimes are the same (down to the second)
-        end.checked_add_signed(chrono::TimeDelta::seconds(1))
-            .unwrap_or(end)
-            .format(format)
-    ))
-    .map(|(_, o)| o.lines().map(String::from).collect())
+    Cmd::new("journalctl")
+        .args([
+            "--no-pager".to_string(),
+            format!("--since={}", start.format(format)),
+            // journalctl will go up to but not including the time, and has second precision
+            // This includes the final second of logs, or all the logs if the start and end
+            //   datetimes are the same (down to the second)
+            format!(
+                "--until={}",
+                end.checked_add_signed(chrono::TimeDelta::seconds(1))
+                    .unwrap_or(end)
+                    .format(format)
+            ),
+            "--utc".to_string(),
+        ])
+        .timeout(CHECK_COMMAND_TIMEOUT)
+        .output()
+        .map(|out| out.stdout.lines().map(String::from).collect())
 }
 
 /// Utility function to conditionally run a task inside the download container, returning the
@@ -837,7 +851,7 @@ where
 
     #[cfg(unix)]
     return if !avoid_download_container
-        && let Ok(container) = DownloadContainer::new(None, sneaky_ip)
+        && let Ok(container) = DownloadContainer::new(None, sneaky_ip, None, None)
     {
         let wan_ip = container.wan_ip();
         let check_result = container
@@ -258,7 +258,7 @@ impl ImmediateTcpdumpCheck {
 
         const SYNC_SIZE: usize = std::mem::size_of::<Sync>();
 
-        let container = DownloadContainer::new(None, None)
+        let container = DownloadContainer::new(None, None, None, None)
             .context("Could not create download container for immediate tcpdump check")?;
 
         let (child, mut capture, sync) = unsafe {
@@ -407,6 +407,13 @@ impl CheckStep<'_> for ImmediateTcpdumpCheck {
             ));
         }
 
+        if !crate::utils::privilege::is_root() {
+            return Ok(CheckResult::not_run(
+                "Capturing packets with tcpdump requires root",
+                serde_json::json!(null),
+            ));
+        }
+
         Ok(tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()
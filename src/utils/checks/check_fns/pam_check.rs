@@ -1,7 +1,8 @@
-use std::{io::prelude::*, path::Path, process::Stdio};
+use std::path::Path;
 
-use crate::utils::checks::{
-    CheckResult, CheckStep, CheckValue, TroubleshooterRunner, get_system_logs,
+use crate::utils::{
+    checks::{CheckResult, CheckStep, CheckValue, TroubleshooterRunner, get_system_logs},
+    pam::pam_login,
 };
 
 struct PamCheck {
@@ -31,59 +32,28 @@ impl CheckStep<'_> for PamCheck {
             ));
         }
 
-        let pamtester = crate::utils::pamtester::Pamtester::new()?;
-
-        let mut cmd = pamtester.command();
-
-        std::thread::sleep(std::time::Duration::from_secs(1));
-
         let start = chrono::Utc::now();
 
-        if let Some(service) = &self.service {
-            cmd.args(["-I", &format!("service={service}")]);
-        }
-        cmd.args([
-            "-v",
-            "login",
-            &*self.username,
-            "authenticate",
-            "open_session",
-            "close_session",
-        ]);
-        let (mut reader, writer) = std::io::pipe()?;
-        cmd.stdin(Stdio::piped());
-        cmd.stdout(writer.try_clone()?);
-        cmd.stderr(writer);
-
-        let mut proc = cmd.spawn()?;
-
         let password = self.password.resolve_prompt(
             tr,
             format!("What is the password for the {} user: ", &self.username),
         )?;
 
-        if let Some(stdin) = &mut proc.stdin {
-            writeln!(stdin, "{password}")?;
-        }
-
-        // Read the example code for pipe:
-        // https://doc.rust-lang.org/stable/std/io/fn.pipe.html
-        drop(cmd);
-        let mut stdout = String::new();
-        reader.read_to_string(&mut stdout)?;
-        let success = proc.wait()?.success();
+        let service = self.service.as_deref().unwrap_or("login");
+        let steps = pam_login(service, &self.username, &password)?;
 
         let end = chrono::Utc::now();
 
         let logs = get_system_logs(start, end);
 
         let service_config = self.get_service_config();
+        let success = !steps.is_empty() && steps.iter().all(|step| step.success);
 
         if success {
             Ok(CheckResult::succeed(
                 "Successfully signed in as user",
                 serde_json::json!({
-                    "pam_test_output": stdout.split('\n').collect::<serde_json::Value>(),
+                    "pam_steps": steps,
                     "system_logs": logs,
                     "service_config": service_config
                 }),
@@ -92,7 +62,7 @@ impl CheckStep<'_> for PamCheck {
             Ok(CheckResult::warn(
                 "Failed to sign in as user",
                 serde_json::json!({
-                    "pam_test_output": stdout.split('\n').collect::<serde_json::Value>(),
+                    "pam_steps": steps,
                     "system_logs": logs,
                     "service_config": service_config
                 }),
@@ -153,6 +153,13 @@ impl CheckStep<'_> for PassiveTcpdumpCheck {
             ));
         }
 
+        if !crate::utils::privilege::is_root() {
+            return Ok(CheckResult::not_run(
+                "Capturing packets with tcpdump requires root",
+                serde_json::json!(null),
+            ));
+        }
+
         let mut capture = self.make_capture()?;
         let (source_ip, source_port, start, proto) = self.get_first_packet(&mut capture)?;
 
@@ -1,16 +1,21 @@
 use std::convert::Into;
+#[cfg(unix)]
+use std::time::Duration;
 
-use crate::utils::{
-    checks::{CheckResult, CheckResultType, CheckStep, TroubleshooterRunner},
-    qx,
-};
-
+use crate::utils::checks::{CheckResult, CheckResultType, CheckStep, TroubleshooterRunner};
+#[cfg(unix)]
+use crate::utils::command::Cmd;
 #[cfg(unix)]
 use crate::utils::systemd::{get_service_info, is_service_active};
 
 #[cfg(unix)]
 use super::check_fn;
 
+/// How long the `which`/`rc-service` probes in this file are given before they're killed, so a
+/// hung one can't stall the check thread waiting on it
+#[cfg(unix)]
+const SERVICE_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[cfg(unix)]
 struct SystemdServiceCheck {
     service_names: Vec<String>,
@@ -23,7 +28,14 @@ impl CheckStep<'_> for SystemdServiceCheck {
     }
 
     fn run_check(&self, _tr: &mut dyn TroubleshooterRunner) -> eyre::Result<CheckResult> {
-        if qx("which systemctl 2>/dev/null")?.1.trim().is_empty() {
+        if Cmd::new("which")
+            .arg("systemctl")
+            .timeout(SERVICE_PROBE_TIMEOUT)
+            .output()?
+            .stdout
+            .trim()
+            .is_empty()
+        {
             return Ok(CheckResult::not_run(
                 "`systemctl` not found on host",
                 serde_json::json!(null),
@@ -107,7 +119,14 @@ impl CheckStep<'_> for OpenrcServiceCheck {
     }
 
     fn run_check(&self, _tr: &mut dyn TroubleshooterRunner) -> eyre::Result<CheckResult> {
-        if qx("which rc-service 2>/dev/null")?.1.trim().is_empty() {
+        if Cmd::new("which")
+            .arg("rc-service")
+            .timeout(SERVICE_PROBE_TIMEOUT)
+            .output()?
+            .stdout
+            .trim()
+            .is_empty()
+        {
             return Ok(CheckResult::not_run(
                 "`rc-service` not found on host",
                 serde_json::json!(null),
@@ -124,8 +143,12 @@ impl CheckStep<'_> for OpenrcServiceCheck {
         for name in &self.service_names {
             // We ignore errors here because we want to check all services
             #[allow(clippy::collapsible_if)]
-            if let Ok((_, res)) = qx(&format!("rc-service {name} status")) {
-                if res.contains("status: started") {
+            if let Ok(out) = Cmd::new("rc-service")
+                .args([name.as_str(), "status"])
+                .timeout(SERVICE_PROBE_TIMEOUT)
+                .output()
+            {
+                if out.stdout.contains("status: started") {
                     return Ok(CheckResult::succeed(
                         format!("OpenRC service '{name}' is active"),
                         serde_json::json!({
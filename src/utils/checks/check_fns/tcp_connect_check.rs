@@ -55,7 +55,7 @@ impl CheckStep<'_> for TcpConnectCheck {
         } else if self.addr.ip().is_loopback() {
             use crate::utils::checks::CheckResultType as CRT;
 
-            let cont = DownloadContainer::new(None, self.download_container_ip)
+            let cont = DownloadContainer::new(None, self.download_container_ip, None, None)
                 .context("Could not create download container for TCP check");
             let client1 = cont.and_then(|cont| {
                 cont.run(|| {
@@ -135,7 +135,7 @@ impl CheckStep<'_> for TcpConnectCheck {
                 result_type: result1 | result2,
             })
         } else {
-            let cont = match DownloadContainer::new(None, self.download_container_ip) {
+            let cont = match DownloadContainer::new(None, self.download_container_ip, None, None) {
                 Ok(v) => v,
                 Err(e) => {
                     return Ok(CheckResult::warn(
@@ -0,0 +1,128 @@
+//! A builder on top of [`tokio::process::Command`] for the cases [`super::qx`]/[`super::system`]
+//! don't cover: explicit argv execution that skips `sh -c`/`cmd /c` entirely, a kill-on-expiry
+//! timeout so a hung child can't stall the caller forever, control over the inherited
+//! environment, and stderr that's actually captured instead of discarded
+
+use std::{process::ExitStatus, time::Duration};
+
+use eyre::Context;
+
+/// The captured result of running a [`Cmd`]
+pub struct CmdOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Builder for running an external command directly (no shell involved), optionally bounded by
+/// a timeout and with control over the environment it inherits
+pub struct Cmd {
+    program: String,
+    args: Vec<String>,
+    timeout: Option<Duration>,
+    clear_env: bool,
+    envs: Vec<(String, String)>,
+}
+
+impl Cmd {
+    /// Starts building a command that runs `program` with the given argv, without passing
+    /// either through a shell
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            timeout: None,
+            clear_env: false,
+            envs: Vec::new(),
+        }
+    }
+
+    /// Appends a single argument
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Appends several arguments at once
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Kills the child and returns an error if it hasn't exited after `duration`
+    pub fn timeout(mut self, duration: Duration) -> Self {
+        self.timeout = Some(duration);
+        self
+    }
+
+    /// Scrubs the environment the child would otherwise inherit from this process; only
+    /// variables added afterwards via [`Cmd::env`] will be visible to it
+    pub fn env_clear(mut self) -> Self {
+        self.clear_env = true;
+        self
+    }
+
+    /// Sets an environment variable for the child, in addition to (or, after [`Cmd::env_clear`],
+    /// instead of) the inherited environment
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Runs the command to completion, capturing stdout and stderr separately. If a timeout was
+    /// set and elapses first, the child is killed and this returns an error instead
+    pub fn output(self) -> eyre::Result<CmdOutput> {
+        let Self {
+            program,
+            args,
+            timeout,
+            clear_env,
+            envs,
+        } = self;
+
+        let mut command = tokio::process::Command::new(&program);
+        command
+            .args(&args)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+
+        if clear_env {
+            command.env_clear();
+        }
+        command.envs(envs);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("Could not start a runtime to run the command on")?;
+
+        let output = rt.block_on(async {
+            let wait = async {
+                command
+                    .spawn()
+                    .with_context(|| format!("Could not spawn {program}"))?
+                    .wait_with_output()
+                    .await
+                    .with_context(|| format!("Could not wait for {program} to finish"))
+            };
+
+            match timeout {
+                Some(duration) => tokio::time::timeout(duration, wait)
+                    .await
+                    .map_err(|_| eyre::eyre!("{program} did not finish within {duration:?}"))?,
+                None => wait.await,
+            }
+        })?;
+
+        Ok(CmdOutput {
+            status: output.status,
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    }
+}
@@ -1,8 +1,17 @@
 //! Utilities for summarizing container runtimes like Docker, Podman, and LXC
 
-use crate::utils::qx;
+use std::time::Duration;
+
+use crate::utils::{
+    parallel::{TaskOutcome, run_bounded},
+    qx,
+};
 use walkdir::WalkDir;
 
+/// How long each runtime/namespace probe in [`get_containers`] is given to respond before it's
+/// considered unresponsive, so a stuck daemon socket doesn't stall the rest of the checks
+const CONTAINER_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Unified structure for any container found on the system
 pub struct Container {
     pub runtime: String, // e.g., "Docker", "Podman", "Containerd (default)"
@@ -13,12 +22,53 @@ pub struct Container {
     pub namespace: Option<String>, // Specifically for containerd namespaces
 }
 
-/// Discovers running containers across Docker, Podman, LXC, and Containerd
+/// Discovers running containers across Docker, Podman, LXC, and Containerd. The runtime and
+/// namespace probes run concurrently (bounded by [`run_bounded`]) so one runtime with an
+/// unresponsive daemon socket doesn't hold up the others
 pub fn get_containers() -> Vec<Container> {
     let mut results = Vec::new();
 
-    // --- Check Docker ---
-    // Format: ID|Image|Status|Names
+    let checks: Vec<Box<dyn FnOnce() -> Vec<Container> + Send>> = vec![
+        Box::new(check_docker),
+        Box::new(check_podman),
+        Box::new(check_lxc),
+    ];
+    let concurrency = checks.len();
+
+    for outcome in run_bounded(checks, concurrency, Some(CONTAINER_CHECK_TIMEOUT)) {
+        match outcome {
+            TaskOutcome::Finished(containers) => results.extend(containers),
+            TaskOutcome::TimedOut => eprintln!("A container runtime check did not respond in time"),
+            TaskOutcome::Panicked => eprintln!("A container runtime check panicked"),
+        }
+    }
+
+    let namespaces = check_containerd_namespaces();
+    let ns_checks: Vec<Box<dyn FnOnce() -> Vec<Container> + Send>> = namespaces
+        .into_iter()
+        .map(|ns| -> Box<dyn FnOnce() -> Vec<Container> + Send> {
+            Box::new(move || check_containerd_namespace(&ns))
+        })
+        .collect();
+
+    let concurrency = ns_checks.len().max(1);
+    for outcome in run_bounded(ns_checks, concurrency, Some(CONTAINER_CHECK_TIMEOUT)) {
+        match outcome {
+            TaskOutcome::Finished(containers) => results.extend(containers),
+            TaskOutcome::TimedOut => {
+                eprintln!("A containerd namespace listing did not respond in time")
+            }
+            TaskOutcome::Panicked => eprintln!("A containerd namespace listing panicked"),
+        }
+    }
+
+    results
+}
+
+/// Format: ID|Image|Status|Names
+fn check_docker() -> Vec<Container> {
+    let mut results = Vec::new();
+
     match qx("docker ps --format '{{.ID}}|{{.Image}}|{{.Status}}|{{.Names}}' --no-trunc") {
         Ok((status, output)) if status.success() => {
             for line in output.lines().filter(|l| !l.trim().is_empty()) {
@@ -42,7 +92,13 @@ pub fn get_containers() -> Vec<Container> {
         Err(e) => eprintln!("Failed to run docker check: {e}"),
     }
 
-    // --- Check Podman ---
+    results
+}
+
+/// Format: ID|Image|Status|Names
+fn check_podman() -> Vec<Container> {
+    let mut results = Vec::new();
+
     match qx("podman ps --format '{{.ID}}|{{.Image}}|{{.Status}}|{{.Names}}' --no-trunc") {
         Ok((status, output)) if status.success() => {
             for line in output.lines().filter(|l| !l.trim().is_empty()) {
@@ -64,8 +120,13 @@ pub fn get_containers() -> Vec<Container> {
         Err(e) => eprintln!("Failed to run podman check: {e}"),
     }
 
-    // --- Check LXC ---
-    // Format: NAME,STATE,IPV4
+    results
+}
+
+/// Format: NAME,STATE,IPV4
+fn check_lxc() -> Vec<Container> {
+    let mut results = Vec::new();
+
     match qx("lxc list --format csv -c n,s,4") {
         Ok((status, output)) if status.success() => {
             for line in output.lines().filter(|l| !l.trim().is_empty()) {
@@ -87,9 +148,12 @@ pub fn get_containers() -> Vec<Container> {
         Err(e) => eprintln!("Failed to run LXC check: {e}"),
     }
 
-    // --- Check Containerd (ctr) ---
-    // 1. Get Namespaces
+    results
+}
+
+fn check_containerd_namespaces() -> Vec<String> {
     let mut namespaces = Vec::new();
+
     match qx("ctr namespaces list -q") {
         Ok((status, output)) if status.success() => {
             for line in output.lines().filter(|l| !l.trim().is_empty()) {
@@ -103,36 +167,57 @@ pub fn get_containers() -> Vec<Container> {
         Err(e) => eprintln!("Failed to run containerd check: {e}"),
     }
 
-    // 2. Iterate Namespaces
-    for ns in namespaces {
-        match qx(&format!("ctr -n {ns} containers ls")) {
-            Ok((status, output)) if status.success() => {
-                for line in output.lines().skip(1).filter(|l| !l.trim().is_empty()) {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 2 {
-                        results.push(Container {
-                            runtime: "Containerd".to_string(),
-                            id: parts[0].to_string(),      // Container ID
-                            image: parts[1].to_string(),   // Image Ref
-                            status: "Unknown".to_string(), // 'ctr c ls' doesn't always show up/down status clearly without 'tasks'
-                            name: parts[0].to_string(),
-                            namespace: Some(ns.clone()),
-                        });
-                    }
+    namespaces
+}
+
+fn check_containerd_namespace(ns: &str) -> Vec<Container> {
+    let mut results = Vec::new();
+
+    match qx(&format!("ctr -n {ns} containers ls")) {
+        Ok((status, output)) if status.success() => {
+            for line in output.lines().skip(1).filter(|l| !l.trim().is_empty()) {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() >= 2 {
+                    results.push(Container {
+                        runtime: "Containerd".to_string(),
+                        id: parts[0].to_string(),      // Container ID
+                        image: parts[1].to_string(),   // Image Ref
+                        status: "Unknown".to_string(), // 'ctr c ls' doesn't always show up/down status clearly without 'tasks'
+                        name: parts[0].to_string(),
+                        namespace: Some(ns.to_string()),
+                    });
                 }
             }
-            Ok((status, err_out)) => eprintln!(
-                "Containerd list for ns {ns} failed ({}): {}",
-                status,
-                err_out.trim()
-            ),
-            Err(e) => eprintln!("Failed to run containerd list for ns {ns}: {e}"),
         }
+        Ok((status, err_out)) => eprintln!(
+            "Containerd list for ns {ns} failed ({}): {}",
+            status,
+            err_out.trim()
+        ),
+        Err(e) => eprintln!("Failed to run containerd list for ns {ns}: {e}"),
     }
 
     results
 }
 
+/// Pull the container ID embedded in a cgroup path read from `/proc/<pid>/cgroup`, e.g.
+/// `.../docker/<id>`, `.../docker-<id>.scope`, or the bare `<id>` cgroup v2 paths used by
+/// containerd/CRI
+fn container_id_from_cgroup(cgroup: &str) -> Option<String> {
+    let re = regex::Regex::new(r"[0-9a-f]{12,64}").ok()?;
+    re.find(cgroup).map(|m| m.as_str().to_string())
+}
+
+/// Given a raw cgroup path for a process, try to match it against a list of known containers
+/// (as returned by [`get_containers`]) so callers can show a container name/image instead of
+/// a raw cgroup path
+pub fn resolve_container<'a>(cgroup: &str, containers: &'a [Container]) -> Option<&'a Container> {
+    let id = container_id_from_cgroup(cgroup)?;
+    containers
+        .iter()
+        .find(|c| c.id.starts_with(&id) || id.starts_with(&c.id))
+}
+
 /// Discovers docker-compose.yml or compose.yaml files
 pub fn find_compose_files() -> Vec<String> {
     let mut found = Vec::new();
@@ -11,7 +11,7 @@
 //! ```no_run
 //! # use jj_rs::utils::{busybox, download_container::DownloadContainer};
 //! # fn test_download_container() -> eyre::Result<()> {
-//! let container = DownloadContainer::new(Some("dlshell".to_string()), None)?;
+//! let container = DownloadContainer::new(Some("dlshell".to_string()), None, None, None)?;
 //! let container_ip = container.run(|| {
 //!     busybox::execute(&["ip", "addr"])
 //! })??;
@@ -71,6 +71,8 @@ pub struct DownloadContainer {
     wan_ip: Ipv4Addr,
     #[allow(dead_code)]
     lan_ip: Ipv4Addr,
+    proxy: Option<String>,
+    doh_pid: Option<Pid>,
 }
 
 impl DownloadContainer {
@@ -92,7 +94,23 @@ impl DownloadContainer {
     ///
     /// If sneaky_ip is not set but the environment variable SNEAKY_IP is available, that variable
     /// will be used
-    pub fn new(name: Option<String>, sneaky_ip: Option<Ipv4Addr>) -> eyre::Result<Self> {
+    ///
+    /// `proxy` is a URL such as `http://10.0.0.5:8080` or `socks5://10.0.0.5:1080`. It doesn't
+    /// change how this container routes traffic (it's still NAT'd out through the host's default
+    /// route exactly as without a proxy); it's only recorded so [`DownloadContainer::proxy_env`]
+    /// can tell callers which environment variables to export, for environments where direct
+    /// egress is blocked and only that proxy is permitted
+    ///
+    /// `doh` is a DNS-over-HTTPS endpoint URL such as `https://cloudflare-dns.com/dns-query`. When
+    /// set, name resolution inside the container is forwarded over HTTPS to that endpoint instead
+    /// of plaintext UDP to 1.1.1.1, for environments where the host's own DNS is firewalled or
+    /// poisoned
+    pub fn new(
+        name: Option<String>,
+        sneaky_ip: Option<Ipv4Addr>,
+        proxy: Option<String>,
+        doh: Option<String>,
+    ) -> eyre::Result<Self> {
         if !geteuid().is_root() {
             bail!("You must be root to make use of download container capabilities");
         }
@@ -139,7 +157,7 @@ impl DownloadContainer {
         ])
         .context("Could not add IP address to WAN interface")?;
 
-        let child = get_namespace(&bb, wan_ip)?;
+        let (child, doh_pid) = get_namespace(&bb, wan_ip, doh)?;
 
         let original_net_ns = open(
             &*format!("/proc/{}/ns/net", getpid()),
@@ -303,6 +321,8 @@ impl DownloadContainer {
             nft,
             wan_ip,
             lan_ip,
+            proxy,
+            doh_pid,
         })
     }
 
@@ -311,7 +331,7 @@ impl DownloadContainer {
     /// ```no_run
     /// # use jj_rs::utils::{busybox, download_container::DownloadContainer};
     /// # fn test_download_container() -> eyre::Result<()> {
-    /// let container = DownloadContainer::new(Some("dlshell".to_string()), None)?;
+    /// let container = DownloadContainer::new(Some("dlshell".to_string()), None, None, None)?;
     /// let container_ip = container.run(|| {
     ///     busybox::execute(&["ip", "addr"])
     /// })??;
@@ -370,10 +390,39 @@ impl DownloadContainer {
     pub fn wan_ip(&self) -> Ipv4Addr {
         self.wan_ip
     }
+
+    /// The `*_proxy` environment variables to export for any process spawned in this container,
+    /// so HTTP/SOCKS-aware tools (wget, curl, apt, ...) route through the configured `--proxy`
+    /// instead of trying direct egress. Empty if no proxy was configured
+    pub fn proxy_env(&self) -> Vec<(&'static str, String)> {
+        let Some(proxy) = &self.proxy else {
+            return Vec::new();
+        };
+
+        vec![
+            ("http_proxy", proxy.clone()),
+            ("https_proxy", proxy.clone()),
+            ("HTTP_PROXY", proxy.clone()),
+            ("HTTPS_PROXY", proxy.clone()),
+            ("all_proxy", proxy.clone()),
+            ("ALL_PROXY", proxy.clone()),
+            ("no_proxy", "localhost,127.0.0.1".to_string()),
+        ]
+    }
 }
 
 impl Drop for DownloadContainer {
     fn drop(&mut self) {
+        if let Some(doh_pid) = self.doh_pid {
+            if let Err(e) = kill(doh_pid, SIGTERM) {
+                eprintln!("Could not kill DNS-over-HTTPS forwarder with pid {doh_pid}: {e}");
+            } else if let Err(e) = waitpid(doh_pid, None) {
+                eprintln!(
+                    "Could not wait for DNS-over-HTTPS forwarder with pid {doh_pid} to die: {e}"
+                );
+            }
+        }
+
         if let Err(e) = kill(self.child, SIGTERM) {
             return eprintln!(
                 "Could not kill download container child with pid {}: {}",
@@ -405,7 +454,15 @@ impl Drop for DownloadContainer {
 /// This will also create a new mount namespace that bind mounts a new file over
 /// /etc/resolv.conf to enable outbound, external DNS that doesn't depend on the domain
 /// controller
-fn get_namespace(bb: &Busybox, wan_ip: Ipv4Addr) -> eyre::Result<Pid> {
+///
+/// If `doh` is set, resolv.conf instead points at a local stub resolver that forwards queries
+/// over HTTPS to that endpoint; the stub runs in a second, dedicated child whose pid is returned
+/// alongside the namespace-holder pid so the caller can tear it down too
+fn get_namespace(
+    bb: &Busybox,
+    wan_ip: Ipv4Addr,
+    doh: Option<String>,
+) -> eyre::Result<(Pid, Option<Pid>)> {
     // Semaphores are nasty but one of the simplest ways to communicate across
     // processes. We have to wait for the process to finish initializing, hence
     // shared memory and a shared semaphore
@@ -414,17 +471,24 @@ fn get_namespace(bb: &Busybox, wan_ip: Ipv4Addr) -> eyre::Result<Pid> {
     struct Sync {
         semaphore: sem_t,
         err: eyre::Result<()>,
+        // 0 means no DoH forwarder was started
+        doh_pid: i32,
     }
 
     const SYNC_SIZE: usize = std::mem::size_of::<Sync>();
 
-    let setup_child = || -> eyre::Result<()> {
+    let setup_child = move || -> eyre::Result<Option<Pid>> {
         nix::sched::unshare(CloneFlags::CLONE_NEWNET | CloneFlags::CLONE_NEWNS)
             .context("Could not unshare as child")?;
 
         let resolve_path_raw = bb.execute(&["mktemp"])?;
         let resolve_path = resolve_path_raw.trim();
-        std::fs::write(resolve_path, "nameserver 1.1.1.1\n")?;
+        let nameserver = if doh.is_some() {
+            "127.0.0.1"
+        } else {
+            "1.1.1.1"
+        };
+        std::fs::write(resolve_path, format!("nameserver {nameserver}\n"))?;
         std::fs::set_permissions(resolve_path, PermissionsExt::from_mode(0o555))?;
 
         let old_nsswitch_contents = std::fs::read_to_string("/etc/nsswitch.conf")?;
@@ -482,7 +546,21 @@ fn get_namespace(bb: &Busybox, wan_ip: Ipv4Addr) -> eyre::Result<Pid> {
             None::<&str>,
         )?;
 
-        Ok(())
+        let doh_pid = if let Some(doh_url) = doh {
+            match unsafe { fork()? } {
+                ForkResult::Child => {
+                    if let Err(e) = run_doh_forwarder(&doh_url) {
+                        eprintln!("DNS-over-HTTPS forwarder exited: {e}");
+                    }
+                    std::process::exit(1);
+                }
+                ForkResult::Parent { child } => Some(child),
+            }
+        } else {
+            None
+        };
+
+        Ok(doh_pid)
     };
 
     unsafe {
@@ -501,7 +579,16 @@ fn get_namespace(bb: &Busybox, wan_ip: Ipv4Addr) -> eyre::Result<Pid> {
 
         match fork()? {
             ForkResult::Child => {
-                (*sync).err = setup_child();
+                match setup_child() {
+                    Ok(doh_pid) => {
+                        (*sync).doh_pid = doh_pid.map_or(0, |p| p.as_raw());
+                        (*sync).err = Ok(());
+                    }
+                    Err(e) => {
+                        (*sync).doh_pid = 0;
+                        (*sync).err = Err(e);
+                    }
+                }
 
                 libc::msync(sync.cast(), SYNC_SIZE, libc::MS_SYNC);
                 libc::sem_post(semaphore);
@@ -515,12 +602,136 @@ fn get_namespace(bb: &Busybox, wan_ip: Ipv4Addr) -> eyre::Result<Pid> {
                 libc::sem_wait(semaphore);
                 libc::sem_destroy(semaphore);
 
-                std::ptr::read(sync).err?;
+                let synced = std::ptr::read(sync);
+                synced.err?;
 
                 libc::munmap(semaphore.cast(), SYNC_SIZE);
 
-                Ok(child)
+                let doh_pid = (synced.doh_pid != 0).then(|| Pid::from_raw(synced.doh_pid));
+
+                Ok((child, doh_pid))
+            }
+        }
+    }
+}
+
+/// Resolves `host` to an IPv4 address using a single plaintext query straight to 1.1.1.1,
+/// bypassing the system resolver entirely. The DoH forwarder needs this to reach its own
+/// endpoint without depending on DNS resolution (which, once resolv.conf is pointed at the
+/// forwarder itself, would otherwise be circular)
+fn bootstrap_resolve(host: &str) -> eyre::Result<Ipv4Addr> {
+    if let Ok(ip) = host.parse::<Ipv4Addr>() {
+        return Ok(ip);
+    }
+
+    let mut query = vec![
+        0xAA, 0xAA, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+    for label in host.split('.') {
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0x00);
+    query.extend_from_slice(&[0x00, 0x01, 0x00, 0x01]); // QTYPE A, QCLASS IN
+
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0")
+        .context("Could not bind bootstrap resolver socket")?;
+    socket.set_read_timeout(Some(std::time::Duration::from_secs(5)))?;
+    socket
+        .connect("1.1.1.1:53")
+        .context("Could not connect bootstrap resolver socket")?;
+    socket
+        .send(&query)
+        .context("Could not send bootstrap DNS query")?;
+
+    let mut response = [0u8; 512];
+    let len = socket
+        .recv(&mut response)
+        .context("Could not read bootstrap DNS response")?;
+    let response = &response[..len];
+
+    let answers = u16::from_be_bytes([response[6], response[7]]);
+
+    let mut pos = 12;
+    while response.get(pos).is_some_and(|&b| b != 0) {
+        pos += response[pos] as usize + 1;
+    }
+    pos += 1 + 4; // null label terminator, then QTYPE and QCLASS
+
+    for _ in 0..answers {
+        // Every answer name we expect here is a pointer back into the question, so it's
+        // always exactly two bytes
+        pos += 2;
+
+        let rtype = u16::from_be_bytes([response[pos], response[pos + 1]]);
+        let rdlength = u16::from_be_bytes([response[pos + 8], response[pos + 9]]) as usize;
+        let rdata = pos + 10;
+
+        if rtype == 1 && rdlength == 4 {
+            return Ok(Ipv4Addr::new(
+                response[rdata],
+                response[rdata + 1],
+                response[rdata + 2],
+                response[rdata + 3],
+            ));
+        }
+
+        pos = rdata + rdlength;
+    }
+
+    bail!("No A record found for {host} while bootstrapping the DoH resolver")
+}
+
+/// Runs forever, forwarding plaintext DNS queries received on 127.0.0.1:53 to a DNS-over-HTTPS
+/// endpoint (RFC 8484) and relaying the raw response back to the querying client unmodified. A
+/// single failed query is logged and skipped rather than killing the whole forwarder
+fn run_doh_forwarder(doh_url: &str) -> eyre::Result<()> {
+    let socket = std::net::UdpSocket::bind("127.0.0.1:53")
+        .context("Could not bind DNS-over-HTTPS forwarder to 127.0.0.1:53")?;
+
+    let parsed = reqwest::Url::parse(doh_url).context("Invalid DNS-over-HTTPS endpoint URL")?;
+    let doh_host = parsed
+        .host_str()
+        .ok_or_else(|| eyre!("DNS-over-HTTPS endpoint URL has no host"))?
+        .to_string();
+    let doh_port = parsed.port_or_known_default().unwrap_or(443);
+
+    // The container's own networking isn't up yet when this forwarder starts, so keep retrying
+    // the bootstrap lookup until it succeeds instead of giving up
+    let client = loop {
+        match bootstrap_resolve(&doh_host) {
+            Ok(ip) => {
+                break reqwest::blocking::Client::builder()
+                    .resolve(&doh_host, (ip, doh_port).into())
+                    .build()
+                    .context("Could not build DNS-over-HTTPS client")?;
+            }
+            Err(e) => {
+                eprintln!("Could not resolve DNS-over-HTTPS endpoint {doh_host} yet: {e}");
+                std::thread::sleep(std::time::Duration::from_secs(2));
+            }
+        }
+    };
+
+    let mut buf = [0u8; 4096];
+    loop {
+        let Ok((len, client_addr)) = socket.recv_from(&mut buf) else {
+            continue;
+        };
+
+        let response = client
+            .post(doh_url)
+            .header("content-type", "application/dns-message")
+            .header("accept", "application/dns-message")
+            .body(buf[..len].to_vec())
+            .send()
+            .and_then(reqwest::blocking::Response::bytes);
+
+        match response {
+            Ok(body) => {
+                let _ = socket.send_to(&body, client_addr);
             }
+            Err(e) => eprintln!("DNS-over-HTTPS query to {doh_url} failed: {e}"),
         }
     }
 }
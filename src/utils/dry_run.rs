@@ -0,0 +1,35 @@
+//! Shared `--dry-run` reporting convention, so each mutating command doesn't reinvent its own
+//! "would do X" vs "did X" printing
+
+use colored::Colorize;
+
+/// Runs `action` unless `dry_run` is set, printing `message` either way: `would {message}` in
+/// yellow if it was skipped, or `{message}` in red once `action` has actually run
+pub fn step<T>(
+    dry_run: bool,
+    message: impl std::fmt::Display,
+    action: impl FnOnce() -> eyre::Result<T>,
+) -> eyre::Result<Option<T>> {
+    if dry_run {
+        println!("{} {message}", "would".yellow());
+        return Ok(None);
+    }
+
+    let result = action()?;
+    println!("{message}", message = message.to_string().red());
+    Ok(Some(result))
+}
+
+/// Prints a dry-run-aware summary line after a batch of [`step`] calls, e.g.
+/// `--- 3 account(s) would be locked` vs `--- 3 account(s) locked`
+pub fn summary(dry_run: bool, would: impl std::fmt::Display, done: impl std::fmt::Display) {
+    println!(
+        "{} {}",
+        "---".blue(),
+        if dry_run {
+            would.to_string()
+        } else {
+            done.to_string()
+        }
+    );
+}
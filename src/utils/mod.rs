@@ -7,30 +7,44 @@
 //! those modules can relax and use the tools from category 2
 use eyre::Context;
 
-use std::{fs::OpenOptions, path::Path, process::ExitStatus};
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+    process::ExitStatus,
+};
 
+pub mod agent;
 #[cfg(unix)]
 pub mod busybox;
 pub mod checks;
 pub mod clap;
+pub mod command;
 #[cfg(unix)]
 pub mod containers;
 pub mod curl;
 #[cfg(unix)]
 pub mod download_container;
+pub mod dry_run;
 pub mod logs;
 pub mod modsecurity;
 #[cfg(unix)]
 pub mod nft;
 pub mod os_version;
+pub mod output;
 #[cfg(unix)]
 pub mod packages;
 pub mod pager;
 #[cfg(unix)]
+pub mod pam;
+#[cfg(unix)]
 pub mod pamtester;
+pub mod parallel;
 #[cfg(unix)]
 pub mod passwd;
+pub mod plugin;
 pub mod ports;
+#[cfg(unix)]
+pub mod privilege;
 #[allow(dead_code)]
 pub mod regex;
 #[cfg(unix)]
@@ -38,10 +52,82 @@ pub mod scheduling;
 #[cfg(unix)]
 pub mod shell_audit;
 #[cfg(unix)]
+pub mod socat;
+#[cfg(unix)]
 pub mod ssh;
 pub mod system;
 #[cfg(unix)]
 pub mod systemd;
+#[cfg(unix)]
+pub mod yara;
+
+/// Picks between statically embedded `x86_64` and `aarch64` variants of a bundled tool for a
+/// given architecture name (as reported by `uname -m`), so a single jj build can carry tool
+/// binaries for both architectures instead of failing outright on a mismatched image
+#[cfg(unix)]
+pub(crate) fn bytes_for_arch<'a>(
+    x86_64: &'a [u8],
+    aarch64: &'a [u8],
+    arch: &str,
+) -> Option<&'a [u8]> {
+    match arch {
+        "x86_64" | "amd64" => Some(x86_64),
+        "aarch64" | "arm64" => Some(aarch64),
+        _ => None,
+    }
+}
+
+/// Same as [`bytes_for_arch`], but picks based on the architecture of the system jj is currently
+/// running on, rather than an arbitrary requested one
+#[cfg(unix)]
+pub(crate) fn embedded_tool_bytes_for_current_arch(
+    x86_64: &'static [u8],
+    aarch64: &'static [u8],
+) -> eyre::Result<&'static [u8]> {
+    let machine = nix::sys::utsname::uname()
+        .context("Could not determine system architecture via uname")?
+        .machine()
+        .to_string_lossy()
+        .into_owned();
+
+    bytes_for_arch(x86_64, aarch64, &machine)
+        .ok_or_else(|| eyre::eyre!("No embedded tool variant available for architecture {machine}"))
+}
+
+/// Fetches a tool binary that this slim build (compiled without the `bundled-tools` feature)
+/// doesn't carry, from `<base>/<arch>/<name>` for each `jj serve --tools` instance listed in
+/// `JJ_TOOLS_URL` (comma-separated, tried in order) — caching it under the system temp
+/// directory so later calls don't re-download it
+#[cfg(all(unix, not(feature = "bundled-tools")))]
+pub(crate) fn fetch_tool_bytes(name: &str) -> eyre::Result<Vec<u8>> {
+    let base_urls = std::env::var("JJ_TOOLS_URL").context(
+        "This is a slim build without an embedded copy of this tool; set JJ_TOOLS_URL to a \
+         comma-separated list of `jj serve --tools` instances to fetch it on demand",
+    )?;
+
+    let machine = nix::sys::utsname::uname()
+        .context("Could not determine system architecture via uname")?
+        .machine()
+        .to_string_lossy()
+        .into_owned();
+
+    let cache_path = std::env::temp_dir().join(format!("jj-tool-{name}-{machine}"));
+
+    if !cache_path.exists() {
+        let urls = base_urls
+            .split(',')
+            .map(str::trim)
+            .filter(|base| !base.is_empty())
+            .map(|base| format!("{}/{machine}/{name}", base.trim_end_matches('/')))
+            .collect::<Vec<_>>();
+        let mirrors = urls.iter().map(String::as_str).collect::<Vec<_>>();
+        download_file_mirrors(&mirrors, &cache_path)
+            .with_context(|| format!("Could not download missing tool {name} from {base_urls}"))?;
+    }
+
+    std::fs::read(&cache_path)
+        .with_context(|| format!("Could not read downloaded tool {}", cache_path.display()))
+}
 
 /// Alias for Perl's qx
 ///
@@ -164,8 +250,15 @@ pub fn system(command: &str) -> eyre::Result<ExitStatus> {
         .context("Could not wait for command to finish")
 }
 
+/// How many times [`download_file`] will retry a failed download before giving up
+const DOWNLOAD_RETRIES: u32 = 3;
+
 /// Downloads a file to a location, similar to `wget`
 ///
+/// Retries up to [`DOWNLOAD_RETRIES`] times with exponential backoff, resuming via a `Range`
+/// request from wherever a previous attempt left off if the target file is already partially
+/// downloaded
+///
 /// ```no_run
 /// # use jj_rs::utils::download_file;
 /// # fn demo_download() -> eyre::Result<()> {
@@ -174,21 +267,229 @@ pub fn system(command: &str) -> eyre::Result<ExitStatus> {
 /// # }
 /// ```
 pub fn download_file<P: AsRef<Path>>(url: &str, to: P) -> eyre::Result<()> {
-    let mut target_file = OpenOptions::new()
-        .truncate(true)
-        .create(true)
-        .write(true)
-        .open(to)?;
-
-    let mut response = reqwest::blocking::get(url)?;
-    if !response.status().is_success() {
+    download_file_mirrors(&[url], to)
+}
+
+/// Downloads a file like [`download_file`], but additionally verifies the downloaded bytes
+/// against an expected SHA-256 hash (as a lowercase hex string), refusing to leave the file in
+/// place if it doesn't match
+pub fn download_file_checked<P: AsRef<Path>>(
+    url: &str,
+    to: P,
+    expected_sha256: &str,
+) -> eyre::Result<()> {
+    download_file_checked_mirrors(&[url], to, expected_sha256)
+}
+
+/// Downloads a file like [`download_file`], trying each URL in `mirrors` in turn: each mirror
+/// gets its own [`DOWNLOAD_RETRIES`] attempts before moving on to the next one, and a partially
+/// downloaded file is resumed regardless of which mirror it came from
+///
+/// ```no_run
+/// # use jj_rs::utils::download_file_mirrors;
+/// # fn demo_download() -> eyre::Result<()> {
+/// download_file_mirrors(
+///     &["https://artifacts.elastic.co/elasticsearch-9.2.0-amd64.deb", "https://mirror.example/elasticsearch-9.2.0-amd64.deb"],
+///     "/tmp/elasticsearch.deb",
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn download_file_mirrors<P: AsRef<Path>>(mirrors: &[&str], to: P) -> eyre::Result<()> {
+    download_file_retrying(mirrors, to.as_ref(), None, None)
+}
+
+/// Combines [`download_file_mirrors`] and [`download_file_checked`]
+pub fn download_file_checked_mirrors<P: AsRef<Path>>(
+    mirrors: &[&str],
+    to: P,
+    expected_sha256: &str,
+) -> eyre::Result<()> {
+    download_file_retrying(mirrors, to.as_ref(), None, Some(expected_sha256))
+}
+
+/// Downloads many files concurrently, one thread and one progress bar per download. Each
+/// download gets the same retry and resume behavior as [`download_file`], and is checksummed
+/// against its expected SHA-256 hash when one is provided. Returns one result per download, in
+/// the same order as `downloads`
+pub fn download_files_concurrent(
+    downloads: Vec<(String, PathBuf, Option<String>)>,
+) -> Vec<eyre::Result<()>> {
+    let multi = indicatif::MultiProgress::new();
+    let style = indicatif::ProgressStyle::with_template(
+        "{msg} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
+    )
+    .unwrap_or_else(|_| indicatif::ProgressStyle::default_bar());
+
+    let handles = downloads
+        .into_iter()
+        .map(|(url, to, expected_sha256)| {
+            let bar = multi.add(indicatif::ProgressBar::new(0));
+            bar.set_style(style.clone());
+            bar.set_message(url.clone());
+
+            std::thread::spawn(move || {
+                let result = download_file_retrying(
+                    &[url.as_str()],
+                    &to,
+                    Some(&bar),
+                    expected_sha256.as_deref(),
+                );
+
+                if result.is_ok() {
+                    bar.finish_with_message(format!("{url} done"));
+                } else {
+                    bar.abandon_with_message(format!("{url} failed"));
+                }
+
+                result
+            })
+        })
+        .collect::<Vec<_>>();
+
+    handles
+        .into_iter()
+        .map(|handle| {
+            handle
+                .join()
+                .unwrap_or_else(|_| eyre::bail!("Download thread panicked"))
+        })
+        .collect()
+}
+
+/// Retries [`download_file_attempt`] up to [`DOWNLOAD_RETRIES`] times with exponential backoff
+/// against each mirror in turn, falling back to the next one once a mirror has exhausted its
+/// retries. A checksum mismatch is treated as a retryable failure, same as a network error, since
+/// the next attempt will redownload the file from scratch
+fn download_file_retrying(
+    mirrors: &[&str],
+    to: &Path,
+    progress: Option<&indicatif::ProgressBar>,
+    expected_sha256: Option<&str>,
+) -> eyre::Result<()> {
+    let mut last_err = None;
+
+    for (mirror_index, url) in mirrors.iter().enumerate() {
+        if mirror_index > 0 {
+            eprintln!("Falling back to mirror {url}");
+        }
+
+        for attempt in 0..DOWNLOAD_RETRIES {
+            if attempt > 0 {
+                std::thread::sleep(std::time::Duration::from_secs(2u64.pow(attempt)));
+            }
+
+            match download_file_attempt(url, to, progress, expected_sha256) {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    eprintln!("Attempt {} to download {url} failed: {e}", attempt + 1);
+                    last_err = Some(e);
+                }
+            }
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| eyre::eyre!("Could not download from any mirror")))
+}
+
+/// A single download attempt. Resumes from the current length of `to` (if any) via a `Range`
+/// request; if the server doesn't honor it, falls back to redownloading the whole file. If
+/// `expected_sha256` is given and the downloaded file doesn't match, the file is removed and an
+/// error is returned so the caller doesn't mistake it for a good download
+fn download_file_attempt(
+    url: &str,
+    to: &Path,
+    progress: Option<&indicatif::ProgressBar>,
+    expected_sha256: Option<&str>,
+) -> eyre::Result<()> {
+    let existing_len = std::fs::metadata(to).map(|m| m.len()).unwrap_or(0);
+
+    let client = reqwest::blocking::Client::new();
+    let mut request = client.get(url);
+    if existing_len > 0 {
+        request = request.header("range", format!("bytes={existing_len}-"));
+    }
+
+    let response = request.send()?;
+    let resuming = existing_len > 0 && response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+
+    if !resuming && !response.status().is_success() {
         eyre::bail!(
             "Got response of {} when downloading {url}",
             response.status()
         );
     }
 
-    response.copy_to(&mut target_file)?;
+    let mut target_file = if resuming {
+        OpenOptions::new().append(true).open(to)?
+    } else {
+        OpenOptions::new()
+            .truncate(true)
+            .create(true)
+            .write(true)
+            .open(to)?
+    };
+
+    if let Some(bar) = progress {
+        let total = response
+            .content_length()
+            .map(|len| if resuming { len + existing_len } else { len });
+        if let Some(total) = total {
+            bar.set_length(total);
+        }
+        if resuming {
+            bar.set_position(existing_len);
+        }
+
+        std::io::copy(&mut bar.wrap_read(response), &mut target_file)?;
+    } else {
+        let mut response = response;
+        response.copy_to(&mut target_file)?;
+    }
+
+    drop(target_file);
+
+    if let Some(expected_sha256) = expected_sha256
+        && let Err(e) = verify_sha256(to, expected_sha256)
+    {
+        let _ = std::fs::remove_file(to);
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Hashes a byte slice with SHA-256 and returns the hex digest
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Hashes `path` with SHA-256 and compares it (case-insensitively) against `expected`, bailing if
+/// they don't match
+fn verify_sha256(path: &Path, expected: &str) -> eyre::Result<()> {
+    use sha2::{Digest, Sha256};
+
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let n = std::io::Read::read(&mut file, &mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+
+    let actual = format!("{:x}", hasher.finalize());
+    if !actual.eq_ignore_ascii_case(expected) {
+        eyre::bail!(
+            "Checksum mismatch for {}: expected {expected}, got {actual}",
+            path.display()
+        );
+    }
 
     Ok(())
 }
@@ -23,7 +23,13 @@ use eyre::Context;
 use flate2::write::GzDecoder;
 use nix::sys::memfd::{MFdFlags, memfd_create};
 
-const NFT_BYTES: &[u8] = include_bytes!(std::env!("NFT_GZIPPED"));
+pub(crate) const NFT_BYTES_X86_64: &[u8] = include_bytes!(std::env!("NFT_GZIPPED_X86_64"));
+pub(crate) const NFT_BYTES_AARCH64: &[u8] = include_bytes!(std::env!("NFT_GZIPPED_AARCH64"));
+
+/// Expected SHA-256 hashes of the gzipped payloads above, baked in at build time so `jj verify`
+/// can detect a tampered binary
+pub(crate) const NFT_SHA256_X86_64: &str = std::env!("NFT_SHA256_X86_64");
+pub(crate) const NFT_SHA256_AARCH64: &str = std::env!("NFT_SHA256_AARCH64");
 
 /// Handle around the `nft` binary
 pub struct Nft {
@@ -41,8 +47,12 @@ impl Nft {
         let temp_file = unsafe { File::from_raw_fd(fd) };
         let mut decoder = GzDecoder::new(temp_file);
 
+        let nft_bytes = crate::utils::embedded_tool_bytes_for_current_arch(
+            NFT_BYTES_X86_64,
+            NFT_BYTES_AARCH64,
+        )?;
         decoder
-            .write_all(NFT_BYTES)
+            .write_all(nft_bytes)
             .context("Could not write all nft bytes")?;
 
         let nft_file = decoder
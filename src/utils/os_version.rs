@@ -18,6 +18,9 @@ pub enum OsFamily {
     Arch,
     Rocky,
     Oracle,
+    Suse,
+    AmazonLinux,
+    Slackware,
     Other(String),
 }
 
@@ -38,14 +41,31 @@ impl Distro {
 
     pub fn is_rhel_based(&self) -> bool {
         use OsFamily as OsF;
-        matches!(self.root_family, OsF::RedHat | OsF::Fedora)
-            || matches!(self.derived_family, Some(OsF::RedHat | OsF::Fedora))
+        matches!(
+            self.root_family,
+            OsF::RedHat | OsF::Fedora | OsF::AmazonLinux
+        ) || matches!(
+            self.derived_family,
+            Some(OsF::RedHat | OsF::Fedora | OsF::AmazonLinux)
+        )
     }
 
     pub fn is_rhel_or_deb_based(&self) -> bool {
         self.is_deb_based() || self.is_rhel_based()
     }
 
+    pub fn is_suse_based(&self) -> bool {
+        self.root_family == OsFamily::Suse || self.derived_family == Some(OsFamily::Suse)
+    }
+
+    pub fn is_arch_based(&self) -> bool {
+        self.root_family == OsFamily::Arch || self.derived_family == Some(OsFamily::Arch)
+    }
+
+    pub fn is_alpine_based(&self) -> bool {
+        self.root_family == OsFamily::Alpine || self.derived_family == Some(OsFamily::Alpine)
+    }
+
     pub fn is_windows(&self) -> bool {
         self.root_family == OsFamily::Windows
     }
@@ -82,6 +102,15 @@ impl From<&str> for OsFamily {
         if s.contains("oracle") {
             return OsFamily::Oracle;
         }
+        if s.contains("suse") {
+            return OsFamily::Suse;
+        }
+        if s.contains("amzn") || s.contains("amazon") {
+            return OsFamily::AmazonLinux;
+        }
+        if s.contains("slackware") {
+            return OsFamily::Slackware;
+        }
 
         OsFamily::Other(s)
     }
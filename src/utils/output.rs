@@ -0,0 +1,25 @@
+//! Shared `--format text|json` switch for read-only commands (`enum`, `ports`, `stat`, ...), so
+//! each one doesn't reinvent its own output format enum and JSON-printing boilerplate
+
+use serde::Serialize;
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// A single JSON value, for scripting and diffing between hosts
+    Json,
+}
+
+impl OutputFormat {
+    pub fn is_json(self) -> bool {
+        matches!(self, OutputFormat::Json)
+    }
+}
+
+/// Prints `value` as pretty-printed JSON to stdout
+pub fn print_json<T: Serialize>(value: &T) -> eyre::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -11,7 +11,9 @@ use nix::{
     sched::{CloneFlags, unshare},
 };
 
-use crate::utils::{busybox::Busybox, download_container::DownloadContainer, qx, system};
+use crate::utils::{
+    busybox::Busybox, download_container::DownloadContainer, os_version::Distro, qx, system,
+};
 
 #[derive(Debug, Clone)]
 pub enum DownloadSettings {
@@ -22,6 +24,74 @@ pub enum DownloadSettings {
     },
 }
 
+/// Which package manager a host's distro uses, so callers that don't care about deb/rpm/etc
+/// specifics can install packages through [`install_packages`] instead of picking a
+/// distro-specific `install_*_packages` function themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Apt,
+    Dnf,
+    Zypper,
+    Pacman,
+    Apk,
+}
+
+impl PackageManager {
+    /// Picks the package manager for `distro`. Errs out for distros this module doesn't know
+    /// how to install packages on (Slackware's `pkgtools` has no concept of dependency
+    /// resolution or an offline-download mode, so it isn't supported here)
+    pub fn for_distro(distro: &Distro) -> eyre::Result<Self> {
+        if distro.is_deb_based() {
+            Ok(Self::Apt)
+        } else if distro.is_rhel_based() {
+            Ok(Self::Dnf)
+        } else if distro.is_suse_based() {
+            Ok(Self::Zypper)
+        } else if distro.is_arch_based() {
+            Ok(Self::Pacman)
+        } else if distro.is_alpine_based() {
+            Ok(Self::Apk)
+        } else {
+            Err(eyre::eyre!(
+                "Don't know how to install packages on {:?}",
+                distro
+                    .derived_family
+                    .as_ref()
+                    .unwrap_or(&distro.root_family)
+            ))
+        }
+    }
+}
+
+/// Download and install `packages` with whichever package manager `distro` uses
+pub fn install_packages<S: AsRef<str>>(
+    distro: &Distro,
+    settings: DownloadSettings,
+    packages: &[S],
+) -> eyre::Result<()> {
+    match PackageManager::for_distro(distro)? {
+        PackageManager::Apt => install_apt_packages(settings, packages),
+        PackageManager::Dnf => install_dnf_packages(settings, packages),
+        PackageManager::Zypper => install_zypper_packages(settings, packages),
+        PackageManager::Pacman => install_pacman_packages(settings, packages),
+        PackageManager::Apk => install_apk_packages(settings, packages),
+    }
+}
+
+/// Whether `package` is already installed, using whichever package manager `distro` uses to
+/// query its package database
+pub fn is_package_installed(distro: &Distro, package: &str) -> eyre::Result<bool> {
+    let installed = match PackageManager::for_distro(distro)? {
+        PackageManager::Apt => qx("dpkg -l")?.1,
+        PackageManager::Dnf => qx("rpm -qa")?.1,
+        PackageManager::Zypper => qx("rpm -qa")?.1,
+        PackageManager::Pacman => qx("pacman -Q")?.1,
+        PackageManager::Apk => qx("apk info")?.1,
+    };
+
+    Ok(installed.split('\n').any(|i| i.starts_with(package)))
+}
+
 /// Download and install apt packages
 pub fn install_apt_packages<S: AsRef<str>>(
     settings: DownloadSettings,
@@ -93,7 +163,7 @@ pub fn install_apt_packages<S: AsRef<str>>(
 
     match settings {
         DownloadSettings::Container { name, sneaky_ip } => {
-            let container = DownloadContainer::new(name, sneaky_ip)?;
+            let container = DownloadContainer::new(name, sneaky_ip, None, None)?;
 
             container.run(|| -> eyre::Result<()> {
                 system("apt update")?;
@@ -163,7 +233,7 @@ pub fn install_dnf_packages<S: AsRef<str>>(
 
     match settings {
         DownloadSettings::Container { name, sneaky_ip } => {
-            let container = DownloadContainer::new(name, sneaky_ip)?;
+            let container = DownloadContainer::new(name, sneaky_ip, None, None)?;
 
             container.run(|| -> eyre::Result<()> {
                 std::process::Command::new("/bin/sh")
@@ -210,3 +280,189 @@ pub fn install_dnf_packages<S: AsRef<str>>(
 
     Ok(())
 }
+
+/// Download and install packages with zypper (SUSE / openSUSE)
+pub fn install_zypper_packages<S: AsRef<str>>(
+    settings: DownloadSettings,
+    packages: &[S],
+) -> eyre::Result<()> {
+    let bb = Busybox::new()?;
+    let packages_dir_raw = bb.execute(&["mktemp", "-d"])?;
+    let packages_dir = packages_dir_raw.trim();
+
+    let package_list = qx("rpm -qa")?.1;
+    let packages = packages
+        .iter()
+        .flat_map(|p| {
+            if !package_list.split('\n').any(|i| i.starts_with(p.as_ref())) {
+                Some(p.as_ref())
+            } else {
+                eprintln!("Package {} already installed!", p.as_ref());
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if packages.is_empty() {
+        println!("All packages specified have already been installed!");
+        return Ok(());
+    }
+
+    let download_cmd = format!(
+        "zypper --pkg-cache-dir {packages_dir} install -y --download-only {}",
+        packages.join(" ")
+    );
+
+    match settings {
+        DownloadSettings::Container { name, sneaky_ip } => {
+            let container = DownloadContainer::new(name, sneaky_ip, None, None)?;
+
+            container.run(|| -> eyre::Result<()> {
+                system(&download_cmd)?;
+                Ok(())
+            })??;
+        }
+        DownloadSettings::NoContainer => {
+            system(&download_cmd)?;
+        }
+    }
+
+    let downloaded_package_paths = walkdir::WalkDir::new(packages_dir)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rpm"))
+        .map(|entry| entry.path().display().to_string())
+        .collect::<Vec<_>>();
+
+    system(&format!(
+        "zypper install -y --allow-unsigned-rpm {}",
+        downloaded_package_paths.join(" ")
+    ))?;
+
+    let _ = std::fs::remove_dir_all(packages_dir);
+
+    Ok(())
+}
+
+/// Download and install packages with pacman (Arch)
+pub fn install_pacman_packages<S: AsRef<str>>(
+    settings: DownloadSettings,
+    packages: &[S],
+) -> eyre::Result<()> {
+    let bb = Busybox::new()?;
+    let packages_dir_raw = bb.execute(&["mktemp", "-d"])?;
+    let packages_dir = packages_dir_raw.trim();
+
+    let package_list = qx("pacman -Q")?.1;
+    let packages = packages
+        .iter()
+        .flat_map(|p| {
+            if !package_list.split('\n').any(|i| i.starts_with(p.as_ref())) {
+                Some(p.as_ref())
+            } else {
+                eprintln!("Package {} already installed!", p.as_ref());
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if packages.is_empty() {
+        println!("All packages specified have already been installed!");
+        return Ok(());
+    }
+
+    let download_cmd = format!(
+        "pacman -Sw --noconfirm --cachedir {packages_dir} {}",
+        packages.join(" ")
+    );
+
+    match settings {
+        DownloadSettings::Container { name, sneaky_ip } => {
+            let container = DownloadContainer::new(name, sneaky_ip, None, None)?;
+
+            container.run(|| -> eyre::Result<()> {
+                system(&download_cmd)?;
+                Ok(())
+            })??;
+        }
+        DownloadSettings::NoContainer => {
+            system(&download_cmd)?;
+        }
+    }
+
+    let downloaded_package_paths = std::fs::read_dir(packages_dir)?
+        .flat_map(|entry| entry)
+        .flat_map(|entry| entry.file_name().into_string())
+        .filter(|entry| entry.ends_with(".pkg.tar.zst"))
+        .map(|entry| format!("{packages_dir}/{entry}"))
+        .collect::<Vec<_>>();
+
+    system(&format!(
+        "pacman -U --noconfirm {}",
+        downloaded_package_paths.join(" ")
+    ))?;
+
+    let _ = std::fs::remove_dir_all(packages_dir);
+
+    Ok(())
+}
+
+/// Download and install packages with apk (Alpine)
+pub fn install_apk_packages<S: AsRef<str>>(
+    settings: DownloadSettings,
+    packages: &[S],
+) -> eyre::Result<()> {
+    let bb = Busybox::new()?;
+    let packages_dir_raw = bb.execute(&["mktemp", "-d"])?;
+    let packages_dir = packages_dir_raw.trim();
+
+    let package_list = qx("apk info")?.1;
+    let packages = packages
+        .iter()
+        .flat_map(|p| {
+            if !package_list.split('\n').any(|i| i.starts_with(p.as_ref())) {
+                Some(p.as_ref())
+            } else {
+                eprintln!("Package {} already installed!", p.as_ref());
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if packages.is_empty() {
+        println!("All packages specified have already been installed!");
+        return Ok(());
+    }
+
+    let download_cmd = format!("apk fetch --output {packages_dir} {}", packages.join(" "));
+
+    match settings {
+        DownloadSettings::Container { name, sneaky_ip } => {
+            let container = DownloadContainer::new(name, sneaky_ip, None, None)?;
+
+            container.run(|| -> eyre::Result<()> {
+                system(&download_cmd)?;
+                Ok(())
+            })??;
+        }
+        DownloadSettings::NoContainer => {
+            system(&download_cmd)?;
+        }
+    }
+
+    let downloaded_package_paths = std::fs::read_dir(packages_dir)?
+        .flat_map(|entry| entry)
+        .flat_map(|entry| entry.file_name().into_string())
+        .filter(|entry| entry.ends_with(".apk"))
+        .map(|entry| format!("{packages_dir}/{entry}"))
+        .collect::<Vec<_>>();
+
+    system(&format!(
+        "apk add --allow-untrusted {}",
+        downloaded_package_paths.join(" ")
+    ))?;
+
+    let _ = std::fs::remove_dir_all(packages_dir);
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+//! Direct libpam bindings for running a PAM login, used by `PamCheck` instead of shelling out to
+//! the bundled `pamtester` binary. Besides dropping an embedded binary, this also works on hosts
+//! where executing from a memfd/tmpfs is blocked (a `noexec` mount, an LSM policy), and reports
+//! which specific PAM call failed instead of scraping pamtester's stdout for it
+
+use std::ffi::{CStr, CString, c_char, c_int, c_void};
+
+use eyre::{Context, bail};
+
+mod ffi {
+    use std::ffi::{c_char, c_int, c_void};
+
+    #[repr(C)]
+    pub struct PamHandle {
+        _data: (),
+        _marker: std::marker::PhantomData<(*mut u8, std::marker::PhantomPinned)>,
+    }
+
+    #[repr(C)]
+    pub struct PamMessage {
+        pub msg_style: c_int,
+        pub msg: *const c_char,
+    }
+
+    #[repr(C)]
+    pub struct PamResponse {
+        pub resp: *mut c_char,
+        pub resp_retcode: c_int,
+    }
+
+    #[repr(C)]
+    pub struct PamConv {
+        pub conv: Option<
+            unsafe extern "C" fn(
+                num_msg: c_int,
+                msg: *mut *const PamMessage,
+                resp: *mut *mut PamResponse,
+                appdata_ptr: *mut c_void,
+            ) -> c_int,
+        >,
+        pub appdata_ptr: *mut c_void,
+    }
+
+    #[link(name = "pam")]
+    unsafe extern "C" {
+        pub fn pam_start(
+            service_name: *const c_char,
+            user: *const c_char,
+            pam_conversation: *const PamConv,
+            pamh: *mut *mut PamHandle,
+        ) -> c_int;
+        pub fn pam_end(pamh: *mut PamHandle, pam_status: c_int) -> c_int;
+        pub fn pam_authenticate(pamh: *mut PamHandle, flags: c_int) -> c_int;
+        pub fn pam_open_session(pamh: *mut PamHandle, flags: c_int) -> c_int;
+        pub fn pam_close_session(pamh: *mut PamHandle, flags: c_int) -> c_int;
+        pub fn pam_strerror(pamh: *mut PamHandle, errnum: c_int) -> *const c_char;
+    }
+}
+
+const PAM_SUCCESS: c_int = 0;
+const PAM_BUF_ERR: c_int = 5;
+const PAM_CONV_ERR: c_int = 19;
+
+const PAM_PROMPT_ECHO_OFF: c_int = 1;
+const PAM_PROMPT_ECHO_ON: c_int = 2;
+
+/// The outcome of one libpam call (`pam_authenticate`, `pam_open_session`, ...), named after the
+/// phase it ran so a caller can tell exactly which stage of the login failed
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PamStepResult {
+    pub step: &'static str,
+    pub success: bool,
+    pub message: String,
+}
+
+/// Feeds `password` back for every `PAM_PROMPT_ECHO_OFF`/`PAM_PROMPT_ECHO_ON` message libpam
+/// asks for (i.e. the password prompt); any other message style is ignored, matching pamtester's
+/// own behavior of only ever answering with the one password it was given
+unsafe extern "C" fn conversation_callback(
+    num_msg: c_int,
+    msg: *mut *const ffi::PamMessage,
+    resp: *mut *mut ffi::PamResponse,
+    appdata_ptr: *mut c_void,
+) -> c_int {
+    if num_msg <= 0 || msg.is_null() || resp.is_null() {
+        return PAM_CONV_ERR;
+    }
+
+    // Allocated with libc's allocator, not Rust's: libpam calls free() on this array and on
+    // each individual response string once it's done with them
+    let responses = unsafe { libc::calloc(num_msg as usize, size_of::<ffi::PamResponse>()) }
+        as *mut ffi::PamResponse;
+    if responses.is_null() {
+        return PAM_BUF_ERR;
+    }
+
+    let password = appdata_ptr as *const c_char;
+
+    for i in 0..num_msg as isize {
+        let message = unsafe { &**msg.offset(i) };
+        let response = unsafe { &mut *responses.offset(i) };
+
+        response.resp_retcode = 0;
+        response.resp = match message.msg_style {
+            PAM_PROMPT_ECHO_OFF | PAM_PROMPT_ECHO_ON => unsafe { libc::strdup(password) },
+            _ => std::ptr::null_mut(),
+        };
+    }
+
+    unsafe { *resp = responses };
+    PAM_SUCCESS
+}
+
+fn pam_err_string(pamh: *mut ffi::PamHandle, errnum: c_int) -> String {
+    let msg = unsafe { ffi::pam_strerror(pamh, errnum) };
+    if msg.is_null() {
+        return format!("unknown PAM error {errnum}");
+    }
+    unsafe { CStr::from_ptr(msg) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Runs a PAM login against `service` as `user`, authenticating with `password`, equivalent to
+/// `pamtester <service> <user> authenticate open_session close_session`. Stops at the first
+/// failing step rather than running the rest, same as pamtester itself
+pub fn pam_login(service: &str, user: &str, password: &str) -> eyre::Result<Vec<PamStepResult>> {
+    let service_c = CString::new(service).context("PAM service name contains an interior NUL")?;
+    let user_c = CString::new(user).context("Username contains an interior NUL")?;
+    let password_c = CString::new(password).context("Password contains an interior NUL")?;
+
+    let conv = ffi::PamConv {
+        conv: Some(conversation_callback),
+        appdata_ptr: password_c.as_ptr() as *mut c_void,
+    };
+
+    let mut pamh: *mut ffi::PamHandle = std::ptr::null_mut();
+    let start_rc = unsafe { ffi::pam_start(service_c.as_ptr(), user_c.as_ptr(), &conv, &mut pamh) };
+    if start_rc != PAM_SUCCESS || pamh.is_null() {
+        bail!(
+            "pam_start failed for service {service}: {}",
+            pam_err_string(pamh, start_rc)
+        );
+    }
+
+    let steps: &[(
+        &str,
+        unsafe extern "C" fn(*mut ffi::PamHandle, c_int) -> c_int,
+    )] = &[
+        ("authenticate", ffi::pam_authenticate),
+        ("open_session", ffi::pam_open_session),
+        ("close_session", ffi::pam_close_session),
+    ];
+
+    let mut results = Vec::with_capacity(steps.len());
+    let mut last_rc = start_rc;
+
+    for (name, step_fn) in steps {
+        last_rc = unsafe { step_fn(pamh, 0) };
+        results.push(PamStepResult {
+            step: name,
+            success: last_rc == PAM_SUCCESS,
+            message: pam_err_string(pamh, last_rc),
+        });
+
+        if last_rc != PAM_SUCCESS {
+            break;
+        }
+    }
+
+    unsafe { ffi::pam_end(pamh, last_rc) };
+
+    Ok(results)
+}
@@ -22,10 +22,23 @@ use std::{
 };
 
 use eyre::Context;
+#[cfg(feature = "bundled-tools")]
 use flate2::write::GzDecoder;
 use nix::sys::memfd::{MFdFlags, memfd_create};
 
-const PAMTESTER_BYTES: &[u8] = include_bytes!(std::env!("PAMTESTER_GZIPPED"));
+#[cfg(feature = "bundled-tools")]
+pub(crate) const PAMTESTER_BYTES_X86_64: &[u8] =
+    include_bytes!(std::env!("PAMTESTER_GZIPPED_X86_64"));
+#[cfg(feature = "bundled-tools")]
+pub(crate) const PAMTESTER_BYTES_AARCH64: &[u8] =
+    include_bytes!(std::env!("PAMTESTER_GZIPPED_AARCH64"));
+
+/// Expected SHA-256 hashes of the gzipped payloads above, baked in at build time so `jj verify`
+/// can detect a tampered binary
+#[cfg(feature = "bundled-tools")]
+pub(crate) const PAMTESTER_SHA256_X86_64: &str = std::env!("PAMTESTER_SHA256_X86_64");
+#[cfg(feature = "bundled-tools")]
+pub(crate) const PAMTESTER_SHA256_AARCH64: &str = std::env!("PAMTESTER_SHA256_AARCH64");
 
 /// Handle around the `pamtester` binary
 pub struct Pamtester {
@@ -40,18 +53,35 @@ impl Pamtester {
 
         let fd = temp_fd.into_raw_fd();
 
-        let temp_file = unsafe { File::from_raw_fd(fd) };
-        let mut decoder = GzDecoder::new(temp_file);
+        let mut temp_file = unsafe { File::from_raw_fd(fd) };
+
+        #[cfg(feature = "bundled-tools")]
+        {
+            let pamtester_bytes = crate::utils::embedded_tool_bytes_for_current_arch(
+                PAMTESTER_BYTES_X86_64,
+                PAMTESTER_BYTES_AARCH64,
+            )?;
 
-        decoder
-            .write_all(PAMTESTER_BYTES)
-            .context("Could not write all pamtester bytes")?;
+            let mut decoder = GzDecoder::new(temp_file);
+            decoder
+                .write_all(pamtester_bytes)
+                .context("Could not write all pamtester bytes")?;
+            temp_file = decoder
+                .finish()
+                .context("Could not finish writing decompressing pamtester")?;
+        }
 
-        let pamtester_file = decoder
-            .finish()
-            .context("Could not finish writing decompressing pamtester")?;
+        #[cfg(not(feature = "bundled-tools"))]
+        {
+            let pamtester_bytes = crate::utils::fetch_tool_bytes("pamtester")?;
+            temp_file
+                .write_all(&pamtester_bytes)
+                .context("Could not write all pamtester bytes")?;
+        }
 
-        Ok(Self { pamtester_file })
+        Ok(Self {
+            pamtester_file: temp_file,
+        })
     }
 
     /// Create a new [`std::process::Command`] object to perform further
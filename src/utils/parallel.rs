@@ -0,0 +1,69 @@
+//! Bounded-parallelism helper for running many independent closures (usually each shelling out
+//! to an external command) concurrently, without spinning up one OS thread per item and without
+//! a single slow one stalling the rest
+
+use std::{sync::mpsc, time::Duration};
+
+/// The outcome of a single task passed to [`run_bounded`]
+pub enum TaskOutcome<T> {
+    /// The task returned `T` before its timeout (if any) elapsed
+    Finished(T),
+    /// The task was still running when its timeout elapsed. It's left running in the
+    /// background rather than killed, but its result is discarded
+    TimedOut,
+    /// The task's thread panicked before it could produce a result
+    Panicked,
+}
+
+/// Runs `tasks` with at most `max_concurrency` running at once, waiting up to `timeout` (if
+/// given) for each one to finish before moving on. Returns one [`TaskOutcome`] per task, in the
+/// same order the tasks were given
+pub fn run_bounded<T, F>(
+    tasks: Vec<F>,
+    max_concurrency: usize,
+    timeout: Option<Duration>,
+) -> Vec<TaskOutcome<T>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let max_concurrency = max_concurrency.max(1);
+    let mut outcomes = Vec::with_capacity(tasks.len());
+    let mut tasks = tasks.into_iter();
+
+    loop {
+        let batch = tasks.by_ref().take(max_concurrency).collect::<Vec<_>>();
+        if batch.is_empty() {
+            break;
+        }
+
+        let waiters = batch
+            .into_iter()
+            .map(|task| {
+                let (tx, rx) = mpsc::channel();
+                // The thread isn't joined if its task times out, so it's left to finish (or
+                // keep hanging) in the background rather than blocking the caller on it
+                std::thread::spawn(move || {
+                    let _ = tx.send(task());
+                });
+                rx
+            })
+            .collect::<Vec<_>>();
+
+        for rx in waiters {
+            outcomes.push(match timeout {
+                Some(duration) => match rx.recv_timeout(duration) {
+                    Ok(value) => TaskOutcome::Finished(value),
+                    Err(mpsc::RecvTimeoutError::Timeout) => TaskOutcome::TimedOut,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => TaskOutcome::Panicked,
+                },
+                None => match rx.recv() {
+                    Ok(value) => TaskOutcome::Finished(value),
+                    Err(_) => TaskOutcome::Panicked,
+                },
+            });
+        }
+    }
+
+    outcomes
+}
@@ -1,9 +1,242 @@
-//! Utilities for reading passwd entries
+//! Utilities for reading passwd entries, plus a safe API for editing `/etc/shadow` directly
+//! (password hashing, lock/unlock/expire), shared by `jj useradd` and `jj rotate` so both go
+//! through the same locking and hashing instead of each shelling out to `chpasswd`/`passwd`
+//! independently
 //!
 //! Makes use of getent to also query for domain user information, if available
 
+use std::{
+    ffi::{CStr, CString},
+    fs::{self, OpenOptions},
+    io::Write,
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::Path,
+};
+
+use eyre::{Context, bail};
+
 use crate::utils::qx;
 
+mod ffi {
+    use std::ffi::{c_char, c_int, c_ulong};
+
+    #[link(name = "crypt")]
+    unsafe extern "C" {
+        pub fn crypt(key: *const c_char, salt: *const c_char) -> *mut c_char;
+        pub fn crypt_gensalt(
+            prefix: *const c_char,
+            count: c_ulong,
+            rbytes: *const c_char,
+            nrbytes: c_int,
+        ) -> *mut c_char;
+    }
+
+    unsafe extern "C" {
+        pub fn lckpwdf() -> c_int;
+        pub fn ulckpwdf() -> c_int;
+    }
+}
+
+const SHADOW_PATH: &str = "/etc/shadow";
+
+/// Password hashing scheme for [`hash_password`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashMethod {
+    /// `$y$` — the current default on most distributions shipping libxcrypt
+    Yescrypt,
+    /// `$6$` — supported by every glibc/libxcrypt in practical use, used as the fallback if
+    /// [`HashMethod::Yescrypt`] isn't recognized by the local libcrypt
+    Sha512,
+}
+
+impl HashMethod {
+    fn prefix(self) -> &'static str {
+        match self {
+            HashMethod::Yescrypt => "$y$",
+            HashMethod::Sha512 => "$6$",
+        }
+    }
+}
+
+/// Hashes `password` for storage in `/etc/shadow`, via the system's own libcrypt (`crypt_gensalt`
+/// generates a fresh random salt for `method`, `crypt` does the hashing) so the result uses
+/// whatever cost parameters the local distribution considers current. Falls back from
+/// [`HashMethod::Yescrypt`] to [`HashMethod::Sha512`] if libcrypt doesn't recognize the `$y$`
+/// prefix (older distributions without libxcrypt)
+pub fn hash_password(password: &str, method: HashMethod) -> eyre::Result<String> {
+    match crypt_with_method(password, method) {
+        Ok(hash) => Ok(hash),
+        Err(e) if method == HashMethod::Yescrypt => {
+            eprintln!("warning: {e}, falling back to sha512-crypt");
+            crypt_with_method(password, HashMethod::Sha512)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn crypt_with_method(password: &str, method: HashMethod) -> eyre::Result<String> {
+    let prefix = CString::new(method.prefix()).expect("hash method prefix has no interior NUL");
+    let salt = unsafe { ffi::crypt_gensalt(prefix.as_ptr(), 0, std::ptr::null(), 0) };
+    if salt.is_null() {
+        bail!(
+            "crypt_gensalt could not generate a {} salt on this system",
+            method.prefix()
+        );
+    }
+    // Copied to an owned buffer right away: crypt_gensalt and crypt share the same static
+    // buffer on most libcrypt implementations, so this would otherwise be clobbered by crypt()
+    let salt = unsafe { CStr::from_ptr(salt) }.to_owned();
+
+    let key = CString::new(password).context("password contains an interior NUL byte")?;
+    let hash = unsafe { ffi::crypt(key.as_ptr(), salt.as_ptr()) };
+    if hash.is_null() {
+        bail!("crypt() failed to hash the password");
+    }
+
+    Ok(unsafe { CStr::from_ptr(hash) }
+        .to_string_lossy()
+        .into_owned())
+}
+
+/// Holds the advisory lock acquired by [`lock_password_files`] until dropped
+pub struct PasswdLock(());
+
+impl Drop for PasswdLock {
+    fn drop(&mut self) {
+        unsafe { ffi::ulckpwdf() };
+    }
+}
+
+/// Acquires the same advisory lock `passwd(1)`/`usermod(8)`/`chpasswd(8)` take before editing
+/// `/etc/passwd`/`/etc/shadow` (glibc's `lckpwdf`/`ulckpwdf`), so a direct edit through this
+/// module can't race with one of those tools running concurrently
+pub fn lock_password_files() -> eyre::Result<PasswdLock> {
+    if unsafe { ffi::lckpwdf() } != 0 {
+        bail!(
+            "Could not acquire the passwd/shadow file lock (is another user-management tool \
+             running?)"
+        );
+    }
+    Ok(PasswdLock(()))
+}
+
+fn days_since_epoch() -> i64 {
+    let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).expect("1970-01-01 is a valid date");
+    chrono::Utc::now()
+        .date_naive()
+        .signed_duration_since(epoch)
+        .num_days()
+}
+
+/// Rewrites `path` with `contents`, preserving its original permissions, after copying the
+/// existing file to `<path>.jj-bak` for recovery. The replacement is written to a temp file in
+/// the same directory and renamed into place, so a crash mid-write can't leave a half-written
+/// `/etc/shadow` behind
+fn write_atomic_with_backup(path: &Path, contents: &str) -> eyre::Result<()> {
+    let permissions = fs::metadata(path)
+        .with_context(|| format!("Could not stat {}", path.display()))?
+        .permissions();
+
+    let backup = format!("{}.jj-bak", path.display());
+    fs::copy(path, &backup)
+        .with_context(|| format!("Could not back up {} to {backup}", path.display()))?;
+
+    let dir = path.parent().unwrap_or_else(|| Path::new("/"));
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("jj-edit");
+    let tmp = dir.join(format!(".{file_name}.jj-tmp"));
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(permissions.mode())
+        .open(&tmp)
+        .with_context(|| format!("Could not create {}", tmp.display()))?;
+    file.write_all(contents.as_bytes())
+        .with_context(|| format!("Could not write {}", tmp.display()))?;
+    file.sync_all()
+        .with_context(|| format!("Could not flush {}", tmp.display()))?;
+    drop(file);
+
+    fs::rename(&tmp, path)
+        .with_context(|| format!("Could not install rewritten {}", path.display()))?;
+
+    Ok(())
+}
+
+/// Edits `user`'s `/etc/shadow` row under [`lock_password_files`], writing the result back via
+/// [`write_atomic_with_backup`]. `edit` receives the row's colon-separated fields (`user`,
+/// `password`, `lastchange`, `min`, `max`, `warn`, `inactive`, `expire`) and mutates them in
+/// place
+fn edit_shadow_entry(user: &str, edit: impl FnOnce(&mut [String])) -> eyre::Result<()> {
+    let _lock = lock_password_files()?;
+
+    let contents =
+        fs::read_to_string(SHADOW_PATH).with_context(|| format!("Could not read {SHADOW_PATH}"))?;
+
+    let mut found = false;
+    let mut rewritten = String::with_capacity(contents.len());
+
+    for line in contents.lines() {
+        let mut fields: Vec<String> = line.split(':').map(String::from).collect();
+        if !found && fields.first().map(String::as_str) == Some(user) && fields.len() >= 8 {
+            found = true;
+            edit(&mut fields);
+        }
+        rewritten.push_str(&fields.join(":"));
+        rewritten.push('\n');
+    }
+
+    if !found {
+        bail!("No {SHADOW_PATH} entry for user {user}");
+    }
+
+    write_atomic_with_backup(Path::new(SHADOW_PATH), &rewritten)
+}
+
+/// Hashes `password` with `method` and sets it as `user`'s password directly in `/etc/shadow`
+/// (also bumping the last-changed field to today), without going through `passwd`/`chpasswd`
+pub fn set_password(user: &str, password: &str, method: HashMethod) -> eyre::Result<()> {
+    let hash = hash_password(password, method)?;
+    let today = days_since_epoch().to_string();
+
+    edit_shadow_entry(user, |fields| {
+        fields[1] = hash;
+        fields[2] = today;
+    })
+}
+
+/// Locks `user`'s password login by prefixing the shadow hash with `!`, matching `passwd -l`. A
+/// no-op if the account is already locked
+pub fn lock_account(user: &str) -> eyre::Result<()> {
+    edit_shadow_entry(user, |fields| {
+        if !fields[1].starts_with('!') {
+            fields[1].insert(0, '!');
+        }
+    })
+}
+
+/// Reverses [`lock_account`], matching `passwd -u`
+pub fn unlock_account(user: &str) -> eyre::Result<()> {
+    edit_shadow_entry(user, |fields| {
+        if fields[1].starts_with('!') {
+            fields[1].remove(0);
+        }
+    })
+}
+
+/// Expires `user`'s account as of today, matching `chage -E 0`: the account becomes unusable
+/// until an administrator clears the expiration field
+pub fn expire_account(user: &str) -> eyre::Result<()> {
+    let today = days_since_epoch().to_string();
+    edit_shadow_entry(user, |fields| {
+        fields[7] = today;
+    })
+}
+
 /// Matches the structure of man 5 passwd
 #[allow(dead_code)]
 pub struct Passwd {
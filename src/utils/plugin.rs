@@ -0,0 +1,81 @@
+//! Loads native check plugins: shared libraries built against a small, stable C ABI, so
+//! site-specific checks can be added without rebuilding jj itself
+//!
+//! A plugin is a `cdylib` exporting three `extern "C"` functions:
+//!
+//! - `jj_plugin_name() -> *const c_char` — a static, null-terminated display name
+//! - `jj_plugin_run_check(config_json: *const c_char) -> *mut c_char` — runs the check, given
+//!   the troubleshooter's `config` as a JSON string, and returns a JSON-encoded
+//!   [`CheckResult`](crate::utils::checks::CheckResult) that the caller takes ownership of
+//! - `jj_plugin_free_string(s: *mut c_char)` — frees a string previously returned by
+//!   `jj_plugin_run_check`, so the plugin's own allocator stays in charge of its own memory
+//!
+//! See [`crate::checks::plugin::PluginTroubleshooter`] for the check type that loads these
+use std::{
+    ffi::{CStr, CString, c_char},
+    path::Path,
+};
+
+use eyre::Context;
+use libloading::{Library, Symbol};
+
+use crate::utils::checks::CheckResult;
+
+type NameFn = unsafe extern "C" fn() -> *const c_char;
+type RunCheckFn = unsafe extern "C" fn(*const c_char) -> *mut c_char;
+type FreeStringFn = unsafe extern "C" fn(*mut c_char);
+
+/// A loaded plugin library, kept alive for as long as calls into it are made. jj does not
+/// sandbox plugins in any way; loading one runs arbitrary native code in-process
+pub struct Plugin {
+    library: Library,
+}
+
+impl Plugin {
+    /// Loads a plugin library from disk
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        let library = unsafe { Library::new(path) }
+            .with_context(|| format!("Could not load plugin library {}", path.display()))?;
+
+        Ok(Self { library })
+    }
+
+    /// The plugin's declared display name
+    pub fn name(&self) -> eyre::Result<String> {
+        unsafe {
+            let name_fn: Symbol<NameFn> = self
+                .library
+                .get(b"jj_plugin_name")
+                .context("Plugin does not export jj_plugin_name")?;
+
+            Ok(CStr::from_ptr(name_fn()).to_string_lossy().into_owned())
+        }
+    }
+
+    /// Runs the plugin's check, passing it `config_json` verbatim and parsing its response as
+    /// a [`CheckResult`]
+    pub fn run_check(&self, config_json: &str) -> eyre::Result<CheckResult> {
+        unsafe {
+            let run_fn: Symbol<RunCheckFn> = self
+                .library
+                .get(b"jj_plugin_run_check")
+                .context("Plugin does not export jj_plugin_run_check")?;
+            let free_fn: Symbol<FreeStringFn> = self
+                .library
+                .get(b"jj_plugin_free_string")
+                .context("Plugin does not export jj_plugin_free_string")?;
+
+            let config =
+                CString::new(config_json).context("Plugin config JSON contains a NUL byte")?;
+            let result_ptr = run_fn(config.as_ptr());
+            if result_ptr.is_null() {
+                eyre::bail!("Plugin returned a null result");
+            }
+
+            let result_json = CStr::from_ptr(result_ptr).to_string_lossy().into_owned();
+            free_fn(result_ptr);
+
+            serde_json::from_str(&result_json).context("Could not parse plugin result as JSON")
+        }
+    }
+}
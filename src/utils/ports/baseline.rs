@@ -0,0 +1,75 @@
+//! Baseline snapshots of listening sockets, used to alert when a new
+//! listener shows up that wasn't present the last time the baseline was
+//! recorded
+use std::{collections::HashSet, net::IpAddr, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::{SocketRecord, SocketState, SocketType};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BaselineListener {
+    pub socket_type: SocketType,
+    pub local_addr: IpAddr,
+    pub local_port: u16,
+    pub exe: Option<String>,
+}
+
+impl std::fmt::Display for BaselineListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}:{} ({})",
+            self.socket_type,
+            self.local_addr,
+            self.local_port,
+            self.exe.as_deref().unwrap_or("?")
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct PortBaseline {
+    listeners: HashSet<BaselineListener>,
+}
+
+impl PortBaseline {
+    /// Load a baseline from disk, returning an empty baseline if the file does not yet exist
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn from_sockets(sockets: &[SocketRecord]) -> Self {
+        Self {
+            listeners: sockets
+                .iter()
+                .filter(|s| s.state() == SocketState::Listen)
+                .map(|s| BaselineListener {
+                    socket_type: s.socket_type(),
+                    local_addr: s.local_addr(),
+                    local_port: s.local_port(),
+                    exe: s.exe().map(str::to_string),
+                })
+                .collect(),
+        }
+    }
+
+    /// Listeners present in `current` but not in this baseline
+    pub fn new_listeners(&self, current: &Self) -> Vec<BaselineListener> {
+        current
+            .listeners
+            .difference(&self.listeners)
+            .cloned()
+            .collect()
+    }
+}
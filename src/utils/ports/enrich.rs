@@ -0,0 +1,41 @@
+//! Optional enrichment of remote addresses shown by `jj ports`: reverse DNS
+//! and GeoIP lookups. Both are best-effort; a failed lookup just leaves the
+//! address unannotated rather than failing the whole command
+use std::{collections::HashMap, net::IpAddr, path::Path};
+
+/// Cache of reverse DNS lookups, since the same remote address often shows up
+/// across several connections
+#[derive(Default)]
+pub struct ReverseDnsResolver {
+    cache: HashMap<IpAddr, Option<String>>,
+}
+
+impl ReverseDnsResolver {
+    pub fn resolve(&mut self, addr: IpAddr) -> Option<String> {
+        self.cache
+            .entry(addr)
+            .or_insert_with(|| dns_lookup::lookup_addr(&addr).ok())
+            .clone()
+    }
+}
+
+pub struct GeoIpDatabase {
+    reader: maxminddb::Reader<Vec<u8>>,
+}
+
+impl GeoIpDatabase {
+    pub fn open(path: &Path) -> eyre::Result<Self> {
+        Ok(Self {
+            reader: maxminddb::Reader::open_readfile(path)?,
+        })
+    }
+
+    /// Look up the ISO country code for an address (e.g. "US"), if the database has an entry
+    pub fn country(&self, addr: IpAddr) -> Option<String> {
+        let country: maxminddb::geoip2::Country = self.reader.lookup(addr).ok()??;
+        country
+            .country?
+            .iso_code
+            .map(std::string::ToString::to_string)
+    }
+}
@@ -15,6 +15,8 @@ use num_traits::{Num, PrimInt};
 
 use super::SocketType;
 
+mod netlink;
+
 /// Mirrors the states [used internally](https://github.com/iproute2/iproute2/blob/ca756f36a0c6d24ab60657f8d14312c17443e5f0/misc/ss.c#L222-L238) for `ss`
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 #[repr(u8)]
@@ -100,6 +102,12 @@ pub struct LinuxSocketRecord {
     pub exe: Option<String>,
     pub cmdline: Option<String>,
     pub cgroup: Option<String>,
+    /// Bytes queued for transmission that have not yet been sent/acked
+    pub tx_queue: u32,
+    /// Bytes received but not yet read by the application
+    pub rx_queue: u32,
+    /// Number of unrecovered retransmissions for this connection
+    pub retransmits: u32,
 }
 
 impl super::OsSocketRecord for LinuxSocketRecord {
@@ -142,12 +150,33 @@ impl super::OsSocketRecord for LinuxSocketRecord {
 
 pub trait OsSocketRecordExt {
     fn cgroup(&self) -> Option<&str>;
+
+    /// Bytes queued for transmission that have not yet been sent/acked
+    fn tx_queue(&self) -> u32;
+
+    /// Bytes received but not yet read by the application
+    fn rx_queue(&self) -> u32;
+
+    /// Number of unrecovered retransmissions for this connection
+    fn retransmits(&self) -> u32;
 }
 
 impl OsSocketRecordExt for super::SocketRecord {
     fn cgroup(&self) -> Option<&str> {
         self.inner.cgroup.as_deref()
     }
+
+    fn tx_queue(&self) -> u32 {
+        self.inner.tx_queue
+    }
+
+    fn rx_queue(&self) -> u32 {
+        self.inner.rx_queue
+    }
+
+    fn retransmits(&self) -> u32 {
+        self.inner.retransmits
+    }
 }
 
 /// Returns a mapping of inodes to the process ID that has the inode
@@ -266,11 +295,11 @@ where
                 rem_addr,
                 rem_port,
                 stat,
-                _tx_queue,
-                _rx_queue,
+                tx_queue,
+                rx_queue,
                 _tr,
                 _tmwhen,
-                _retrnsmt,
+                retrnsmt,
                 _uid,
                 _timeout,
                 inode,
@@ -289,6 +318,9 @@ where
                     remote_port: u16::from_str_radix(rem_port, 16)?,
                     state: u8::from_str_radix(stat, 16)?.into(),
                     inode,
+                    tx_queue: u32::from_str_radix(tx_queue, 16)?,
+                    rx_queue: u32::from_str_radix(rx_queue, 16)?,
+                    retransmits: u32::from_str_radix(retrnsmt, 16)?,
                     pid: None,
                     exe: None,
                     cmdline: None,
@@ -366,9 +398,55 @@ where
     Ok(enrich_ip_stats(ip_stats, &inode_pids))
 }
 
+/// Converts a netlink `sock_diag` reply into a `LinuxSocketRecord`, leaving the `/proc`-derived
+/// fields unset; callers are expected to run the result through [`enrich_ip_stats`]
+impl From<netlink::NetlinkSocketInfo> for LinuxSocketRecord {
+    fn from(info: netlink::NetlinkSocketInfo) -> Self {
+        Self {
+            socket_type: info.socket_type,
+            local_address: info.local_addr,
+            local_port: info.local_port,
+            remote_address: info.remote_addr,
+            remote_port: info.remote_port,
+            state: info.state.into(),
+            inode: info.inode,
+            // Retransmit counts live behind the INET_DIAG_INFO extension attribute, which we
+            // don't request; 0 here just means "unknown", same as the proc parser on a row it
+            // can't read
+            retransmits: 0,
+            tx_queue: info.tx_queue,
+            rx_queue: info.rx_queue,
+            pid: None,
+            exe: None,
+            cmdline: None,
+            cgroup: None,
+        }
+    }
+}
+
+/// Tries the `NETLINK_SOCK_DIAG` fast path for every socket matching `filter` (or every socket,
+/// if `None`), enriching it with process info the same way the `/proc/net` parser does. Returns
+/// `None` if the netlink query itself failed, so the caller can fall back to the text parser
+fn parse_ports_netlink(filter: Option<SocketType>) -> Option<Vec<LinuxSocketRecord>> {
+    let raw = netlink::query_all()?;
+    let inode_pids = socket_inodes().unwrap_or_default();
+
+    Some(enrich_ip_stats(
+        raw.into_iter()
+            .filter(|info| filter.is_none_or(|f| f == info.socket_type))
+            .map(LinuxSocketRecord::from)
+            .collect(),
+        &inode_pids,
+    ))
+}
+
 /// Shortcut to parse statistics from /proc/net/tcp
 #[allow(dead_code)]
 pub fn parse_net_tcp() -> eyre::Result<Vec<LinuxSocketRecord>> {
+    if let Some(records) = parse_ports_netlink(Some(SocketType::Tcp)) {
+        return Ok(records);
+    }
+
     Ok([
         parse_ip_stats::<_, Ipv4Addr>("/proc/net/tcp", SocketType::Tcp)?,
         parse_ip_stats::<_, Ipv6Addr>("/proc/net/tcp6", SocketType::Tcp)?,
@@ -379,6 +457,10 @@ pub fn parse_net_tcp() -> eyre::Result<Vec<LinuxSocketRecord>> {
 /// Shortcut to parse statistics from /proc/net/udp
 #[allow(dead_code)]
 pub fn parse_net_udp() -> eyre::Result<Vec<LinuxSocketRecord>> {
+    if let Some(records) = parse_ports_netlink(Some(SocketType::Udp)) {
+        return Ok(records);
+    }
+
     Ok([
         parse_ip_stats::<_, Ipv4Addr>("/proc/net/udp", SocketType::Udp)?,
         parse_ip_stats::<_, Ipv6Addr>("/proc/net/udp6", SocketType::Udp)?,
@@ -388,6 +470,10 @@ pub fn parse_net_udp() -> eyre::Result<Vec<LinuxSocketRecord>> {
 
 /// Shortcut to parse statistics from both /proc/net/tcp and /proc/net/udp
 pub fn parse_ports() -> eyre::Result<Vec<LinuxSocketRecord>> {
+    if let Some(records) = parse_ports_netlink(None) {
+        return Ok(records);
+    }
+
     Ok([
         parse_ip_stats::<_, Ipv4Addr>("/proc/net/tcp", SocketType::Tcp)?,
         parse_ip_stats::<_, Ipv4Addr>("/proc/net/udp", SocketType::Udp)?,
@@ -0,0 +1,247 @@
+//! A raw `AF_NETLINK`/`NETLINK_SOCK_DIAG` client that dumps every open TCP/UDP socket (with its
+//! UID and `/proc/<pid>/fd` inode) in a handful of syscalls, instead of parsing
+//! `/proc/net/{tcp,udp}{,6}` text tables. Used as the fast path by [`super::linux::parse_ports`]
+//! and friends, which fall back to the text parser if this returns `None`.
+//!
+//! The `inet_diag` wire structs below mirror `<linux/inet_diag.h>`, which `libc` doesn't wrap
+//! (it only exposes the generic `nlmsghdr`/`sockaddr_nl` netlink plumbing).
+
+use std::{
+    io,
+    mem::size_of,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+};
+
+use super::SocketType;
+
+const SOCK_DIAG_BY_FAMILY: u16 = 20;
+
+/// Bitmask matching every value `idiag_state` can take; `ss` uses the same "all bits set"
+/// shortcut instead of enumerating every `TCPF_*` state
+const ALL_STATES: u32 = u32::MAX;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct InetDiagSockId {
+    sport: u16,
+    dport: u16,
+    src: [u32; 4],
+    dst: [u32; 4],
+    interface: u32,
+    cookie: [u32; 2],
+}
+
+#[repr(C)]
+struct InetDiagReqV2 {
+    family: u8,
+    protocol: u8,
+    ext: u8,
+    pad: u8,
+    states: u32,
+    id: InetDiagSockId,
+}
+
+#[repr(C)]
+struct InetDiagMsg {
+    family: u8,
+    state: u8,
+    timer: u8,
+    retrans: u8,
+    id: InetDiagSockId,
+    expires: u32,
+    rqueue: u32,
+    wqueue: u32,
+    uid: u32,
+    inode: u32,
+}
+
+/// One socket as reported by the kernel over `NETLINK_SOCK_DIAG`. Mirrors the fields
+/// [`super::linux::LinuxSocketRecord`] can fill in without walking `/proc`
+#[allow(dead_code)]
+pub struct NetlinkSocketInfo {
+    pub socket_type: SocketType,
+    pub local_addr: IpAddr,
+    pub local_port: u16,
+    pub remote_addr: IpAddr,
+    pub remote_port: u16,
+    pub state: u8,
+    pub uid: u32,
+    pub inode: u64,
+    pub tx_queue: u32,
+    pub rx_queue: u32,
+}
+
+fn open_socket() -> io::Result<OwnedFd> {
+    let raw = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+            libc::NETLINK_SOCK_DIAG,
+        )
+    };
+    if raw < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+
+    // Only the `nl_family` field matters for a request socket; zeroing the rest asks the
+    // kernel to pick our `nl_pid` and leaves us in no multicast groups
+    let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+    addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+
+    let bound = unsafe {
+        libc::bind(
+            fd.as_raw_fd(),
+            (&raw const addr).cast(),
+            size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        )
+    };
+    if bound < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(fd)
+}
+
+fn send_dump_request(fd: &OwnedFd, family: u8, protocol: u8) -> io::Result<()> {
+    #[repr(C)]
+    struct Request {
+        header: libc::nlmsghdr,
+        body: InetDiagReqV2,
+    }
+
+    let request = Request {
+        header: libc::nlmsghdr {
+            nlmsg_len: size_of::<Request>() as u32,
+            nlmsg_type: SOCK_DIAG_BY_FAMILY,
+            nlmsg_flags: (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16,
+            nlmsg_seq: 1,
+            nlmsg_pid: 0,
+        },
+        body: InetDiagReqV2 {
+            family,
+            protocol,
+            ext: 0,
+            pad: 0,
+            states: ALL_STATES,
+            id: InetDiagSockId {
+                sport: 0,
+                dport: 0,
+                src: [0; 4],
+                dst: [0; 4],
+                interface: 0,
+                cookie: [0; 2],
+            },
+        },
+    };
+
+    let bytes = unsafe {
+        std::slice::from_raw_parts((&raw const request).cast::<u8>(), size_of::<Request>())
+    };
+
+    let sent = unsafe { libc::send(fd.as_raw_fd(), bytes.as_ptr().cast(), bytes.len(), 0) };
+    if sent < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+fn addr_from_be32s(words: [u32; 4], is_v6: bool) -> IpAddr {
+    if is_v6 {
+        let mut octets = [0u8; 16];
+        for (i, word) in words.iter().enumerate() {
+            octets[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+        }
+        IpAddr::V6(Ipv6Addr::from(octets))
+    } else {
+        IpAddr::V4(Ipv4Addr::from(words[0].to_be()))
+    }
+}
+
+/// Dumps every socket of `socket_type` for `family` (`AF_INET`/`AF_INET6`) in one
+/// `NETLINK_SOCK_DIAG` request, draining replies until the kernel sends `NLMSG_DONE`
+fn dump(socket_type: SocketType, family: u8) -> io::Result<Vec<NetlinkSocketInfo>> {
+    let fd = open_socket()?;
+    let protocol = match socket_type {
+        SocketType::Tcp => libc::IPPROTO_TCP as u8,
+        SocketType::Udp => libc::IPPROTO_UDP as u8,
+    };
+    send_dump_request(&fd, family, protocol)?;
+
+    let mut results = Vec::new();
+    let mut buf = vec![0u8; 16 * 1024];
+
+    'recv: loop {
+        let received = unsafe { libc::recv(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len(), 0) };
+        if received < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let received = received as usize;
+
+        let mut offset = 0usize;
+        while offset + size_of::<libc::nlmsghdr>() <= received {
+            let header = unsafe { &*(buf.as_ptr().add(offset).cast::<libc::nlmsghdr>()) };
+            let msg_len = header.nlmsg_len as usize;
+            if msg_len < size_of::<libc::nlmsghdr>() || offset + msg_len > received {
+                break;
+            }
+
+            match header.nlmsg_type {
+                t if t == libc::NLMSG_DONE as u16 => break 'recv,
+                t if t == libc::NLMSG_ERROR as u16 => {
+                    return Err(io::Error::from_raw_os_error(libc::EIO));
+                }
+                SOCK_DIAG_BY_FAMILY => {
+                    let payload = offset + size_of::<libc::nlmsghdr>();
+                    if payload + size_of::<InetDiagMsg>() <= received {
+                        let msg = unsafe { &*(buf.as_ptr().add(payload).cast::<InetDiagMsg>()) };
+                        let is_v6 = family == libc::AF_INET6 as u8;
+                        results.push(NetlinkSocketInfo {
+                            socket_type,
+                            local_addr: addr_from_be32s(msg.id.src, is_v6),
+                            local_port: u16::from_be(msg.id.sport),
+                            remote_addr: addr_from_be32s(msg.id.dst, is_v6),
+                            remote_port: u16::from_be(msg.id.dport),
+                            state: msg.state,
+                            uid: msg.uid,
+                            inode: u64::from(msg.inode),
+                            tx_queue: msg.wqueue,
+                            rx_queue: msg.rqueue,
+                        });
+                    }
+                }
+                _ => {}
+            }
+
+            // The kernel already pads nlmsg_len to NLMSG_ALIGNTO
+            offset += msg_len;
+        }
+    }
+
+    Ok(results)
+}
+
+/// Queries the kernel for every open TCP/UDP socket (v4 and v6) over `NETLINK_SOCK_DIAG`.
+/// Returns `None` (after printing a warning) if the sandbox/kernel doesn't support it, so
+/// callers can fall back to parsing `/proc/net/{tcp,udp}{,6}`
+pub fn query_all() -> Option<Vec<NetlinkSocketInfo>> {
+    let mut results = Vec::new();
+
+    for socket_type in [SocketType::Tcp, SocketType::Udp] {
+        for family in [libc::AF_INET as u8, libc::AF_INET6 as u8] {
+            match dump(socket_type, family) {
+                Ok(mut rows) => results.append(&mut rows),
+                Err(e) => {
+                    eprintln!(
+                        "warning: NETLINK_SOCK_DIAG query failed ({e}), falling back to /proc/net parsing"
+                    );
+                    return None;
+                }
+            }
+        }
+    }
+
+    Some(results)
+}
@@ -0,0 +1,129 @@
+//! macOS socket enumeration, built on the cross-platform `netstat2` crate (itself backed by
+//! `libproc`/`sysctl` on this platform) plus `libproc` directly to resolve each socket's owning
+//! PID to an executable path
+
+use std::{collections::HashMap, net::IpAddr};
+
+use netstat2::{AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo, TcpState};
+
+use super::{SocketState, SocketType};
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct MacosSocketRecord {
+    pub socket_type: SocketType,
+    pub local_address: IpAddr,
+    pub local_port: u16,
+    pub remote_address: Option<IpAddr>,
+    pub remote_port: Option<u16>,
+    pub state: SocketState,
+    pub pid: Option<u64>,
+    pub exe: Option<String>,
+}
+
+impl super::OsSocketRecord for MacosSocketRecord {
+    fn socket_type(&self) -> SocketType {
+        self.socket_type
+    }
+
+    fn local_addr(&self) -> IpAddr {
+        self.local_address
+    }
+
+    fn local_port(&self) -> u16 {
+        self.local_port
+    }
+
+    fn remote_addr(&self) -> Option<IpAddr> {
+        self.remote_address
+    }
+
+    fn remote_port(&self) -> Option<u16> {
+        self.remote_port
+    }
+
+    fn state(&self) -> SocketState {
+        self.state
+    }
+
+    fn pid(&self) -> Option<u64> {
+        self.pid
+    }
+
+    fn cmdline(&self) -> Option<&str> {
+        self.exe.as_deref()
+    }
+
+    fn exe(&self) -> Option<&str> {
+        self.exe.as_deref()
+    }
+}
+
+impl From<TcpState> for SocketState {
+    fn from(value: TcpState) -> Self {
+        match value {
+            TcpState::Closed => Self::Closed,
+            TcpState::Listen => Self::Listen,
+            TcpState::SynSent => Self::SynSent,
+            TcpState::SynReceived => Self::SynRecv,
+            TcpState::Established => Self::Established,
+            TcpState::FinWait1 => Self::FinWait1,
+            TcpState::FinWait2 => Self::FinWait2,
+            TcpState::CloseWait => Self::CloseWait,
+            TcpState::Closing => Self::Closing,
+            TcpState::LastAck => Self::LastAck,
+            TcpState::TimeWait => Self::TimeWait,
+            TcpState::DeleteTcb | TcpState::Unknown => Self::Unknown,
+        }
+    }
+}
+
+/// Resolves a PID to the path of its executable, caching lookups since the same process usually
+/// owns several sockets
+fn exe_for_pid(cache: &mut HashMap<u32, Option<String>>, pid: u32) -> Option<String> {
+    cache
+        .entry(pid)
+        .or_insert_with(|| libproc::proc_pid::pidpath(pid as i32).ok())
+        .clone()
+}
+
+pub fn list_ports() -> eyre::Result<Vec<MacosSocketRecord>> {
+    let sockets_info = netstat2::get_sockets_info(
+        AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6,
+        ProtocolFlags::TCP | ProtocolFlags::UDP,
+    )
+    .map_err(|e| eyre::eyre!("Could not enumerate sockets: {e}"))?;
+
+    let mut exe_cache = HashMap::new();
+
+    Ok(sockets_info
+        .into_iter()
+        .map(|si| {
+            let pid = si.associated_pids.first().copied();
+            let exe = pid.and_then(|p| exe_for_pid(&mut exe_cache, p));
+
+            match si.protocol_socket_info {
+                ProtocolSocketInfo::Tcp(tcp) => MacosSocketRecord {
+                    socket_type: SocketType::Tcp,
+                    local_address: tcp.local_addr,
+                    local_port: tcp.local_port,
+                    remote_address: Some(tcp.remote_addr).filter(|a| !a.is_unspecified()),
+                    remote_port: Some(tcp.remote_port).filter(|p| *p != 0),
+                    state: tcp.state.into(),
+                    pid: pid.map(u64::from),
+                    exe,
+                },
+                ProtocolSocketInfo::Udp(udp) => MacosSocketRecord {
+                    socket_type: SocketType::Udp,
+                    local_address: udp.local_addr,
+                    local_port: udp.local_port,
+                    remote_address: None,
+                    remote_port: None,
+                    state: SocketState::Unknown,
+                    pid: pid.map(u64::from),
+                    exe,
+                },
+            }
+        })
+        .collect())
+}
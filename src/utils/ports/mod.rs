@@ -1,15 +1,21 @@
 use std::net::IpAddr;
 
+use serde::{Deserialize, Serialize};
+
 /// Used to differentiate socket records, as records from multiple
 /// files in /proc might be mixed together
 /// Or for Windows, multiple sockets from GetExtendedTcpTable or
 /// GetExtendedUdpTable
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SocketType {
     Tcp,
     Udp,
 }
 
+pub mod baseline;
+pub mod enrich;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SocketState {
     Unknown,
@@ -50,6 +56,12 @@ pub mod windows;
 #[cfg(windows)]
 use windows::WindowsSocketRecord as OsSocketRecordImpl;
 
+#[cfg(target_os = "macos")]
+pub mod macos;
+
+#[cfg(target_os = "macos")]
+use macos::MacosSocketRecord as OsSocketRecordImpl;
+
 /// Trait to generalize and abstract over socket records for different
 /// operating systems
 pub trait OsSocketRecord {
@@ -181,6 +193,11 @@ pub fn list_ports() -> eyre::Result<Vec<SocketRecord>> {
     windows::list_ports().map(|p| p.into_iter().map(|inner| SocketRecord { inner }).collect())
 }
 
+#[cfg(target_os = "macos")]
+pub fn list_ports() -> eyre::Result<Vec<SocketRecord>> {
+    macos::list_ports().map(|p| p.into_iter().map(|inner| SocketRecord { inner }).collect())
+}
+
 impl std::fmt::Display for SocketType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
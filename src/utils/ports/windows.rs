@@ -14,9 +14,15 @@ use windows::Win32::{
     Networking::WinSock::{AF_INET, AF_INET6},
     System::{
         ProcessStatus::GetProcessImageFileNameA,
+        Services::{
+            ENUM_SERVICE_STATUS_PROCESSW, EnumServicesStatusExW, OpenSCManagerW,
+            SC_ENUM_PROCESS_INFO, SC_MANAGER_CONNECT, SC_MANAGER_ENUMERATE_SERVICE,
+            SERVICE_STATE_ALL, SERVICE_WIN32,
+        },
         Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION},
     },
 };
+use windows_core::PCWSTR;
 
 use super::{SocketState, SocketType};
 
@@ -32,6 +38,96 @@ pub struct WindowsSocketRecord {
     pub pid: Option<u32>,
     pub image: Option<Arc<str>>,
     pub cmdline: Option<Arc<str>>,
+    /// Display names of the services hosted in this PID, resolved via the SCM. Mostly useful
+    /// for `svchost.exe`, which multiplexes many unrelated services into one process
+    pub service_names: Option<Arc<str>>,
+}
+
+/// Extension trait for data that is only available on Windows
+pub trait OsSocketRecordExt {
+    fn service_names(&self) -> Option<&str>;
+}
+
+impl OsSocketRecordExt for super::SocketRecord {
+    fn service_names(&self) -> Option<&str> {
+        self.inner.service_names.as_deref()
+    }
+}
+
+/// Resolve every running service's display name, grouped by the PID hosting it
+///
+/// A single process (most commonly `svchost.exe`) can host several unrelated services, so this
+/// returns a `Vec` of names per PID rather than a single name
+unsafe fn get_services_by_pid() -> eyre::Result<HashMap<u32, Vec<String>>> {
+    let scm = OpenSCManagerW(
+        PCWSTR::null(),
+        PCWSTR::null(),
+        SC_MANAGER_CONNECT | SC_MANAGER_ENUMERATE_SERVICE,
+    )?;
+
+    if scm.0.is_null() {
+        eyre::bail!(
+            "Could not open connection to sc manager: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+
+    let mut buffer = vec![0u8; 4096];
+    let mut bytes_needed = 0u32;
+    let mut services_returned = 0u32;
+    let mut resume_handle = 0u32;
+
+    let res = EnumServicesStatusExW(
+        scm,
+        SC_ENUM_PROCESS_INFO,
+        SERVICE_WIN32,
+        SERVICE_STATE_ALL,
+        Some(&mut buffer),
+        &mut bytes_needed,
+        &mut services_returned,
+        Some(&mut resume_handle),
+        None,
+    );
+
+    if let Err(e) = &res
+        && e.code() == ERROR_INSUFFICIENT_BUFFER.into()
+    {
+        buffer = vec![0u8; bytes_needed as usize];
+        EnumServicesStatusExW(
+            scm,
+            SC_ENUM_PROCESS_INFO,
+            SERVICE_WIN32,
+            SERVICE_STATE_ALL,
+            Some(&mut buffer),
+            &mut bytes_needed,
+            &mut services_returned,
+            Some(&mut resume_handle),
+            None,
+        )?;
+    } else {
+        res?;
+    }
+
+    let entries = std::slice::from_raw_parts(
+        buffer.as_ptr() as *const ENUM_SERVICE_STATUS_PROCESSW,
+        services_returned as usize,
+    );
+
+    let mut by_pid: HashMap<u32, Vec<String>> = HashMap::new();
+
+    for entry in entries {
+        let pid = entry.ServiceStatusProcess.dwProcessId;
+        if pid == 0 || entry.lpDisplayName.is_null() {
+            continue;
+        }
+
+        by_pid
+            .entry(pid)
+            .or_default()
+            .push(entry.lpDisplayName.to_string().unwrap_or_default());
+    }
+
+    Ok(by_pid)
 }
 
 impl super::OsSocketRecord for WindowsSocketRecord {
@@ -117,8 +213,13 @@ fn get_state(dwState: u32) -> SocketState {
     }
 }
 
+fn join_service_names(svc_by_pid: &HashMap<u32, Vec<String>>, pid: u32) -> Option<Arc<str>> {
+    svc_by_pid.get(&pid).map(|names| names.join(", ").into())
+}
+
 unsafe fn get_tcp_ports(
     proc_list: &mut HashMap<u32, WinProc>,
+    svc_by_pid: &HashMap<u32, Vec<String>>,
 ) -> eyre::Result<Vec<WindowsSocketRecord>> {
     let mut tcptable = vec![0; 4096];
     let mut size = 4096u32;
@@ -196,6 +297,7 @@ unsafe fn get_tcp_ports(
                 state: get_state(entry.dwState),
                 cmdline,
                 image,
+                service_names: join_service_names(svc_by_pid, entry.dwOwningPid),
             })
         })
         .collect())
@@ -203,6 +305,7 @@ unsafe fn get_tcp_ports(
 
 unsafe fn get_tcp6_ports(
     proc_list: &mut HashMap<u32, WinProc>,
+    svc_by_pid: &HashMap<u32, Vec<String>>,
 ) -> eyre::Result<Vec<WindowsSocketRecord>> {
     let mut tcptable = vec![0; 4096];
     let mut size = 4096u32;
@@ -280,6 +383,7 @@ unsafe fn get_tcp6_ports(
                 state: get_state(entry.dwState),
                 cmdline,
                 image,
+                service_names: join_service_names(svc_by_pid, entry.dwOwningPid),
             })
         })
         .collect())
@@ -287,6 +391,7 @@ unsafe fn get_tcp6_ports(
 
 unsafe fn get_udp_ports(
     proc_list: &mut HashMap<u32, WinProc>,
+    svc_by_pid: &HashMap<u32, Vec<String>>,
 ) -> eyre::Result<Vec<WindowsSocketRecord>> {
     let mut udptable = vec![0; 4096];
     let mut size = 4096u32;
@@ -358,6 +463,7 @@ unsafe fn get_udp_ports(
                 state: SocketState::Unknown,
                 cmdline,
                 image,
+                service_names: join_service_names(svc_by_pid, entry.dwOwningPid),
             })
         })
         .collect())
@@ -365,6 +471,7 @@ unsafe fn get_udp_ports(
 
 unsafe fn get_udp6_ports(
     proc_list: &mut HashMap<u32, WinProc>,
+    svc_by_pid: &HashMap<u32, Vec<String>>,
 ) -> eyre::Result<Vec<WindowsSocketRecord>> {
     let mut udptable = vec![0; 4096];
     let mut size = 4096u32;
@@ -436,6 +543,7 @@ unsafe fn get_udp6_ports(
                 state: SocketState::Unknown,
                 cmdline,
                 image,
+                service_names: join_service_names(svc_by_pid, entry.dwOwningPid),
             })
         })
         .collect())
@@ -445,10 +553,14 @@ pub fn list_ports() -> eyre::Result<Vec<WindowsSocketRecord>> {
     let mut procs = HashMap::new();
 
     unsafe {
-        let tcp4 = get_tcp_ports(&mut procs)?;
-        let tcp6 = get_tcp6_ports(&mut procs)?;
-        let udp4 = get_udp_ports(&mut procs)?;
-        let udp6 = get_udp6_ports(&mut procs)?;
+        // Service name resolution is best-effort; don't fail the whole listing if the SCM
+        // can't be reached (e.g. insufficient privileges)
+        let svc_by_pid = get_services_by_pid().unwrap_or_default();
+
+        let tcp4 = get_tcp_ports(&mut procs, &svc_by_pid)?;
+        let tcp6 = get_tcp6_ports(&mut procs, &svc_by_pid)?;
+        let udp4 = get_udp_ports(&mut procs, &svc_by_pid)?;
+        let udp6 = get_udp6_ports(&mut procs, &svc_by_pid)?;
 
         Ok([tcp4, tcp6, udp4, udp6].concat())
     }
@@ -0,0 +1,59 @@
+//! Shared helper for commands that need root: detects when it's missing, explains why, and
+//! offers to re-exec the current invocation under `sudo`/`doas` instead of failing midway
+//! through with an opaque EPERM
+
+use std::{
+    io::{BufRead, Write},
+    os::unix::process::CommandExt,
+};
+
+use colored::Colorize;
+use eyre::Context;
+use nix::unistd::geteuid;
+
+use super::qx;
+
+/// Is the current process running as root
+pub fn is_root() -> bool {
+    geteuid().is_root()
+}
+
+/// Checks that the process is running as root, explaining `reason` (e.g. "apply firewall
+/// rules") and offering to re-exec the current invocation under `sudo` or `doas` if it isn't.
+///
+/// Falls back to the usual `You must be root to <reason>` error if no elevation tool is found
+/// on `PATH` or the user declines
+pub fn require_root(reason: &str) -> eyre::Result<()> {
+    if is_root() {
+        return Ok(());
+    }
+
+    let Some(elevator) = ["sudo", "doas"]
+        .into_iter()
+        .find(|cmd| qx(&format!("command -v {cmd}")).is_ok_and(|(status, _)| status.success()))
+    else {
+        eyre::bail!("You must be root to {reason}");
+    };
+
+    print!(
+        "{} {reason}, which requires root. Re-run with {elevator}? [y/N] ",
+        "This needs to".yellow()
+    );
+    std::io::stdout().flush()?;
+
+    let mut input = String::new();
+    std::io::stdin().lock().read_line(&mut input)?;
+
+    if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+        eyre::bail!("You must be root to {reason}");
+    }
+
+    let exe = std::env::current_exe().context("Could not determine the current executable path")?;
+
+    let err = std::process::Command::new(elevator)
+        .arg(exe)
+        .args(std::env::args_os().skip(1))
+        .exec();
+
+    Err(err).with_context(|| format!("Failed to re-exec under {elevator}"))
+}
@@ -1,4 +1,24 @@
-//! Useful Regex constants for use with `pcre!`
+//! Useful Regex constants for use with `pcre!`, plus a little glue for `pcre_fancy!`
+//! (lookarounds, backreferences) to read the same way at call sites
+
+/// Adds [`regex::Captures::extract`]'s ergonomics to `fancy_regex::Captures`, so code reached
+/// through [`crate::pcre_fancy!`] can destructure a match the same way as code using
+/// [`crate::pcre!`]: `caps.extract::<2>().1`
+pub trait FancyCapturesExt {
+    fn extract<const N: usize>(&self) -> (&str, [&str; N]);
+}
+
+impl FancyCapturesExt for fancy_regex::Captures<'_> {
+    fn extract<const N: usize>(&self) -> (&str, [&str; N]) {
+        let whole = self.get(0).map(|m| m.as_str()).unwrap_or_default();
+        let mut groups = [""; N];
+        for (i, group) in groups.iter_mut().enumerate() {
+            *group = self.get(i + 1).map(|m| m.as_str()).unwrap_or_default();
+        }
+
+        (whole, groups)
+    }
+}
 
 pub const DEC: &str = "[0-9]+";
 pub const HEX: &str = "[0-9A-F]+";
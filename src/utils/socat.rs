@@ -0,0 +1,91 @@
+//! Provides a handle for a bundled copy of socat, for port forwarding, relays, and quick
+//! listeners on boxes that don't have any usable netcat
+//!
+//! ```no_run
+//! # // don't run the unit test to open a listener...
+//! # use jj_rs::utils::socat::Socat;
+//! # fn test_socat() -> eyre::Result<()> {
+//! let socat = Socat::new()?;
+//! socat
+//!     .command()
+//!     .args(["tcp-listen:4444,reuseaddr,fork", "exec:/bin/sh"])
+//!     .spawn()?
+//!     .wait()?;
+//! # Ok(())
+//! # }
+//! # test_socat().expect("could not run socat test");
+//! ```
+use std::{
+    fs::File,
+    io::prelude::*,
+    os::fd::{AsRawFd, FromRawFd, IntoRawFd},
+    process::Command,
+};
+
+use eyre::Context;
+#[cfg(feature = "bundled-tools")]
+use flate2::write::GzDecoder;
+use nix::sys::memfd::{MFdFlags, memfd_create};
+
+#[cfg(feature = "bundled-tools")]
+pub(crate) const SOCAT_BYTES_X86_64: &[u8] = include_bytes!(std::env!("SOCAT_GZIPPED_X86_64"));
+#[cfg(feature = "bundled-tools")]
+pub(crate) const SOCAT_BYTES_AARCH64: &[u8] = include_bytes!(std::env!("SOCAT_GZIPPED_AARCH64"));
+
+/// Expected SHA-256 hashes of the gzipped payloads above, baked in at build time so `jj verify`
+/// can detect a tampered binary
+#[cfg(feature = "bundled-tools")]
+pub(crate) const SOCAT_SHA256_X86_64: &str = std::env!("SOCAT_SHA256_X86_64");
+#[cfg(feature = "bundled-tools")]
+pub(crate) const SOCAT_SHA256_AARCH64: &str = std::env!("SOCAT_SHA256_AARCH64");
+
+/// Handle around the `socat` binary
+pub struct Socat {
+    socat_file: File,
+}
+
+impl Socat {
+    /// Create a new socat handle that can be used later to set up relays and listeners
+    pub fn new() -> eyre::Result<Self> {
+        let temp_fd =
+            memfd_create("", MFdFlags::empty()).context("Could not create memory file")?;
+
+        let fd = temp_fd.into_raw_fd();
+
+        let mut temp_file = unsafe { File::from_raw_fd(fd) };
+
+        #[cfg(feature = "bundled-tools")]
+        {
+            let socat_bytes = crate::utils::embedded_tool_bytes_for_current_arch(
+                SOCAT_BYTES_X86_64,
+                SOCAT_BYTES_AARCH64,
+            )?;
+
+            let mut decoder = GzDecoder::new(temp_file);
+            decoder
+                .write_all(socat_bytes)
+                .context("Could not write all socat bytes")?;
+            temp_file = decoder
+                .finish()
+                .context("Could not finish writing decompressing socat")?;
+        }
+
+        #[cfg(not(feature = "bundled-tools"))]
+        {
+            let socat_bytes = crate::utils::fetch_tool_bytes("socat")?;
+            temp_file
+                .write_all(&socat_bytes)
+                .context("Could not write all socat bytes")?;
+        }
+
+        Ok(Self {
+            socat_file: temp_file,
+        })
+    }
+
+    /// Create a new [`std::process::Command`] object to perform further
+    /// customization around later
+    pub fn command(&self) -> Command {
+        Command::new(format!("/proc/self/fd/{}", self.socat_file.as_raw_fd()))
+    }
+}
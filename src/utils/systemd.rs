@@ -1,12 +1,130 @@
-//! Utilities for interacting with systemd services outside some basic
-//! `system` invocations
+//! Utilities for interacting with systemd services, preferring a direct connection to
+//! `org.freedesktop.systemd1` over the system D-Bus over shelling out to `systemctl`. The old
+//! `systemctl show` parser is kept as a fallback for hosts where the system bus isn't reachable
+//! (containers without dbus, non-systemd `unix` targets like macOS, etc.)
 
-use std::collections::HashMap;
+use std::{collections::HashMap, future::Future, time::Duration};
 
 use eyre::Context;
+use futures_util::StreamExt;
 
 use crate::{pcre, utils::qx};
 
+mod dbus {
+    use zbus::{proxy, zvariant::OwnedObjectPath};
+
+    /// The 10-tuple `ListUnits`/`ListUnitsByNames` returns per unit: name, description,
+    /// load state, active state, sub state, "following" unit, unit object path, queued job id,
+    /// job type, and job object path
+    pub type UnitStatus = (
+        String,
+        String,
+        String,
+        String,
+        String,
+        String,
+        OwnedObjectPath,
+        u32,
+        String,
+        OwnedObjectPath,
+    );
+
+    #[proxy(
+        interface = "org.freedesktop.systemd1.Manager",
+        default_service = "org.freedesktop.systemd1",
+        default_path = "/org/freedesktop/systemd1"
+    )]
+    pub trait Manager {
+        fn load_unit(&self, name: &str) -> zbus::Result<OwnedObjectPath>;
+        fn start_unit(&self, name: &str, mode: &str) -> zbus::Result<OwnedObjectPath>;
+        fn stop_unit(&self, name: &str, mode: &str) -> zbus::Result<OwnedObjectPath>;
+        fn list_units(&self) -> zbus::Result<Vec<UnitStatus>>;
+        fn subscribe(&self) -> zbus::Result<()>;
+    }
+
+    #[proxy(
+        interface = "org.freedesktop.systemd1.Unit",
+        default_service = "org.freedesktop.systemd1"
+    )]
+    pub trait Unit {
+        #[zbus(property)]
+        fn active_state(&self) -> zbus::Result<String>;
+        #[zbus(property)]
+        fn sub_state(&self) -> zbus::Result<String>;
+        #[zbus(property)]
+        fn load_state(&self) -> zbus::Result<String>;
+        #[zbus(property, name = "MainPID")]
+        fn main_pid(&self) -> zbus::Result<u32>;
+        #[zbus(property)]
+        fn exec_main_start_timestamp(&self) -> zbus::Result<u64>;
+        #[zbus(property)]
+        fn inactive_enter_timestamp(&self) -> zbus::Result<u64>;
+    }
+}
+
+/// How long we give the system bus to answer a query before giving up on it for this call and
+/// falling back to `systemctl show`
+const DBUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runs `fut` to completion on a fresh single-threaded runtime; [`get_service_info`] and friends
+/// are called from synchronous check code, not from inside jj's own async commands
+fn block_on<T>(fut: impl Future<Output = eyre::Result<T>>) -> eyre::Result<T> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Could not start a runtime to talk to the system bus on")?;
+    rt.block_on(fut)
+}
+
+async fn unit_proxy(
+    connection: &zbus::Connection,
+    manager: &dbus::ManagerProxy<'_>,
+    unit: &str,
+) -> eyre::Result<dbus::UnitProxy<'static>> {
+    let unit_path = manager
+        .load_unit(unit)
+        .await
+        .with_context(|| format!("Could not load unit {unit}"))?;
+
+    dbus::UnitProxy::builder(connection)
+        .path(unit_path)?
+        .build()
+        .await
+        .with_context(|| format!("Could not open a D-Bus proxy for unit {unit}"))
+}
+
+async fn unit_properties(service: &str) -> eyre::Result<HashMap<String, String>> {
+    let connection = zbus::Connection::system()
+        .await
+        .context("Could not connect to the D-Bus system bus")?;
+    let manager = dbus::ManagerProxy::new(&connection)
+        .await
+        .context("Could not open the systemd1 manager proxy")?;
+    let unit = unit_proxy(&connection, &manager, service).await?;
+
+    let mut info = HashMap::new();
+    if let Ok(v) = unit.active_state().await {
+        info.insert("ActiveState".to_string(), v);
+    }
+    if let Ok(v) = unit.sub_state().await {
+        info.insert("SubState".to_string(), v);
+    }
+    if let Ok(v) = unit.load_state().await {
+        info.insert("LoadState".to_string(), v);
+    }
+    if let Ok(v) = unit.main_pid().await {
+        info.insert("MainPID".to_string(), v.to_string());
+    }
+    if let Ok(v) = unit.exec_main_start_timestamp().await {
+        info.insert("ExecMainStartTimestamp".to_string(), v.to_string());
+    }
+    if let Ok(v) = unit.inactive_enter_timestamp().await {
+        info.insert("InactiveEnterTimestamp".to_string(), v.to_string());
+    }
+
+    Ok(info)
+}
+
 /// Check to see if a service is currently active
 pub fn is_service_active(service_info: &HashMap<String, String>) -> bool {
     service_info
@@ -14,8 +132,24 @@ pub fn is_service_active(service_info: &HashMap<String, String>) -> bool {
         .is_some_and(|field| field == "active")
 }
 
-/// Pull state and configuration information about a systemd unit
+/// Pull state and configuration information about a systemd unit, preferring a direct query
+/// over the D-Bus system bus and falling back to parsing `systemctl show` if the bus can't be
+/// reached within [`DBUS_TIMEOUT`]
 pub fn get_service_info(service: &str) -> eyre::Result<HashMap<String, String>> {
+    let info = block_on(async {
+        tokio::time::timeout(DBUS_TIMEOUT, unit_properties(service))
+            .await
+            .map_err(|_| eyre::eyre!("Timed out waiting for the system bus"))?
+    });
+
+    if let Ok(info) = info {
+        return Ok(info);
+    }
+
+    get_service_info_shell(service)
+}
+
+fn get_service_info_shell(service: &str) -> eyre::Result<HashMap<String, String>> {
     let service_info = qx(&format!("systemctl show --no-pager {service}"))
         .context("Could not show service info")?
         .1;
@@ -28,3 +162,97 @@ pub fn get_service_info(service: &str) -> eyre::Result<HashMap<String, String>>
     .map(|[k, v]| (k.trim().to_string(), v.trim().to_string()))
     .collect::<HashMap<_, _>>())
 }
+
+/// Lists the names of every unit currently loaded on the system bus, equivalent to
+/// `systemctl list-units --all --no-legend`
+pub fn list_units() -> eyre::Result<Vec<String>> {
+    block_on(async {
+        let connection = zbus::Connection::system()
+            .await
+            .context("Could not connect to the D-Bus system bus")?;
+        let manager = dbus::ManagerProxy::new(&connection)
+            .await
+            .context("Could not open the systemd1 manager proxy")?;
+
+        Ok(manager
+            .list_units()
+            .await
+            .context("Could not list units")?
+            .into_iter()
+            .map(|(name, ..)| name)
+            .collect())
+    })
+}
+
+/// Starts a unit over the system bus, equivalent to `systemctl start <unit>`
+pub fn start_unit(unit: &str) -> eyre::Result<()> {
+    block_on(async {
+        let connection = zbus::Connection::system()
+            .await
+            .context("Could not connect to the D-Bus system bus")?;
+        let manager = dbus::ManagerProxy::new(&connection)
+            .await
+            .context("Could not open the systemd1 manager proxy")?;
+
+        manager
+            .start_unit(unit, "replace")
+            .await
+            .with_context(|| format!("Could not start {unit}"))?;
+
+        Ok(())
+    })
+}
+
+/// Stops a unit over the system bus, equivalent to `systemctl stop <unit>`
+pub fn stop_unit(unit: &str) -> eyre::Result<()> {
+    block_on(async {
+        let connection = zbus::Connection::system()
+            .await
+            .context("Could not connect to the D-Bus system bus")?;
+        let manager = dbus::ManagerProxy::new(&connection)
+            .await
+            .context("Could not open the systemd1 manager proxy")?;
+
+        manager
+            .stop_unit(unit, "replace")
+            .await
+            .with_context(|| format!("Could not stop {unit}"))?;
+
+        Ok(())
+    })
+}
+
+/// Blocks until `unit`'s `ActiveState` changes, or `timeout` elapses, and returns the new state.
+/// Lets callers react to a service going up or down without polling `get_service_info` in a loop
+pub fn watch_service_state(unit: &str, timeout: Duration) -> eyre::Result<String> {
+    block_on(async {
+        let connection = zbus::Connection::system()
+            .await
+            .context("Could not connect to the D-Bus system bus")?;
+        let manager = dbus::ManagerProxy::new(&connection)
+            .await
+            .context("Could not open the systemd1 manager proxy")?;
+        // Systemd only emits per-unit property change signals to subscribed clients
+        manager
+            .subscribe()
+            .await
+            .context("Could not subscribe to systemd unit changes")?;
+        let unit = unit_proxy(&connection, &manager, unit).await?;
+
+        let mut changes = unit.receive_active_state_changed().await;
+        let change = tokio::time::timeout(timeout, changes.next())
+            .await
+            .map_err(|_| eyre::eyre!("{} did not change state within {timeout:?}", unit.path()))?
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "Lost the system bus connection while watching {}",
+                    unit.path()
+                )
+            })?;
+
+        change
+            .get()
+            .await
+            .context("Could not read the new ActiveState value")
+    })
+}
@@ -0,0 +1,86 @@
+//! Provides a handle for a bundled copy of yara, so `jj yara` can scan files and process memory
+//! against a ruleset regardless of whether the host has yara installed
+//!
+//! ```no_run
+//! # use jj_rs::utils::yara::Yara;
+//! # fn test_yara() -> eyre::Result<()> {
+//! let yara = Yara::new()?;
+//! yara.command().args(["-s", "rules.yar", "/var/www/html"]).output()?;
+//! # Ok(())
+//! # }
+//! # test_yara().expect("could not run yara test");
+//! ```
+use std::{
+    fs::File,
+    io::prelude::*,
+    os::fd::{AsRawFd, FromRawFd, IntoRawFd},
+    process::Command,
+};
+
+use eyre::Context;
+#[cfg(feature = "bundled-tools")]
+use flate2::write::GzDecoder;
+use nix::sys::memfd::{MFdFlags, memfd_create};
+
+#[cfg(feature = "bundled-tools")]
+pub(crate) const YARA_BYTES_X86_64: &[u8] = include_bytes!(std::env!("YARA_GZIPPED_X86_64"));
+#[cfg(feature = "bundled-tools")]
+pub(crate) const YARA_BYTES_AARCH64: &[u8] = include_bytes!(std::env!("YARA_GZIPPED_AARCH64"));
+
+/// Expected SHA-256 hashes of the gzipped payloads above, baked in at build time so `jj verify`
+/// can detect a tampered binary
+#[cfg(feature = "bundled-tools")]
+pub(crate) const YARA_SHA256_X86_64: &str = std::env!("YARA_SHA256_X86_64");
+#[cfg(feature = "bundled-tools")]
+pub(crate) const YARA_SHA256_AARCH64: &str = std::env!("YARA_SHA256_AARCH64");
+
+/// Handle around the `yara` binary
+pub struct Yara {
+    yara_file: File,
+}
+
+impl Yara {
+    /// Create a new yara handle that can be used later to scan files or process memory
+    pub fn new() -> eyre::Result<Self> {
+        let temp_fd =
+            memfd_create("", MFdFlags::empty()).context("Could not create memory file")?;
+
+        let fd = temp_fd.into_raw_fd();
+
+        let mut temp_file = unsafe { File::from_raw_fd(fd) };
+
+        #[cfg(feature = "bundled-tools")]
+        {
+            let yara_bytes = crate::utils::embedded_tool_bytes_for_current_arch(
+                YARA_BYTES_X86_64,
+                YARA_BYTES_AARCH64,
+            )?;
+
+            let mut decoder = GzDecoder::new(temp_file);
+            decoder
+                .write_all(yara_bytes)
+                .context("Could not write all yara bytes")?;
+            temp_file = decoder
+                .finish()
+                .context("Could not finish writing decompressing yara")?;
+        }
+
+        #[cfg(not(feature = "bundled-tools"))]
+        {
+            let yara_bytes = crate::utils::fetch_tool_bytes("yara")?;
+            temp_file
+                .write_all(&yara_bytes)
+                .context("Could not write all yara bytes")?;
+        }
+
+        Ok(Self {
+            yara_file: temp_file,
+        })
+    }
+
+    /// Create a new [`std::process::Command`] object to perform further
+    /// customization around later
+    pub fn command(&self) -> Command {
+        Command::new(format!("/proc/self/fd/{}", self.yara_file.as_raw_fd()))
+    }
+}